@@ -29,4 +29,82 @@ pub enum GameError {
     VaultHasReservedFunds,
     #[msg("Vault capacity exceeded - too many concurrent sessions would risk insolvency")]
     VaultCapacityExceeded,
+    #[msg("Revealed server seed does not match the pending commitment")]
+    CommitmentMismatch,
+    #[msg("VRF round has not been resolved yet")]
+    VrfNotReady,
+    #[msg("Signer is not the house vault's game keeper")]
+    UnauthorizedKeeper,
+    #[msg("Session referrer account was not supplied")]
+    MissingReferrer,
+    #[msg("Game is paused - only cash out and lose_session are allowed")]
+    GamePaused,
+    #[msg("Deposit amount must be greater than zero")]
+    InvalidDepositAmount,
+    #[msg("Signer is not the pending house authority")]
+    NotPendingAuthority,
+    #[msg("Signer is not the pending config admin")]
+    NotPendingAdmin,
+    #[msg("Session index does not match the user's session counter")]
+    StaleSessionIndex,
+    #[msg("Cash out payout is below the requested minimum")]
+    PayoutBelowMinimum,
+    #[msg("Session has not reached the minimum dives required to cash out")]
+    MinDivesNotReached,
+    #[msg("Account was written by a newer program version")]
+    UnsupportedAccountVersion,
+    #[msg("Treasure gamble needs max_dives reached and room below the payout cap")]
+    GambleNotAvailable,
+    #[msg("Bet can only be increased before the first dive")]
+    BetIncreaseNotAllowed,
+    #[msg("User already has the maximum number of active sessions")]
+    TooManyActiveSessions,
+    #[msg("Insurance is disabled, already bought, or no dive is left to insure")]
+    InsuranceNotAvailable,
+    #[msg("Insurance cost is below the premium for this dive")]
+    InsurancePremiumTooLow,
+    #[msg("Full coverage is enforced, so the vault must be seeded at init")]
+    VaultSeedRequired,
+    #[msg("Max dives override needs the house authority's signature and must stay within absolute_max_dives")]
+    InvalidMaxDivesOverride,
+    #[msg("First dive played before min_slots_before_play elapsed")]
+    PlayedTooSoon,
+    #[msg("House withdrawals are timelocked, use request_withdraw")]
+    WithdrawalTimelocked,
+    #[msg("No house withdrawal is pending")]
+    NoPendingWithdrawal,
+    #[msg("Pending house withdrawal is still locked")]
+    WithdrawalNotUnlocked,
+    #[msg("Withdrawal amount must be positive")]
+    InvalidWithdrawAmount,
+    #[msg("House withdrawal requires the vault cosigner's signature")]
+    MissingCosigner,
+    #[msg("Bet exceeds the share of the vault balance allowed by max_bet_vault_bps")]
+    BetExceedsVaultCap,
+    #[msg("Price feed account is missing, unexpected or malformed")]
+    InvalidPriceFeed,
+    #[msg("Price feed is older than max_price_age_secs")]
+    StalePriceFeed,
+    #[msg("No bonus is locked for this player")]
+    NoBonusLocked,
+    #[msg("Bonus wagering requirement not met yet")]
+    BonusWageringNotMet,
+    #[msg("Player already holds a bonus from another vault")]
+    BonusVaultMismatch,
+    #[msg("Boost is disabled, too large, already pending, or no dive is left to boost")]
+    BoostNotAvailable,
+    #[msg("Boost cost is below its price for this treasure")]
+    BoostCostTooLow,
+    #[msg("Signer is not the vault's authorized crank")]
+    UnauthorizedCrank,
+    #[msg("Free vault balance is above the dust threshold, use withdraw_house")]
+    NotDust,
+    #[msg("Referrer can't be the player or the house authority")]
+    SelfReferral,
+    #[msg("A VRF request is pending; play it with play_round_vrf first")]
+    VrfRequestPending,
+    #[msg("VRF round is not the one recorded for this session")]
+    StaleVrfRequest,
+    #[msg("VRF round already has a result; request a new one")]
+    VrfAlreadyResolved,
 }
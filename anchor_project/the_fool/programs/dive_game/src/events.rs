@@ -1,9 +1,13 @@
+use crate::states::{GameConfig, SessionSnapshot, VaultHealth};
 use anchor_lang::prelude::*;
 #[event]
 pub struct InitializeHouseVaultEvent {
     pub house_vault: Pubkey,
     pub house_authority: Pubkey,
+    pub game_keeper: Pubkey,
     pub locked: bool,
+    /// Playable lamports moved in on top of rent
+    pub seed_amount: u64,
     pub timestamp: i64,
 }
 #[event]
@@ -12,6 +16,20 @@ pub struct SessionStartedEvent {
     pub user: Pubkey,
     pub house_vault: Pubkey,
     pub bet_amount: u64,
+    pub rake_amount: u64,
+    pub max_payout: u64,
+    /// Vault-wide order of game events, see HouseVault::next_sequence
+    pub sequence: u64,
+    pub timestamp: i64,
+    /// GameSession.client_tag
+    pub client_tag: [u8; 16],
+}
+#[event]
+pub struct BetIncreasedEvent {
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub additional: u64,
+    pub bet_amount: u64,
     pub max_payout: u64,
     pub timestamp: i64,
 }
@@ -21,6 +39,38 @@ pub struct RoundPlayedEvent {
     pub user: Pubkey,
     pub dive_number: u16,
     pub current_treasure: u64,
+    /// Vault-wide order of game events, see HouseVault::next_sequence
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+/// Emitted for every roll so clients can check `roll < survival_threshold` independently
+#[event]
+pub struct RoundRolledEvent {
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub dive_number: u16,
+    pub roll: u32,
+    pub survival_threshold: u32,
+    pub survived: bool,
+    pub timestamp: i64,
+}
+/// Emitted when a session closes with the last server seed the keeper revealed
+/// Keepers may use a new seed every round, so replays go through RoundSeedRevealedEvent
+#[event]
+pub struct RevealedSeedEvent {
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub server_seed: u64,
+    pub timestamp: i64,
+}
+/// Emitted for every keeper reveal so players can replay each round's roll
+/// The roll is `rng::random_roll_from_slots(slot_hash, session, server_seed, dive_number)`
+#[event]
+pub struct RoundSeedRevealedEvent {
+    pub session: Pubkey,
+    pub dive_number: u16,
+    pub server_seed: u64,
+    pub slot_hash: [u8; 32],
     pub timestamp: i64,
 }
 #[event]
@@ -30,6 +80,30 @@ pub struct SessionLostEvent {
     pub house_vault: Pubkey,
     pub bet_amount: u64,
     pub final_dive_number: u16,
+    /// Paid back to the player when the failed dive was insured
+    pub insurance_refund: u64,
+    /// Cashback of loss_rebate_bps of the bet
+    pub rebate: u64,
+    /// Vault-wide order of game events, see HouseVault::next_sequence
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct InsuranceBoughtEvent {
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub dive_number: u16,
+    pub cost: u64,
+    pub coverage: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct BoostAppliedEvent {
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub dive_number: u16,
+    pub boost_bps: u32,
+    pub cost: u64,
     pub timestamp: i64,
 }
 #[event]
@@ -38,7 +112,124 @@ pub struct SessionCashedOutEvent {
     pub user: Pubkey,
     pub house_vault: Pubkey,
     pub payout_amount: u64,
+    pub referral_fee: u64,
+    /// payout_amount at the session's locked price, 0 unless the config is USD-denominated
+    pub payout_usd_cents: u64,
     pub final_dive_number: u16,
+    /// Vault-wide order of game events, see HouseVault::next_sequence
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct JackpotWonEvent {
+    pub house_vault: Pubkey,
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+/// Emitted right before a cash-out fails because the vault can't cover the treasure
+#[event]
+pub struct VaultInsolventEvent {
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub house_vault: Pubkey,
+    pub owed_amount: u64,
+    pub available_balance: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct TreasureGambledEvent {
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub roll: u32,
+    pub win_threshold: u32,
+    pub won: bool,
+    pub current_treasure: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct PartialCashOutEvent {
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub house_vault: Pubkey,
+    pub amount: u64,
+    pub remaining_treasure: u64,
+    pub dive_number: u16,
+    /// Vault-wide order of game events, see HouseVault::next_sequence
+    pub sequence: u64,
+    pub timestamp: i64,
+}
+/// Emitted by init_config and update_config with the resulting parameters
+#[event]
+pub struct ConfigUpdatedEvent {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub settings: GameConfig,
+    pub timestamp: i64,
+}
+#[event]
+pub struct AdminProposedEvent {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+    pub timestamp: i64,
+}
+#[event]
+pub struct AdminTransferredEvent {
+    pub config: Pubkey,
+    pub old_admin: Pubkey,
+    pub new_admin: Pubkey,
+    pub timestamp: i64,
+}
+/// Mirrors get_max_dives' return value for clients that can't read return data
+#[event]
+pub struct MaxDivesViewEvent {
+    pub bet_amount: u64,
+    pub max_dives: u16,
+}
+/// Mirrors get_survival_probability's return value, plus the odds one dive further
+#[event]
+pub struct SurvivalProbabilityViewEvent {
+    pub dive_number: u16,
+    pub survival_bps: u32,
+    pub next_dive_survival_bps: u32,
+}
+/// Mirrors get_next_treasure's return value
+#[event]
+pub struct NextTreasureViewEvent {
+    pub session: Pubkey,
+    pub dive_number: u16,
+    pub next_treasure: u64,
+}
+#[event]
+pub struct VrfRequestedEvent {
+    pub session: Pubkey,
+    pub vrf: Pubkey,
+    pub dive_number: u16,
+    pub request_slot: u64,
+}
+/// Mirrors verify_outcome's return value
+#[event]
+pub struct OutcomeVerifiedEvent {
+    pub session: Pubkey,
+    pub dive_number: u16,
+    pub claimed_roll: u32,
+    pub valid: bool,
+}
+#[event]
+pub struct GamePausedEvent {
+    pub config: Pubkey,
+    pub admin: Pubkey,
+    pub paused: bool,
+    pub timestamp: i64,
+}
+#[event]
+pub struct HouseDepositEvent {
+    pub house_vault: Pubkey,
+    pub depositor: Pubkey,
+    pub amount: u64,
+    pub new_balance: u64,
     pub timestamp: i64,
 }
 #[event]
@@ -49,10 +240,177 @@ pub struct ToggleHouseLockEvent {
     pub timestamp: i64,
 }
 #[event]
+pub struct HouseAuthorityProposedEvent {
+    pub house_vault: Pubkey,
+    pub house_authority: Pubkey,
+    pub pending_authority: Pubkey,
+    pub timestamp: i64,
+}
+#[event]
+pub struct HouseAuthorityTransferredEvent {
+    pub house_vault: Pubkey,
+    pub old_house_authority: Pubkey,
+    pub new_house_authority: Pubkey,
+    pub timestamp: i64,
+}
+#[event]
+pub struct GameKeeperUpdatedEvent {
+    pub house_vault: Pubkey,
+    pub house_authority: Pubkey,
+    pub old_game_keeper: Pubkey,
+    pub new_game_keeper: Pubkey,
+    pub timestamp: i64,
+}
+#[event]
+pub struct CosignerUpdatedEvent {
+    pub house_vault: Pubkey,
+    pub old_cosigner: Option<Pubkey>,
+    pub new_cosigner: Option<Pubkey>,
+    pub timestamp: i64,
+}
+/// The vault locked itself after more than max_consecutive_big_payouts big payouts in a row
+#[event]
+pub struct CircuitBreakerTrippedEvent {
+    pub house_vault: Pubkey,
+    pub consecutive_big_payouts: u16,
+    pub last_payout: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct DustSweptEvent {
+    pub house_vault: Pubkey,
+    pub house_authority: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct AuthorizedCrankUpdatedEvent {
+    pub house_vault: Pubkey,
+    pub old_crank: Option<Pubkey>,
+    pub new_crank: Option<Pubkey>,
+    pub timestamp: i64,
+}
+#[event]
+pub struct SessionKeeperReboundEvent {
+    pub session: Pubkey,
+    pub house_vault: Pubkey,
+    pub old_keeper: Pubkey,
+    pub new_keeper: Pubkey,
+    pub timestamp: i64,
+}
+#[event]
+pub struct WithdrawRequestedEvent {
+    pub house_vault: Pubkey,
+    pub amount: u64,
+    pub unlock_slot: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct WithdrawExecutedEvent {
+    pub house_vault: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct WithdrawCancelledEvent {
+    pub house_vault: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct SessionSimulatedEvent {
+    pub bet_amount: u64,
+    pub server_seed: u64,
+    pub lost: bool,
+    pub final_dive: u16,
+}
+#[event]
+pub struct BonusGrantedEvent {
+    pub house_vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub bonus_locked_lamports: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct BonusClaimedEvent {
+    pub house_vault: Pubkey,
+    pub user: Pubkey,
+    pub amount: u64,
+    pub total_bet: u64,
+    pub timestamp: i64,
+}
+#[event]
 pub struct SessionCleanedEvent {
     pub session: Pubkey,
     pub user: Pubkey,
     pub crank: Pubkey,
     pub released_amount: u64,
+    pub refunded_amount: u64,
     pub slots_inactive: u64,
+    /// Paid to the crank from the vault on top of the session's rent
+    pub crank_reward: u64,
+}
+#[event]
+pub struct SessionAdminClosedEvent {
+    pub session: Pubkey,
+    pub user: Pubkey,
+    pub admin: Pubkey,
+    pub released_amount: u64,
+    pub refunded_amount: u64,
+    pub timestamp: i64,
+}
+#[event]
+pub struct ReservedReconciledEvent {
+    pub house_vault: Pubkey,
+    pub house_authority: Pubkey,
+    pub previous_reserved: u64,
+    pub new_reserved: u64,
+    pub session_count: u32,
+    pub timestamp: i64,
+}
+#[event]
+pub struct SessionSnapshotEvent {
+    pub snapshot: SessionSnapshot,
+}
+#[event]
+pub struct VaultHealthEvent {
+    pub house_vault: Pubkey,
+    pub health: VaultHealth,
+}
+/// Which way a VaultFlowEvent moved lamports, seen from the vault
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlowDirection {
+    In,
+    Out,
+}
+/// What a vault lamport movement paid for
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FlowReason {
+    Seed,
+    Deposit,
+    Bet,
+    BetIncrease,
+    Insurance,
+    Boost,
+    CashOut,
+    PartialCashOut,
+    ReferralFee,
+    LossRefund,
+    ExpiryRefund,
+    CrankReward,
+    Withdrawal,
+    BonusClaim,
+    DustSweep,
+}
+/// Emitted for every lamport movement into or out of a vault, so its balance can be rebuilt
+/// from logs alone
+#[event]
+pub struct VaultFlowEvent {
+    pub vault: Pubkey,
+    pub direction: FlowDirection,
+    pub amount: u64,
+    pub reason: FlowReason,
+    pub balance_after: u64,
 }
@@ -1,38 +1,221 @@
-use crate::states::GameConfig;
+use crate::states::{CurveMode, DecayMode, GameConfig, ReservationMode, RoundingMode};
 pub fn survival_probability_bps(config: &GameConfig, dive_number: u16) -> u32 {
-    let reduction =
-        (dive_number.saturating_sub(1) as u32).saturating_mul(config.decay_per_dive_ppm);
-    config
-        .base_survival_ppm
-        .saturating_sub(reduction)
-        .max(config.min_survival_ppm)
+    if config.first_dive_guaranteed && dive_number == 1 {
+        return 1_000_000;
+    }
+    let decayed = match config.decay_mode {
+        DecayMode::Linear => {
+            let reduction =
+                (dive_number.saturating_sub(1) as u32).saturating_mul(config.decay_per_dive_ppm);
+            config.base_survival_ppm.saturating_sub(reduction)
+        }
+        DecayMode::Exponential => exponential_survival(config, dive_number),
+    };
+    decayed.max(config.min_survival_ppm)
+}
+fn exponential_survival(config: &GameConfig, dive_number: u16) -> u32 {
+    let keep = 1_000_000u64.saturating_sub(config.decay_per_dive_ppm as u64);
+    let mut survival = config.base_survival_ppm as u64;
+    for _ in 1..dive_number {
+        // Once at the floor further dives can't change the result
+        if survival <= config.min_survival_ppm as u64 {
+            break;
+        }
+        survival = survival * keep / 1_000_000;
+    }
+    survival as u32
 }
+/// Treasure after `dive_number` dives, net of the configured house edge
 pub fn treasure_for_dive(config: &GameConfig, bet_amount: u64, dive_number: u16) -> u64 {
     if dive_number == 0 {
         return bet_amount;
     }
+    apply_house_edge(
+        config,
+        gross_treasure_for_dive(config, bet_amount, dive_number),
+    )
+}
+/// Multiplies a payout by (1_000_000 - house_edge_bps) / 1_000_000
+pub fn apply_house_edge(config: &GameConfig, payout: u64) -> u64 {
+    let keep = 1_000_000u128.saturating_sub(config.house_edge_bps as u128);
+    // Rounding up can't pass the payout itself since keep <= 1_000_000
+    mul_div(config, payout as u128, keep, 1_000_000).unwrap_or(0) as u64
+}
+/// value * num / den under the config's rounding mode, None on overflow or a zero den
+fn mul_div(config: &GameConfig, value: u128, num: u128, den: u128) -> Option<u128> {
+    let product = value.checked_mul(num)?;
+    match config.rounding_mode {
+        RoundingMode::Floor => product.checked_div(den),
+        RoundingMode::Nearest => product.checked_add(den / 2)?.checked_div(den),
+    }
+}
+/// Referrer share of a payout, referral_fee_bps out of 1_000_000
+pub fn referral_fee(config: &GameConfig, payout: u64) -> u64 {
+    (payout as u128 * config.referral_fee_bps as u128 / 1_000_000) as u64
+}
+/// Raw curve capped at max payout, before the house edge
+fn gross_treasure_for_dive(config: &GameConfig, bet_amount: u64, dive_number: u16) -> u64 {
+    if dive_number == 0 {
+        return bet_amount;
+    }
+    match config.curve_mode {
+        CurveMode::Geometric => geometric_treasure(config, bet_amount, dive_number),
+        CurveMode::Linear => linear_treasure(config, bet_amount, dive_number),
+    }
+}
+fn geometric_treasure(config: &GameConfig, bet_amount: u64, dive_number: u16) -> u64 {
     let scale = config.treasure_multiplier_den as u128;
     let mult = config.treasure_multiplier_num as u128;
     let max = max_payout_for_bet(config, bet_amount);
     let mut result = bet_amount as u128;
     for _ in 0..dive_number {
-        result = result
-            .checked_mul(mult)
-            .and_then(|v| v.checked_div(scale))
-            .unwrap_or(max as u128);
+        result = mul_div(config, result, mult, scale).unwrap_or(max as u128);
         if result >= max as u128 {
             return max;
         }
     }
     result.min(max as u128) as u64
 }
+fn linear_treasure(config: &GameConfig, bet_amount: u64, dive_number: u16) -> u64 {
+    let max = max_payout_for_bet(config, bet_amount) as u128;
+    let step = mul_div(
+        config,
+        bet_amount as u128,
+        config.linear_step_bps as u128,
+        1_000_000,
+    )
+    .unwrap_or(max);
+    let result = (bet_amount as u128).saturating_add(step.saturating_mul(dive_number as u128));
+    result.min(max) as u64
+}
+/// Gross curve value at `dive_number`, stepping once from `previous` (the value at
+/// `dive_number - 1`) when the caller has it cached instead of replaying the whole curve.
+/// Bit-identical to the from-scratch computation
+pub fn gross_treasure_from(
+    config: &GameConfig,
+    bet_amount: u64,
+    dive_number: u16,
+    previous: Option<u64>,
+) -> u64 {
+    match (config.curve_mode, previous) {
+        (CurveMode::Geometric, Some(previous)) if dive_number > 0 => {
+            geometric_step(config, bet_amount, dive_number, previous)
+        }
+        _ => gross_treasure_for_dive(config, bet_amount, dive_number),
+    }
+}
+/// One iteration of geometric_treasure's loop; the cap is absorbing once a dive has hit it
+fn geometric_step(config: &GameConfig, bet_amount: u64, dive_number: u16, previous: u64) -> u64 {
+    let max = max_payout_for_bet(config, bet_amount);
+    if dive_number > 1 && previous >= max {
+        return max;
+    }
+    mul_div(
+        config,
+        previous as u128,
+        config.treasure_multiplier_num as u128,
+        config.treasure_multiplier_den as u128,
+    )
+    .unwrap_or(max as u128)
+    .min(max as u128) as u64
+}
+/// Lamports worth `usd_cents` at a SOL price of `price_micro_usd`, rounded down
+/// None for a zero price or a result past u64
+pub fn usd_cents_to_lamports(usd_cents: u64, price_micro_usd: u64) -> Option<u64> {
+    let lamports =
+        (usd_cents as u128 * 10_000 * 1_000_000_000).checked_div(price_micro_usd as u128)?;
+    u64::try_from(lamports).ok()
+}
+/// USD cents worth `lamports` at a SOL price of `price_micro_usd`, rounded down
+pub fn lamports_to_usd_cents(lamports: u64, price_micro_usd: u64) -> u64 {
+    (lamports as u128 * price_micro_usd as u128 / (10_000 * 1_000_000_000)) as u64
+}
+/// Treasure raised by milestone_bonus_bps, never past `cap`
+pub fn with_milestone_bonus(config: &GameConfig, treasure: u64, cap: u64) -> u64 {
+    let boosted = treasure as u128 * (1_000_000 + config.milestone_bonus_bps as u128) / 1_000_000;
+    boosted.min(cap as u128) as u64
+}
+/// Cashback paid from the vault when a session with `bet_amount` is lost
+pub fn loss_rebate(config: &GameConfig, bet_amount: u64) -> u64 {
+    (bet_amount as u128 * config.loss_rebate_bps as u128 / 1_000_000) as u64
+}
+/// Refund paid by insurance when the insured dive fails
+pub fn insurance_refund(config: &GameConfig, treasure: u64) -> u64 {
+    (treasure as u128 * config.insurance_payout_bps as u128 / 1_000_000) as u64
+}
+/// Fair price of insuring `treasure` on `dive_number`: the refund weighted by the chance of
+/// losing that dive, rounded up
+pub fn insurance_premium(config: &GameConfig, treasure: u64, dive_number: u16) -> u64 {
+    let loss_ppm = 1_000_000u128 - survival_probability_bps(config, dive_number) as u128;
+    (insurance_refund(config, treasure) as u128 * loss_ppm).div_ceil(1_000_000) as u64
+}
+/// Survival threshold with a one-dive boost added, capped at certain survival
+pub fn boosted_survival_bps(survival_bps: u32, boost_bps: u32) -> u32 {
+    survival_bps.saturating_add(boost_bps).min(1_000_000)
+}
+/// Least apply_boost accepts for `boost_bps`: the treasure at stake weighted by the extra
+/// survival chance, rounded up
+pub fn boost_price(treasure: u64, boost_bps: u32) -> u64 {
+    (treasure as u128 * boost_bps as u128).div_ceil(1_000_000) as u64
+}
+/// House rake taken from a bet at session start, rake_bps out of 1_000_000
+pub fn rake_for_bet(config: &GameConfig, bet_amount: u64) -> u64 {
+    (bet_amount as u128 * config.rake_bps as u128 / 1_000_000) as u64
+}
+/// Jackpot pool contribution taken from a bet at session start, jackpot_bps out of 1_000_000
+pub fn jackpot_for_bet(config: &GameConfig, bet_amount: u64) -> u64 {
+    (bet_amount as u128 * config.jackpot_bps as u128 / 1_000_000) as u64
+}
+/// Part of the bet left after the rake and jackpot share; the treasure curve is based on it
+pub fn effective_bet(config: &GameConfig, bet_amount: u64) -> u64 {
+    bet_amount - rake_for_bet(config, bet_amount) - jackpot_for_bet(config, bet_amount)
+}
+/// Multiplier cap, further clamped by max_payout_lamports when that is set
 pub fn max_payout_for_bet(config: &GameConfig, bet_amount: u64) -> u64 {
-    bet_amount.saturating_mul(config.max_payout_multiplier as u64)
+    let max = bet_amount.saturating_mul(config.max_payout_multiplier as u64);
+    match config.max_payout_lamports {
+        0 => max,
+        cap => max.min(cap),
+    }
+}
+/// Largest probability-weighted payout on the curve: for every dive a player could cash out
+/// at, its treasure times the chance of surviving each dive before it
+/// Boosts and insurance are left out; never below the bet nor above max_payout
+pub fn expected_payout_for_bet(config: &GameConfig, bet_amount: u64, max_dives: u16) -> u64 {
+    let max = max_payout_for_bet(config, bet_amount);
+    let cap = apply_house_edge(config, max);
+    let mut reach_bps = 1_000_000u128;
+    let mut best = bet_amount as u128 * 1_000_000;
+    let mut previous = None;
+    for dive in 2..=max_dives {
+        // Rounded up so the reservation errs towards covering more
+        reach_bps =
+            (reach_bps * survival_probability_bps(config, dive - 1) as u128).div_ceil(1_000_000);
+        if reach_bps == 0 {
+            break;
+        }
+        let gross = gross_treasure_from(config, bet_amount, dive, previous);
+        previous = Some(gross);
+        let mut treasure = apply_house_edge(config, gross);
+        // Same point play_round applies the bonus: once the milestone dive is survived
+        if config.milestone_reached(dive - 1) {
+            treasure = with_milestone_bonus(config, treasure, cap);
+        }
+        best = best.max(treasure as u128 * reach_bps);
+    }
+    (best.div_ceil(1_000_000) as u64).min(max)
+}
+/// Lamports start_session reserves for a session with this effective bet
+pub fn reservation_for_bet(config: &GameConfig, bet_amount: u64, max_dives: u16) -> u64 {
+    match config.reservation_mode {
+        ReservationMode::Full => max_payout_for_bet(config, bet_amount),
+        ReservationMode::Expected => expected_payout_for_bet(config, bet_amount, max_dives),
+    }
 }
 pub fn max_dives_for_bet(config: &GameConfig, bet_amount: u64) -> u16 {
     let max = max_payout_for_bet(config, bet_amount);
     let mut dive = 1u16;
-    while treasure_for_dive(config, bet_amount, dive) < max && dive < config.max_dives {
+    while gross_treasure_for_dive(config, bet_amount, dive) < max && dive < config.max_dives {
         dive += 1;
     }
     dive
@@ -40,23 +223,11 @@ pub fn max_dives_for_bet(config: &GameConfig, bet_amount: u64) -> u16 {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use anchor_lang::prelude::Pubkey;
+    use crate::states::ReservationMode;
     use rstest::rstest;
     fn test_config() -> GameConfig {
-        let (base, decay, min, num, den, max_mult, _max_dives, fixed_bet) =
-            GameConfig::default_config();
-        GameConfig {
-            admin: Pubkey::default(),
-            base_survival_ppm: base,
-            decay_per_dive_ppm: decay,
-            min_survival_ppm: min,
-            treasure_multiplier_num: num,
-            treasure_multiplier_den: den,
-            max_payout_multiplier: max_mult,
-            max_dives: 50, // Use higher limit for math tests (production uses 5)
-            fixed_bet,
-            bump: 0,
-        }
+        // Use higher limit for math tests (production uses 5)
+        crate::test_utils::test_config(50)
     }
     #[rstest]
     #[case(1, 700_000)]
@@ -111,6 +282,62 @@ mod tests {
         let _ = survival_probability_bps(&config, dive);
     }
     #[rstest]
+    #[case(RoundingMode::Floor, 7, 2, 24)]
+    #[case(RoundingMode::Nearest, 7, 2, 25)]
+    #[case(RoundingMode::Floor, 7, 3, 45)]
+    #[case(RoundingMode::Nearest, 7, 3, 48)]
+    #[case(RoundingMode::Floor, 1_000_005, 4, 13_032_160)]
+    #[case(RoundingMode::Nearest, 1_000_005, 4, 13_032_168)]
+    // Exact products don't depend on the mode
+    #[case(RoundingMode::Floor, 1_000_000, 5, 24_760_990)]
+    #[case(RoundingMode::Nearest, 1_000_000, 5, 24_760_990)]
+    fn test_treasure_rounding_modes(
+        #[case] rounding_mode: RoundingMode,
+        #[case] bet: u64,
+        #[case] dive: u16,
+        #[case] expected: u64,
+    ) {
+        let config = GameConfig {
+            rounding_mode,
+            ..test_config()
+        };
+        assert_eq!(treasure_for_dive(&config, bet, dive), expected);
+    }
+    #[rstest]
+    #[case(RoundingMode::Floor, 1, 0)]
+    #[case(RoundingMode::Nearest, 1, 1)]
+    #[case(RoundingMode::Floor, 3, 1)]
+    #[case(RoundingMode::Nearest, 3, 2)]
+    #[case(RoundingMode::Nearest, u64::MAX, u64::MAX / 2 + 1)]
+    fn test_house_edge_rounding_at_half(
+        #[case] rounding_mode: RoundingMode,
+        #[case] payout: u64,
+        #[case] expected: u64,
+    ) {
+        let config = GameConfig {
+            rounding_mode,
+            house_edge_bps: 500_000,
+            ..test_config()
+        };
+        assert_eq!(apply_house_edge(&config, payout), expected);
+    }
+    #[test]
+    fn test_floor_rounding_matches_truncating_math() {
+        let config = GameConfig {
+            house_edge_bps: 20_000,
+            ..test_config()
+        };
+        let max_mult = config.max_payout_multiplier as u128;
+        for bet in [1u64, 7, 999_999, 1_000_005, 123_456_789] {
+            let mut gross = bet as u128;
+            for dive in 1..=12u16 {
+                gross = (gross * 19 / 10).min(bet as u128 * max_mult);
+                let truncated = (gross * 980_000 / 1_000_000) as u64;
+                assert_eq!(treasure_for_dive(&config, bet, dive), truncated);
+            }
+        }
+    }
+    #[rstest]
     #[case(1_000_000, 1, 1_900_000)]
     #[case(1_000_000, 2, 3_610_000)]
     #[case(1_000_000, 5, 24_760_990)]
@@ -178,6 +405,18 @@ mod tests {
         assert!(max > 0);
     }
     #[rstest]
+    #[case(1_000_000, 100_000_000)]
+    #[case(10_000_000_000, 50_000_000_000)]
+    #[case(u64::MAX / 1000, 50_000_000_000)]
+    fn test_max_payout_clamped_by_absolute_cap(#[case] bet: u64, #[case] expected: u64) {
+        let config = GameConfig {
+            max_payout_lamports: 50_000_000_000,
+            ..test_config()
+        };
+        assert_eq!(max_payout_for_bet(&config, bet), expected);
+        assert!(treasure_for_dive(&config, bet, 200) <= expected);
+    }
+    #[rstest]
     #[case(0, 1_000_000, 1_000_000)]
     #[case(1, 0, 0)]
     #[case(5, 0, 0)]
@@ -289,6 +528,35 @@ mod tests {
             );
         }
     }
+    #[rstest]
+    #[case(DecayMode::Linear)]
+    #[case(DecayMode::Exponential)]
+    fn test_survival_decay_modes_decrease_to_floor(#[case] decay_mode: DecayMode) {
+        let config = GameConfig {
+            decay_mode,
+            ..test_config()
+        };
+        let mut prev = survival_probability_bps(&config, 1);
+        assert_eq!(prev, config.base_survival_ppm);
+        for dive in 2..=1_000 {
+            let p = survival_probability_bps(&config, dive);
+            assert!(p <= prev, "Dive {dive}: {p} rose above {prev}");
+            assert!(p >= config.min_survival_ppm, "Dive {dive}: {p} below floor");
+            prev = p;
+        }
+        assert_eq!(prev, config.min_survival_ppm);
+    }
+    #[rstest]
+    #[case(2, 694_400)]
+    #[case(3, 688_844)]
+    #[case(1000, 50_000)]
+    fn test_exponential_survival_specific_dives(#[case] dive: u16, #[case] expected: u32) {
+        let config = GameConfig {
+            decay_mode: DecayMode::Exponential,
+            ..test_config()
+        };
+        assert_eq!(survival_probability_bps(&config, dive), expected);
+    }
     #[test]
     fn test_survival_probability_step_is_bounded() {
         let config = test_config();
@@ -356,6 +624,202 @@ mod tests {
         );
     }
     #[test]
+    fn test_zero_house_edge_keeps_curve() {
+        let config = test_config();
+        let bet = 1_000_000;
+        for dive in 0..=20 {
+            assert_eq!(
+                treasure_for_dive(&config, bet, dive),
+                gross_treasure_for_dive(&config, bet, dive)
+            );
+        }
+    }
+    #[rstest]
+    #[case(10_000, 1_900_000, 1_881_000)]
+    #[case(500_000, 1_900_000, 950_000)]
+    #[case(1_000_000, 1_900_000, 0)]
+    fn test_house_edge_haircut(#[case] edge: u32, #[case] gross: u64, #[case] expected: u64) {
+        let mut config = test_config();
+        config.house_edge_bps = edge;
+        assert_eq!(treasure_for_dive(&config, 1_000_000, 1), expected);
+        assert_eq!(apply_house_edge(&config, gross), expected);
+    }
+    #[test]
+    fn test_house_edge_does_not_change_max_dives() {
+        let mut config = test_config();
+        let bet = 1_000_000;
+        let without_edge = max_dives_for_bet(&config, bet);
+        config.house_edge_bps = 50_000;
+        assert_eq!(max_dives_for_bet(&config, bet), without_edge);
+    }
+    #[test]
+    fn test_house_edge_makes_always_continue_negative_ev() {
+        let mut config = test_config();
+        config.house_edge_bps = 800_000;
+        let bet = 1_000_000u64;
+        let max_dive = max_dives_for_bet(&config, bet);
+        let mut prob_survive_all = 1_000_000u128;
+        for d in 1..=max_dive {
+            let p = survival_probability_bps(&config, d) as u128;
+            prob_survive_all = prob_survive_all * p / 1_000_000;
+        }
+        let payout = treasure_for_dive(&config, bet, max_dive) as u128;
+        let ev = prob_survive_all * payout / 1_000_000;
+        assert!(
+            ev < bet as u128,
+            "House edge should make always-continue -EV: EV={ev}, bet={bet}"
+        );
+    }
+    fn linear_config() -> GameConfig {
+        let mut config = test_config();
+        config.curve_mode = CurveMode::Linear;
+        config.linear_step_bps = 500_000;
+        config
+    }
+    #[rstest]
+    #[case(1_000_000, 1, 1_900_000)]
+    #[case(1_000_000, 2, 3_610_000)]
+    #[case(1_000_000, 3, 6_859_000)]
+    #[case(1_000_000, 5, 24_760_990)]
+    #[case(1_000_000, 8, 100_000_000)]
+    #[case(500_000, 3, 3_429_500)]
+    fn test_geometric_mode_exact_values(
+        #[case] bet: u64,
+        #[case] dive: u16,
+        #[case] expected: u64,
+    ) {
+        let config = test_config();
+        assert_eq!(config.curve_mode, CurveMode::Geometric);
+        assert_eq!(treasure_for_dive(&config, bet, dive), expected);
+    }
+    #[rstest]
+    #[case(1_000_000, 1, 1_500_000)]
+    #[case(1_000_000, 2, 2_000_000)]
+    #[case(1_000_000, 10, 6_000_000)]
+    #[case(1_000_000, 198, 100_000_000)]
+    #[case(1_000_000, 500, 100_000_000)]
+    fn test_linear_mode_values(#[case] bet: u64, #[case] dive: u16, #[case] expected: u64) {
+        let config = linear_config();
+        assert_eq!(treasure_for_dive(&config, bet, dive), expected);
+    }
+    #[rstest]
+    #[case(CurveMode::Geometric)]
+    #[case(CurveMode::Linear)]
+    fn test_curve_modes_monotonic_and_capped(#[case] mode: CurveMode) {
+        let mut config = linear_config();
+        config.curve_mode = mode;
+        for bet in [1_000u64, 1_000_000, 10_000_000_000] {
+            let max = max_payout_for_bet(&config, bet);
+            let mut prev = 0;
+            for dive in 0..=300 {
+                let treasure = treasure_for_dive(&config, bet, dive);
+                assert!(treasure >= prev, "{mode:?} decreased at dive {dive}");
+                assert!(treasure <= max, "{mode:?} exceeded cap at dive {dive}");
+                prev = treasure;
+            }
+        }
+    }
+    #[rstest]
+    #[case(0, 10_000_000, 0)]
+    #[case(50_000, 10_000_000, 500_000)]
+    #[case(10_000, 1_234_567, 12_345)]
+    #[case(1_000_000, 10_000_000, 10_000_000)]
+    #[case(100_000, u64::MAX, u64::MAX / 10)]
+    fn test_referral_fee(#[case] fee_bps: u32, #[case] payout: u64, #[case] expected: u64) {
+        let mut config = test_config();
+        config.referral_fee_bps = fee_bps;
+        assert_eq!(referral_fee(&config, payout), expected);
+    }
+    #[test]
+    fn test_rake_reduces_effective_bet_and_max_payout() {
+        let mut config = test_config();
+        assert_eq!(effective_bet(&config, 10_000_000), 10_000_000);
+        config.rake_bps = 20_000; // 2%
+        assert_eq!(rake_for_bet(&config, 10_000_000), 200_000);
+        let effective = effective_bet(&config, 10_000_000);
+        assert_eq!(effective, 9_800_000);
+        assert_eq!(
+            max_payout_for_bet(&config, effective),
+            9_800_000 * config.max_payout_multiplier as u64
+        );
+        assert_eq!(treasure_for_dive(&config, effective, 0), effective);
+        assert_eq!(effective_bet(&config, u64::MAX), u64::MAX - u64::MAX / 50);
+    }
+    #[test]
+    fn test_usd_conversion_at_mocked_price() {
+        // $150 per SOL: a $15.00 bet is 0.1 SOL
+        let price = 150_000_000;
+        assert_eq!(usd_cents_to_lamports(1_500, price), Some(100_000_000));
+        assert_eq!(lamports_to_usd_cents(100_000_000, price), 1_500);
+        // Round trip never pays out more than the bet was worth
+        let lamports = usd_cents_to_lamports(333, 123_456_789).unwrap();
+        assert!(lamports_to_usd_cents(lamports, 123_456_789) <= 333);
+        assert_eq!(usd_cents_to_lamports(1_500, 0), None);
+        assert_eq!(lamports_to_usd_cents(100_000_000, 0), 0);
+    }
+
+    #[test]
+    fn test_expected_payout_is_bounded_by_bet_and_max_payout() {
+        let config = test_config();
+        let bet = 10_000_000;
+        let expected = expected_payout_for_bet(&config, bet, config.max_dives);
+        assert!(expected >= bet);
+        assert!(expected < max_payout_for_bet(&config, bet));
+        // Every cash-out dive is covered in expectation
+        let mut reach = 1.0f64;
+        for dive in 2..=config.max_dives {
+            reach *= survival_probability_bps(&config, dive - 1) as f64 / 1_000_000.0;
+            assert!(treasure_for_dive(&config, bet, dive) as f64 * reach <= expected as f64);
+        }
+    }
+    #[test]
+    fn test_expected_payout_without_risk_is_the_curve() {
+        let config = GameConfig {
+            base_survival_ppm: 1_000_000,
+            decay_per_dive_ppm: 0,
+            min_survival_ppm: 1_000_000,
+            ..test_config()
+        };
+        let bet = 10_000_000;
+        assert_eq!(
+            expected_payout_for_bet(&config, bet, 6),
+            treasure_for_dive(&config, bet, 6)
+        );
+        assert_eq!(expected_payout_for_bet(&config, bet, 1), bet);
+    }
+    #[test]
+    fn test_reservation_follows_mode() {
+        let full = test_config();
+        let expected = GameConfig {
+            reservation_mode: ReservationMode::Expected,
+            ..test_config()
+        };
+        let bet = 10_000_000;
+        assert_eq!(
+            reservation_for_bet(&full, bet, full.max_dives),
+            max_payout_for_bet(&full, bet)
+        );
+        assert_eq!(
+            reservation_for_bet(&expected, bet, expected.max_dives),
+            expected_payout_for_bet(&expected, bet, expected.max_dives)
+        );
+    }
+
+    #[test]
+    fn test_jackpot_share_grows_pool_across_sessions() {
+        let mut config = test_config();
+        config.rake_bps = 20_000; // 2%
+        config.jackpot_bps = 10_000; // 1%
+        assert_eq!(jackpot_for_bet(&config, 10_000_000), 100_000);
+        assert_eq!(effective_bet(&config, 10_000_000), 9_700_000);
+
+        let mut vault = crate::test_utils::test_vault();
+        for bet in [10_000_000, 50_000_000, 1_000_000_000] {
+            vault.add_to_jackpot(jackpot_for_bet(&config, bet)).unwrap();
+        }
+        assert_eq!(vault.jackpot_pool, 100_000 + 500_000 + 10_000_000);
+    }
+    #[test]
     fn test_treasure_no_panic_on_realistic_bets() {
         let config = test_config();
         let sol_amounts = [
@@ -378,4 +842,43 @@ mod tests {
             }
         }
     }
+    #[rstest]
+    #[case(CurveMode::Geometric, 19, 10, 100, 1_000_000)]
+    #[case(CurveMode::Geometric, 10_001, 10_000, 100, 10_000_000)]
+    #[case(CurveMode::Geometric, 9, 10, 100, 1_000_000)]
+    #[case(CurveMode::Geometric, 9, 10, 1, 1_000_000)]
+    #[case(CurveMode::Geometric, 19, 10, 100, u64::MAX / 100)]
+    #[case(CurveMode::Linear, 19, 10, 100, 1_000_000)]
+    fn test_incremental_treasure_matches_from_scratch(
+        #[case] curve_mode: CurveMode,
+        #[case] num: u16,
+        #[case] den: u16,
+        #[case] max_mult: u16,
+        #[case] bet: u64,
+    ) {
+        let config = GameConfig {
+            curve_mode,
+            treasure_multiplier_num: num,
+            treasure_multiplier_den: den,
+            max_payout_multiplier: max_mult,
+            linear_step_bps: 250_000,
+            house_edge_bps: 20_000,
+            ..test_config()
+        };
+        let mut previous = gross_treasure_from(&config, bet, 0, None);
+        for dive in 1..=100u16 {
+            let stepped = gross_treasure_from(&config, bet, dive, Some(previous));
+            assert_eq!(
+                stepped,
+                gross_treasure_for_dive(&config, bet, dive),
+                "dive {dive}"
+            );
+            assert_eq!(
+                apply_house_edge(&config, stepped),
+                treasure_for_dive(&config, bet, dive),
+                "dive {dive}"
+            );
+            previous = stepped;
+        }
+    }
 }
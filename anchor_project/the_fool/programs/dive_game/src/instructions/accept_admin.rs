@@ -0,0 +1,78 @@
+use crate::events::AdminTransferredEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Completes a config admin transfer; must be signed by the pending admin
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let clock = Clock::get()?;
+    let old_admin = config.admin;
+    config.accept_admin(ctx.accounts.new_admin.key())?;
+    emit!(AdminTransferredEvent {
+        config: config.key(),
+        old_admin,
+        new_admin: config.admin,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub new_admin: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&config.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::GameError;
+    use crate::test_utils::*;
+
+    fn accept_as(pending: Option<Pubkey>, signer: &mut TestAccount) -> Result<GameConfig> {
+        install_syscall_stubs();
+        let mut config = TestAccount::program(
+            &GameConfig {
+                admin: Pubkey::new_unique(),
+                pending_admin: pending,
+                ..test_config(5)
+            },
+            1_000_000,
+        );
+        let signer_info = signer.info();
+        let config_info = config.info();
+        let mut accounts = AcceptAdmin {
+            new_admin: Signer::try_from(&signer_info)?,
+            config: Account::try_from(&config_info)?,
+        };
+        accept_admin(Context::new(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            Default::default(),
+        ))?;
+        Ok((*accounts.config).clone())
+    }
+
+    #[test]
+    fn test_full_admin_handoff() {
+        let mut signer = TestAccount::wallet(0);
+        let config = accept_as(Some(signer.key), &mut signer).unwrap();
+        assert_eq!(config.admin, signer.key);
+        assert_eq!(config.pending_admin, None);
+    }
+
+    #[test]
+    fn test_stray_accept_is_rejected() {
+        let mut signer = TestAccount::wallet(0);
+        let err = accept_as(Some(Pubkey::new_unique()), &mut signer)
+            .err()
+            .unwrap();
+        assert_eq!(err, GameError::NotPendingAdmin.into());
+        assert!(accept_as(None, &mut signer).is_err());
+    }
+}
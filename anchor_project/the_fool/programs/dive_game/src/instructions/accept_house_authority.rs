@@ -0,0 +1,72 @@
+use crate::events::HouseAuthorityTransferredEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Completes a house authority transfer; must be signed by the pending authority
+pub fn accept_house_authority(ctx: Context<AcceptHouseAuthority>) -> Result<()> {
+    let house_vault = &mut ctx.accounts.house_vault;
+    let clock = Clock::get()?;
+    let old_house_authority = house_vault.house_authority;
+    house_vault.accept_authority(ctx.accounts.new_authority.key())?;
+    emit!(HouseAuthorityTransferredEvent {
+        house_vault: house_vault.key(),
+        old_house_authority,
+        new_house_authority: house_vault.house_authority,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+#[derive(Accounts)]
+pub struct AcceptHouseAuthority<'info> {
+    pub new_authority: Signer<'info>,
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::GameError;
+    use crate::test_utils::*;
+
+    fn accept_as(pending: Option<Pubkey>, signer: &mut TestAccount) -> Result<HouseVault> {
+        install_syscall_stubs();
+        let mut vault = TestAccount::program(
+            &HouseVault {
+                pending_authority: pending,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let signer_info = signer.info();
+        let vault_info = vault.info();
+        let mut accounts = AcceptHouseAuthority {
+            new_authority: Signer::try_from(&signer_info)?,
+            house_vault: Account::try_from(&vault_info)?,
+        };
+        accept_house_authority(Context::new(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            Default::default(),
+        ))?;
+        Ok((*accounts.house_vault).clone())
+    }
+
+    #[test]
+    fn test_pending_authority_accepts() {
+        let mut signer = TestAccount::wallet(0);
+        let vault = accept_as(Some(signer.key), &mut signer).unwrap();
+        assert_eq!(vault.house_authority, signer.key);
+        assert_eq!(vault.pending_authority, None);
+    }
+
+    #[test]
+    fn test_other_signer_cannot_accept() {
+        let mut signer = TestAccount::wallet(0);
+        let err = accept_as(Some(Pubkey::new_unique()), &mut signer)
+            .err()
+            .unwrap();
+        assert_eq!(err, GameError::NotPendingAuthority.into());
+    }
+}
@@ -0,0 +1,229 @@
+use crate::events::SessionAdminClosedEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+use super::clean_expired_session::release_and_refund;
+
+/// Force-closes an Active session while the game is being wound down
+///
+/// Admin-only. Releases the reservation, refunds the current treasure to the player and
+/// closes the session with its rent going to the player as well. Unlike
+/// clean_expired_session there is no inactivity requirement.
+pub fn admin_close_session(ctx: Context<AdminCloseSession>) -> Result<()> {
+    let session = &ctx.accounts.session;
+    let clock = Clock::get()?;
+
+    session.ensure_active()?;
+
+    force_close(
+        session,
+        &mut ctx.accounts.house_vault,
+        &mut ctx.accounts.user_stats,
+        &mut ctx.accounts.house_stats,
+        &ctx.accounts.user.to_account_info(),
+    )?;
+    ctx.accounts.config.close_sessions(1);
+
+    emit!(SessionAdminClosedEvent {
+        session: session.key(),
+        user: session.user,
+        admin: ctx.accounts.admin.key(),
+        released_amount: session.reserved_amount(),
+        refunded_amount: session.current_treasure,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+/// Refunds the session and books it in both stats accounts; the refund counts as a house payout
+pub(crate) fn force_close<'info>(
+    session: &GameSession,
+    house_vault: &mut Account<'info, HouseVault>,
+    user_stats: &mut UserStats,
+    house_stats: &mut HouseStats,
+    user: &AccountInfo<'info>,
+) -> Result<()> {
+    release_and_refund(session, house_vault, user)?;
+    user_stats.close_session();
+    house_stats.record_payout(session.current_treasure)
+}
+
+#[derive(Accounts)]
+pub struct AdminCloseSession<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+
+    /// CHECK: The session owner receiving the refund and rent, validated by has_one on session
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        has_one = house_vault,
+        has_one = user,
+        close = user,
+    )]
+    pub session: Account<'info, GameSession>,
+
+    /// The session owner's stats, so the closed session stops counting as active
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// The vault's stats, which book the refund as a payout
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use anchor_lang::Bumps;
+    use std::collections::BTreeSet;
+
+    /// Runs Anchor's account validation for admin_close_session with `signer` as admin
+    fn validate_accounts(admin: Pubkey, signer: Pubkey) -> Result<()> {
+        let (config_key, config_bump) =
+            Pubkey::find_program_address(&[GAME_CONFIG_SEED.as_bytes()], &crate::ID);
+        let mut config_acc = TestAccount::program(
+            &GameConfig {
+                admin,
+                bump: config_bump,
+                ..test_config(5)
+            },
+            1_000_000,
+        );
+        config_acc.key = config_key;
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                user: user_acc.key,
+                house_vault: vault_acc.key,
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let (user_stats_key, user_stats_bump) = Pubkey::find_program_address(
+            &[USER_STATS_SEED.as_bytes(), user_acc.key.as_ref()],
+            &crate::ID,
+        );
+        let mut user_stats_acc = TestAccount::program(
+            &UserStats {
+                bump: user_stats_bump,
+                ..test_user_stats()
+            },
+            1_000_000,
+        );
+        user_stats_acc.key = user_stats_key;
+        let (house_stats_key, house_stats_bump) = Pubkey::find_program_address(
+            &[HOUSE_STATS_SEED.as_bytes(), vault_acc.key.as_ref()],
+            &crate::ID,
+        );
+        let mut house_stats_acc = TestAccount::program(
+            &HouseStats {
+                bump: house_stats_bump,
+                ..test_house_stats()
+            },
+            1_000_000,
+        );
+        house_stats_acc.key = house_stats_key;
+        let mut signer_acc = TestAccount::wallet(0);
+        signer_acc.key = signer;
+        let infos = [
+            signer_acc.info(),
+            config_acc.info(),
+            vault_acc.info(),
+            user_acc.info(),
+            session_acc.info(),
+            user_stats_acc.info(),
+            house_stats_acc.info(),
+        ];
+        let mut accounts: &[AccountInfo] = &infos;
+        let mut bumps = <AdminCloseSession as Bumps>::Bumps::default();
+        AdminCloseSession::try_accounts(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn test_admin_passes_account_validation() {
+        let admin = Pubkey::new_unique();
+        assert!(validate_accounts(admin, admin).is_ok());
+    }
+
+    #[test]
+    fn test_non_admin_is_rejected() {
+        let err = validate_accounts(Pubkey::new_unique(), Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, anchor_lang::error::ErrorCode::ConstraintHasOne.into());
+    }
+
+    #[test]
+    fn test_force_close_keeps_accounting_balanced() {
+        let session = GameSession {
+            current_treasure: 36_100_000,
+            dive_number: 3,
+            ..test_session()
+        };
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                total_reserved: 3_000_000_000,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut user_acc = TestAccount::wallet(0);
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let before = vault_info.lamports() + user_info.lamports();
+        let mut user_stats = UserStats {
+            active_session_count: 1,
+            ..test_user_stats()
+        };
+        let mut house_stats = test_house_stats();
+
+        force_close(
+            &session,
+            &mut vault,
+            &mut user_stats,
+            &mut house_stats,
+            &user_info,
+        )
+        .unwrap();
+
+        // Only this session's reservation is released
+        assert_eq!(vault.total_reserved, 2_000_000_000);
+        // Lamports move from vault to user without being created or lost
+        assert_eq!(user_info.lamports(), 36_100_000);
+        assert_eq!(vault_info.lamports() + user_info.lamports(), before);
+        // The refund is booked like any other payout
+        assert_eq!(house_stats.total_paid_out, 36_100_000);
+        assert_eq!(house_stats.net_profit_lamports, -36_100_000);
+        assert_eq!(user_stats.active_session_count, 0);
+    }
+}
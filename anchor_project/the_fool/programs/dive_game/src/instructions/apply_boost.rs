@@ -0,0 +1,150 @@
+use crate::errors::GameError;
+use crate::events::{BoostAppliedEvent, FlowDirection, FlowReason};
+use crate::game_math;
+use crate::states::*;
+use crate::util::vault_flow;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Buys a one-dive survival boost of `boost_bps` for `cost` lamports, paid into the vault
+///
+/// The next roll's survival threshold is raised by `boost_bps`, never past 1_000_000, and the
+/// boost is cleared by that roll whatever its outcome. `cost` must cover boost_price for the
+/// current treasure; anything above it is kept by the house.
+pub fn apply_boost(ctx: Context<ApplyBoost>, boost_bps: u32, cost: u64) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let session = &mut ctx.accounts.session;
+    let clock = Clock::get()?;
+    config.ensure_not_paused()?;
+    require!(!ctx.accounts.house_vault.locked, GameError::HouseLocked);
+
+    apply_boost_to(config, session, boost_bps, cost)?;
+
+    let transfer_ix = system_program::Transfer {
+        from: ctx.accounts.user.to_account_info(),
+        to: ctx.accounts.house_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+    system_program::transfer(cpi_ctx, cost)?;
+    emit!(vault_flow(
+        &ctx.accounts.house_vault.to_account_info(),
+        FlowDirection::In,
+        cost,
+        FlowReason::Boost
+    ));
+    ctx.accounts.house_stats.record_bet_increase(cost)?;
+
+    emit!(BoostAppliedEvent {
+        session: session.key(),
+        user: session.user,
+        dive_number: session.dive_number,
+        boost_bps,
+        cost,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Checks the boost against max_boost_bps and its price, then stores it on the session
+pub(crate) fn apply_boost_to(
+    config: &GameConfig,
+    session: &mut GameSession,
+    boost_bps: u32,
+    cost: u64,
+) -> Result<()> {
+    session.ensure_active()?;
+    session.ensure_no_pending_vrf()?;
+    require!(
+        boost_bps > 0
+            && boost_bps <= config.max_boost_bps
+            && session.next_dive_boost_bps == 0
+            && session.dive_number < session.max_dives(config),
+        GameError::BoostNotAvailable
+    );
+    let price = game_math::boost_price(session.current_treasure, boost_bps);
+    require!(cost > 0 && cost >= price, GameError::BoostCostTooLow);
+    session.next_dive_boost_bps = boost_bps;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ApplyBoost<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        has_one = user,
+        has_one = house_vault,
+    )]
+    pub session: Account<'info, GameSession>,
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn boost_config() -> GameConfig {
+        GameConfig {
+            max_boost_bps: 100_000,
+            ..test_config(5)
+        }
+    }
+
+    #[test]
+    fn test_boost_requires_its_price() {
+        let config = boost_config();
+        let mut session = test_session();
+        let price = game_math::boost_price(session.current_treasure, 50_000);
+        assert!(price > 0);
+
+        let err = apply_boost_to(&config, &mut session, 50_000, price - 1).unwrap_err();
+        assert_eq!(err, GameError::BoostCostTooLow.into());
+        assert_eq!(session.next_dive_boost_bps, 0);
+
+        apply_boost_to(&config, &mut session, 50_000, price).unwrap();
+        assert_eq!(session.next_dive_boost_bps, 50_000);
+    }
+
+    #[test]
+    fn test_boost_is_capped_by_config() {
+        let config = boost_config();
+        let mut session = test_session();
+        let err = apply_boost_to(&config, &mut session, 100_001, u64::MAX).unwrap_err();
+        assert_eq!(err, GameError::BoostNotAvailable.into());
+
+        let err = apply_boost_to(&test_config(5), &mut session, 1, u64::MAX).unwrap_err();
+        assert_eq!(err, GameError::BoostNotAvailable.into());
+    }
+
+    #[test]
+    fn test_boosts_do_not_stack() {
+        let config = boost_config();
+        let mut session = test_session();
+        apply_boost_to(&config, &mut session, 50_000, 10_000_000).unwrap();
+
+        let err = apply_boost_to(&config, &mut session, 50_000, 10_000_000).unwrap_err();
+        assert_eq!(err, GameError::BoostNotAvailable.into());
+    }
+
+    #[test]
+    fn test_boosted_survival_is_capped() {
+        assert_eq!(game_math::boosted_survival_bps(950_000, 100_000), 1_000_000);
+        assert_eq!(game_math::boosted_survival_bps(u32::MAX, 1), 1_000_000);
+        assert_eq!(game_math::boosted_survival_bps(700_000, 100_000), 800_000);
+    }
+}
@@ -0,0 +1,210 @@
+use crate::errors::GameError;
+use crate::rng;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+use super::play_round::{prepare_round, settle_round, PlayRound};
+
+/// Plays consecutive rounds in one transaction until the session reaches `target_dive`,
+/// loses, or hits the session's max dives
+/// Every dive rolls independently via `rng::random_roll_bps(seed, dive_number)`, and a loss
+/// performs the same atomic release and close as play_round
+pub fn auto_play(ctx: Context<PlayRound>, server_seed: u64, target_dive: u16) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = ctx.accounts;
+    let slot_hash = prepare_round(accounts, server_seed, &clock)?;
+    let seed = rng::generate_seed(&slot_hash, &accounts.session.key(), server_seed);
+
+    let target = auto_play_target(
+        accounts.session.max_dives(&accounts.config),
+        accounts.session.dive_number,
+        target_dive,
+    )?;
+
+    run_auto_play(
+        &accounts.config,
+        &mut accounts.session,
+        &mut accounts.house_vault,
+        &mut accounts.user_stats,
+        &mut accounts.house_stats,
+        &accounts.user.to_account_info(),
+        &seed,
+        target,
+        &clock,
+    )?;
+    accounts.config.release_ended(&accounts.session);
+
+    Ok(())
+}
+
+/// Clamps the requested target to max_dives; the target must be ahead of the current dive
+pub(crate) fn auto_play_target(max_dives: u16, current_dive: u16, target_dive: u16) -> Result<u16> {
+    require!(target_dive > current_dive, GameError::RoundMismatch);
+    Ok(target_dive.min(max_dives))
+}
+
+/// Rolls until the target dive is reached or the player loses
+/// Returns whether the session is still active
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_auto_play<'info>(
+    config: &GameConfig,
+    session: &mut Account<'info, GameSession>,
+    house_vault: &mut Account<'info, HouseVault>,
+    user_stats: &mut UserStats,
+    house_stats: &mut HouseStats,
+    user: &AccountInfo<'info>,
+    seed: &[u8; 32],
+    target: u16,
+    clock: &Clock,
+) -> Result<bool> {
+    while session.dive_number < target {
+        let roll = rng::random_roll_bps(seed, session.dive_number);
+        if !settle_round(
+            config,
+            session,
+            house_vault,
+            user_stats,
+            house_stats,
+            user,
+            roll,
+            clock,
+        )?
+        .survived
+        {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_math;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_target_is_clamped_to_max_dives() {
+        let config = test_config(10);
+        assert_eq!(auto_play_target(config.max_dives, 1, 4).unwrap(), 4);
+        assert_eq!(
+            auto_play_target(config.max_dives, 1, 50).unwrap(),
+            config.max_dives
+        );
+        assert!(auto_play_target(config.max_dives, 3, 3).is_err());
+        assert!(auto_play_target(config.max_dives, 3, 2).is_err());
+    }
+
+    #[test]
+    fn test_max_dives_override_extends_auto_play() {
+        let config = fixed_odds_config(1_000_000, 5);
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                max_dives_override: Some(8),
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        let target = auto_play_target(session.max_dives(&config), 1, 50).unwrap();
+        assert_eq!(target, 8);
+        let active = run_auto_play(
+            &config,
+            &mut session,
+            &mut vault,
+            &mut test_user_stats(),
+            &mut test_house_stats(),
+            &user_info,
+            &[3u8; 32],
+            target,
+            &test_clock(100),
+        )
+        .unwrap();
+
+        assert!(active);
+        assert_eq!(session.dive_number, 8);
+    }
+
+    #[test]
+    fn test_survived_auto_play_ends_active_at_target() {
+        let config = fixed_odds_config(1_000_000, 10);
+        let mut session_acc = TestAccount::program(&test_session(), 2_000_000);
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let mut stats = test_user_stats();
+        let mut house_stats = test_house_stats();
+
+        let active = run_auto_play(
+            &config,
+            &mut session,
+            &mut vault,
+            &mut stats,
+            &mut house_stats,
+            &user_info,
+            &[3u8; 32],
+            5,
+            &test_clock(100),
+        )
+        .unwrap();
+
+        assert!(active);
+        assert_eq!(session.status, SessionStatus::Active);
+        assert_eq!(session.dive_number, 5);
+        assert_eq!(
+            session.current_treasure,
+            game_math::treasure_for_dive(&config, session.bet_amount, 5)
+        );
+        assert_eq!(session.last_active_slot, 100);
+        assert_eq!(session.expires_at_slot, 100 + config.timeout_slots);
+        assert_eq!(vault.total_reserved, 1_000_000_000);
+        assert_eq!(session_info.lamports(), 2_000_000);
+    }
+
+    #[test]
+    fn test_losing_auto_play_closes_account() {
+        let config = fixed_odds_config(0, 10);
+        let mut session_acc = TestAccount::program(&test_session(), 2_000_000);
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let mut stats = test_user_stats();
+        let mut house_stats = test_house_stats();
+
+        let active = run_auto_play(
+            &config,
+            &mut session,
+            &mut vault,
+            &mut stats,
+            &mut house_stats,
+            &user_info,
+            &[3u8; 32],
+            5,
+            &test_clock(100),
+        )
+        .unwrap();
+
+        assert!(!active);
+        assert_eq!(session.dive_number, 1);
+        assert_eq!(vault.total_reserved, 0);
+        assert_eq!(session_info.lamports(), 0);
+        assert_eq!(user_info.lamports(), 2_000_000);
+        assert_eq!(stats.total_lost, 10_000_000);
+        assert_eq!(house_stats.total_losses, 1);
+    }
+}
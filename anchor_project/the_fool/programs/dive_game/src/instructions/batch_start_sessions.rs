@@ -0,0 +1,371 @@
+use crate::errors::GameError;
+use crate::events::{FlowDirection, FlowReason, SessionStartedEvent};
+use crate::game_math;
+use crate::states::*;
+use crate::util::{create_pda, vault_flow};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+use super::start_session::reserve_session_funds;
+
+/// One seat of a tournament batch
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BatchEntry {
+    pub user: Pubkey,
+    pub bet_amount: u64,
+}
+
+/// Amounts start_session would book for one entry
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct PlannedStart {
+    pub bet_amount: u64,
+    pub rake_amount: u64,
+    pub effective_bet: u64,
+    pub max_payout: u64,
+    pub reserved_payout: u64,
+}
+
+/// Opens a session for every entry in one transaction, with the house authority funding the
+/// bets and session rent
+///
+/// remaining_accounts holds a `[session, user_stats]` pair per entry: the uninitialized session
+/// PDA at the user's next session_index and the user's existing, writable UserStats. Every
+/// start is checked against the vault before anything is transferred or created, so a single
+/// entry that the vault can't cover fails the whole batch.
+pub fn batch_start_sessions<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BatchStartSessions<'info>>,
+    entries: Vec<BatchEntry>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let clock = Clock::get()?;
+    config.ensure_not_paused()?;
+    // USD pricing needs a feed read per start; tournaments are seeded in lamports
+    require!(!config.usd_denominated, GameError::InvalidConfig);
+    require!(
+        ctx.remaining_accounts.len() == entries.len() * 2,
+        anchor_lang::error::ErrorCode::AccountNotEnoughKeys
+    );
+    let house_vault = &mut ctx.accounts.house_vault;
+    require!(!house_vault.locked, GameError::HouseLocked);
+
+    let bets: Vec<u64> = entries.iter().map(|entry| entry.bet_amount).collect();
+    let (reserved_vault, planned) = plan_batch(
+        config,
+        house_vault,
+        house_vault.key(),
+        house_vault.to_account_info().lamports(),
+        &bets,
+    )?;
+    let total_bet = planned
+        .iter()
+        .try_fold(0u64, |total, start| total.checked_add(start.bet_amount))
+        .ok_or(GameError::Overflow)?;
+    let transfer_ix = system_program::Transfer {
+        from: ctx.accounts.house_authority.to_account_info(),
+        to: house_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+    system_program::transfer(cpi_ctx, total_bet)?;
+    emit!(vault_flow(
+        &house_vault.to_account_info(),
+        FlowDirection::In,
+        total_bet,
+        FlowReason::Bet
+    ));
+    house_vault.set_inner(reserved_vault);
+
+    let house_stats = &mut ctx.accounts.house_stats;
+    house_stats.house_vault = house_vault.key();
+    house_stats.bump = ctx.bumps.house_stats;
+
+    let space = 8 + GameSession::INIT_SPACE;
+    for ((entry, start), pair) in entries
+        .iter()
+        .zip(&planned)
+        .zip(ctx.remaining_accounts.chunks(2))
+    {
+        let (session_info, stats_info) = (&pair[0], &pair[1]);
+        // UserStats.user is only ever written by start_session for the PDA's own user
+        require!(stats_info.is_writable, GameError::InvalidSessionStatus);
+        let mut user_stats = Account::<UserStats>::try_from(stats_info)?;
+        require_keys_eq!(user_stats.user, entry.user);
+
+        let session_index = user_stats.session_counter;
+        let index_bytes = session_index.to_le_bytes();
+        let (session_key, bump) = Pubkey::find_program_address(
+            &[SESSION_SEED.as_bytes(), entry.user.as_ref(), &index_bytes],
+            &crate::ID,
+        );
+        require_keys_eq!(
+            session_info.key(),
+            session_key,
+            anchor_lang::error::ErrorCode::ConstraintSeeds
+        );
+        let signer_seeds: &[&[u8]] = &[
+            SESSION_SEED.as_bytes(),
+            entry.user.as_ref(),
+            &index_bytes,
+            &[bump],
+        ];
+        create_pda(
+            &ctx.accounts.house_authority.to_account_info(),
+            session_info,
+            &ctx.accounts.system_program.to_account_info(),
+            space,
+            signer_seeds,
+        )?;
+
+        let mut session = GameSession {
+            user: entry.user,
+            house_vault: house_vault.key(),
+            bet_amount: start.effective_bet,
+            current_treasure: start.effective_bet,
+            max_payout: start.max_payout,
+            reserved_payout: start.reserved_payout,
+            dive_number: 1,
+            bump,
+            difficulty: config.difficulty,
+            keeper: house_vault.game_keeper,
+            ..GameSession::blank()
+        };
+        session.record_activity(clock.slot, config.timeout_slots);
+        session.try_serialize(&mut &mut session_info.try_borrow_mut_data()?[..])?;
+
+        user_stats.claim_session_index(session_index)?;
+        user_stats.open_session(config.max_active_sessions_per_user)?;
+        user_stats.record_house_funded_session()?;
+        user_stats.exit(&crate::ID)?;
+        house_stats.record_session_start(start.bet_amount)?;
+
+        emit!(SessionStartedEvent {
+            session: session_key,
+            user: entry.user,
+            house_vault: house_vault.key(),
+            bet_amount: start.effective_bet,
+            rake_amount: start.rake_amount,
+            max_payout: start.max_payout,
+            sequence: house_vault.next_sequence()?,
+            timestamp: clock.unix_timestamp,
+            client_tag: session.client_tag,
+        });
+    }
+    ctx.accounts.config.open_sessions(entries.len() as u64)?;
+    msg!(
+        "BATCH_STARTED sessions={} total_bet={}",
+        entries.len(),
+        total_bet
+    );
+    Ok(())
+}
+
+/// Runs start_session's vault checks for each bet in order on a copy of the vault, as if every
+/// earlier bet had already landed and booked its reservation
+/// Returns the vault with all reservations booked, or the first entry's error with nothing
+/// booked at all
+pub(crate) fn plan_batch(
+    config: &GameConfig,
+    house_vault: &HouseVault,
+    vault_key: Pubkey,
+    vault_balance: u64,
+    bets: &[u64],
+) -> Result<(HouseVault, Vec<PlannedStart>)> {
+    let mut vault = house_vault.clone();
+    let mut balance = vault_balance;
+    let mut planned = Vec::with_capacity(bets.len());
+    for &bet_amount in bets {
+        config.validate_bet(bet_amount)?;
+        config.validate_bet_vault_cap(bet_amount, balance)?;
+        vault.open_session(config.max_total_sessions)?;
+        let effective_bet = game_math::effective_bet(config, bet_amount);
+        let max_payout = game_math::max_payout_for_bet(config, effective_bet);
+        let reserved_payout =
+            game_math::reservation_for_bet(config, effective_bet, config.max_dives);
+        balance = balance.checked_add(bet_amount).ok_or(GameError::Overflow)?;
+        reserve_session_funds(
+            config,
+            &mut vault,
+            vault_key,
+            balance,
+            game_math::jackpot_for_bet(config, bet_amount),
+            reserved_payout,
+        )?;
+        planned.push(PlannedStart {
+            bet_amount,
+            rake_amount: game_math::rake_for_bet(config, bet_amount),
+            effective_bet,
+            max_payout,
+            reserved_payout,
+        });
+    }
+    Ok((vault, planned))
+}
+
+#[derive(Accounts)]
+pub struct BatchStartSessions<'info> {
+    #[account(mut)]
+    pub house_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(
+        mut,
+        has_one = house_authority,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+
+    #[account(
+        init_if_needed,
+        payer = house_authority,
+        space = 8 + HouseStats::INIT_SPACE,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const SOL: u64 = 1_000_000_000;
+
+    fn empty_vault() -> HouseVault {
+        HouseVault {
+            total_reserved: 0,
+            ..test_vault()
+        }
+    }
+
+    #[test]
+    fn test_plan_reserves_every_entry() {
+        let config = test_config(10);
+        let bet = SOL / 10;
+        let (vault, planned) = plan_batch(
+            &config,
+            &empty_vault(),
+            Pubkey::new_unique(),
+            100 * SOL,
+            &[bet; 3],
+        )
+        .unwrap();
+        assert_eq!(planned.len(), 3);
+        let max_payout =
+            game_math::max_payout_for_bet(&config, game_math::effective_bet(&config, bet));
+        assert!(planned.iter().all(|start| start.max_payout == max_payout));
+        assert_eq!(vault.total_reserved, 3 * max_payout);
+        assert_eq!(vault.active_sessions, 3);
+    }
+
+    #[test]
+    fn test_over_cap_entry_rolls_back_whole_batch() {
+        let config = GameConfig {
+            max_exposure_bps: 500_000,
+            ..test_config(10)
+        };
+        let bet = SOL / 10;
+        let max_payout =
+            game_math::max_payout_for_bet(&config, game_math::effective_bet(&config, bet));
+        // Room for exactly two reservations at 50% exposure once their bets land
+        let balance = 4 * max_payout - 2 * bet;
+        let vault = empty_vault();
+        assert!(plan_batch(&config, &vault, Pubkey::new_unique(), balance, &[bet; 2]).is_ok());
+
+        let err = plan_batch(&config, &vault, Pubkey::new_unique(), balance, &[bet; 3])
+            .err()
+            .unwrap();
+        assert_eq!(err, GameError::VaultCapacityExceeded.into());
+        // The third entry goes over the exposure cap, and nothing is booked for the two before it
+        assert_eq!(vault.total_reserved, 0);
+        assert_eq!(vault.active_sessions, 0);
+    }
+
+    #[test]
+    fn test_expected_mode_fits_more_sessions() {
+        let full = GameConfig {
+            max_exposure_bps: 500_000,
+            ..test_config(10)
+        };
+        let expected = GameConfig {
+            reservation_mode: ReservationMode::Expected,
+            ..full.clone()
+        };
+        let bet = SOL / 10;
+        let max_payout = game_math::max_payout_for_bet(&full, game_math::effective_bet(&full, bet));
+        let balance = 4 * max_payout - 2 * bet;
+        let fits = |config: &GameConfig| {
+            (1..=100)
+                .take_while(|&n| {
+                    plan_batch(
+                        config,
+                        &empty_vault(),
+                        Pubkey::new_unique(),
+                        balance,
+                        &vec![bet; n],
+                    )
+                    .is_ok()
+                })
+                .count()
+        };
+        assert_eq!(fits(&full), 2);
+        assert!(fits(&expected) > 2 * fits(&full));
+
+        let (vault, planned) = plan_batch(
+            &expected,
+            &empty_vault(),
+            Pubkey::new_unique(),
+            balance,
+            &[bet; 2],
+        )
+        .unwrap();
+        assert!(planned
+            .iter()
+            .all(|start| start.reserved_payout < start.max_payout
+                && start.reserved_payout >= start.effective_bet));
+        assert_eq!(vault.total_reserved, 2 * planned[0].reserved_payout);
+    }
+
+    #[test]
+    fn test_invalid_entry_fails_batch() {
+        let config = test_config(10);
+        let err = plan_batch(
+            &config,
+            &empty_vault(),
+            Pubkey::new_unique(),
+            100 * SOL,
+            &[SOL / 10, 0, SOL / 10],
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err, GameError::InvalidBetAmount.into());
+    }
+
+    #[test]
+    fn test_batch_counts_against_session_cap() {
+        let config = GameConfig {
+            max_total_sessions: 2,
+            ..test_config(10)
+        };
+        let vault = HouseVault {
+            active_sessions: 1,
+            ..empty_vault()
+        };
+        let err = plan_batch(
+            &config,
+            &vault,
+            Pubkey::new_unique(),
+            100 * SOL,
+            &[SOL / 10; 2],
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err, GameError::VaultCapacityExceeded.into());
+    }
+}
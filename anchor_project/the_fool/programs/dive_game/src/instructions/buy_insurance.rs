@@ -0,0 +1,143 @@
+use crate::errors::GameError;
+use crate::events::{FlowDirection, FlowReason, InsuranceBoughtEvent};
+use crate::game_math;
+use crate::states::*;
+use crate::util::vault_flow;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Insures the session's next dive for `cost` lamports
+///
+/// If the next roll fails, settle_round refunds insurance_payout_bps of the treasure held at
+/// that point. `cost` must cover the fair premium for the dive so insurance never has positive
+/// expected value for the player; anything above it is kept by the house. The flag is consumed
+/// by the next roll whatever its outcome.
+pub fn buy_insurance(ctx: Context<BuyInsurance>, cost: u64) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let session = &mut ctx.accounts.session;
+    let clock = Clock::get()?;
+    config.ensure_not_paused()?;
+    require!(!ctx.accounts.house_vault.locked, GameError::HouseLocked);
+
+    apply_insurance(config, session, cost)?;
+
+    let transfer_ix = system_program::Transfer {
+        from: ctx.accounts.user.to_account_info(),
+        to: ctx.accounts.house_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+    system_program::transfer(cpi_ctx, cost)?;
+    emit!(vault_flow(
+        &ctx.accounts.house_vault.to_account_info(),
+        FlowDirection::In,
+        cost,
+        FlowReason::Insurance
+    ));
+    ctx.accounts.house_stats.record_bet_increase(cost)?;
+
+    emit!(InsuranceBoughtEvent {
+        session: session.key(),
+        user: session.user,
+        dive_number: session.dive_number,
+        cost,
+        coverage: game_math::insurance_refund(config, session.current_treasure),
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Checks `cost` against the premium for the session's next dive and marks it insured
+pub(crate) fn apply_insurance(
+    config: &GameConfig,
+    session: &mut GameSession,
+    cost: u64,
+) -> Result<()> {
+    session.ensure_active()?;
+    session.ensure_no_pending_vrf()?;
+    require!(
+        config.insurance_payout_bps > 0
+            && !session.insured
+            && session.dive_number < session.max_dives(config),
+        GameError::InsuranceNotAvailable
+    );
+    let premium =
+        game_math::insurance_premium(config, session.current_treasure, session.dive_number);
+    require!(
+        cost > 0 && cost >= premium,
+        GameError::InsurancePremiumTooLow
+    );
+    session.insured = true;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct BuyInsurance<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        has_one = user,
+        has_one = house_vault,
+    )]
+    pub session: Account<'info, GameSession>,
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn insured_config() -> GameConfig {
+        GameConfig {
+            insurance_payout_bps: 500_000,
+            ..test_config(5)
+        }
+    }
+
+    #[test]
+    fn test_insurance_requires_the_premium() {
+        let config = insured_config();
+        let mut session = test_session();
+        let premium =
+            game_math::insurance_premium(&config, session.current_treasure, session.dive_number);
+        assert!(premium > 0);
+
+        let err = apply_insurance(&config, &mut session, premium - 1).unwrap_err();
+        assert_eq!(err, GameError::InsurancePremiumTooLow.into());
+        assert!(!session.insured);
+
+        apply_insurance(&config, &mut session, premium).unwrap();
+        assert!(session.insured);
+    }
+
+    #[test]
+    fn test_insurance_is_bought_once_per_dive() {
+        let config = insured_config();
+        let mut session = test_session();
+        apply_insurance(&config, &mut session, 10_000_000).unwrap();
+
+        let err = apply_insurance(&config, &mut session, 10_000_000).unwrap_err();
+        assert_eq!(err, GameError::InsuranceNotAvailable.into());
+    }
+
+    #[test]
+    fn test_insurance_disabled_by_default() {
+        let mut session = test_session();
+        let err = apply_insurance(&test_config(5), &mut session, 10_000_000).unwrap_err();
+        assert_eq!(err, GameError::InsuranceNotAvailable.into());
+    }
+}
@@ -0,0 +1,17 @@
+use crate::events::WithdrawCancelledEvent;
+use anchor_lang::prelude::*;
+
+use super::withdraw_house::WithdrawHouse;
+
+/// Drops the pending timelocked withdrawal
+pub fn cancel_withdraw(ctx: Context<WithdrawHouse>) -> Result<()> {
+    let clock = Clock::get()?;
+    let house_vault = &mut ctx.accounts.house_vault;
+    let amount = house_vault.cancel_withdraw()?;
+    emit!(WithdrawCancelledEvent {
+        house_vault: house_vault.key(),
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
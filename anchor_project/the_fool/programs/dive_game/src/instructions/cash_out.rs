@@ -1,21 +1,44 @@
 use crate::errors::GameError;
-use crate::events::SessionCashedOutEvent;
+use crate::events::{
+    CircuitBreakerTrippedEvent, FlowReason, JackpotWonEvent, SessionCashedOutEvent,
+    VaultInsolventEvent,
+};
+use crate::game_math;
 use crate::states::*;
+use crate::util::{close_session_to, pay_from_vault};
 use anchor_lang::prelude::*;
-pub fn cash_out(ctx: Context<CashOut>) -> Result<()> {
+
+use super::play_round::revealed_seed_event;
+/// Pays the treasure to the user and, when the session has a referrer, referral_fee_bps of it
+/// to the referrer account passed in remaining_accounts
+/// Fails with PayoutBelowMinimum if the treasure has dropped below `min_payout`
+/// With allow_breakeven_exit a session whose treasure hasn't grown past the bet can still
+/// exit: the bet is refunded without a referral fee and min_dives_to_cash_out doesn't apply
+/// Returns the lamports paid to the user, net of the referral fee and including any jackpot;
+/// the session's rent comes back on top
+pub fn cash_out<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CashOut<'info>>,
+    min_payout: u64,
+) -> Result<u64> {
     let session = &mut ctx.accounts.session;
     let house_vault = &mut ctx.accounts.house_vault;
     let clock = Clock::get()?;
 
     // Use helper method to ensure session is active
     session.ensure_active()?;
+    // Exiting on a VRF roll the player can already read would make losses optional
+    session.ensure_no_pending_vrf()?;
 
     // Phase 2: Update activity tracking before closing
-    session.last_active_slot = clock.slot;
+    session.record_activity(clock.slot, ctx.accounts.config.timeout_slots);
 
     require!(!house_vault.locked, GameError::HouseLocked);
+    session.ensure_min_payout(min_payout)?;
 
-    if session.current_treasure <= session.bet_amount {
+    let breakeven = session.current_treasure <= session.bet_amount;
+    if !breakeven {
+        ctx.accounts.config.ensure_min_dives(session.dive_number)?;
+    } else if !ctx.accounts.config.allow_breakeven_exit {
         msg!(
             "INSUFFICIENT_TREASURE treasure={} bet={} session={}",
             session.current_treasure / 1_000_000_000,
@@ -25,60 +48,207 @@ pub fn cash_out(ctx: Context<CashOut>) -> Result<()> {
         return Err(GameError::InsufficientTreasure.into());
     }
 
+    // A refund carries no winnings to share with a referrer
+    let referrer = if breakeven {
+        None
+    } else {
+        referrer_account(session.referrer, ctx.remaining_accounts)?
+    };
+
+    let paid = pay_out_session(
+        &ctx.accounts.config,
+        session,
+        house_vault,
+        &mut ctx.accounts.user_stats,
+        &mut ctx.accounts.house_stats,
+        &mut ctx.accounts.leaderboard,
+        &ctx.accounts.user.to_account_info(),
+        referrer,
+        &clock,
+    )?;
+    ctx.accounts.config.close_sessions(1);
+    Ok(paid)
+}
+/// Pays current_treasure out of the vault (less the referral fee when a referrer is given),
+/// releases the reservation, records stats and closes the session to the user
+/// Sessions cashing out at their max_dives also win the vault's whole jackpot_pool
+/// Shared by cash_out and deep_dive; returns the lamports the user was paid from the vault
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn pay_out_session<'info>(
+    config: &GameConfig,
+    session: &mut Account<'info, GameSession>,
+    house_vault: &mut Account<'info, HouseVault>,
+    user_stats: &mut UserStats,
+    house_stats: &mut HouseStats,
+    leaderboard: &mut Leaderboard,
+    user: &AccountInfo<'info>,
+    referrer: Option<&AccountInfo<'info>>,
+    clock: &Clock,
+) -> Result<u64> {
     let vault_balance = house_vault.to_account_info().lamports();
+    let jackpot = if session.dive_number >= session.max_dives(config) {
+        house_vault.jackpot_pool
+    } else {
+        0
+    };
+    let owed = session
+        .current_treasure
+        .checked_add(jackpot)
+        .ok_or(GameError::Overflow)?;
 
-    if vault_balance < session.current_treasure {
+    if let Some(event) = vault_insolvency(session, house_vault.key(), vault_balance, owed, clock) {
         msg!(
             "VAULT_UNDERFUNDED need={} have={} vault={}",
-            session.current_treasure / 1_000_000_000,
+            owed / 1_000_000_000,
             vault_balance / 1_000_000_000,
             house_vault.key()
         );
+        // The failed transaction's logs still carry this for monitoring; no funds move
+        emit!(event);
         return Err(GameError::InsufficientVaultBalance.into());
     }
 
+    let referral_fee = match referrer {
+        Some(_) => game_math::referral_fee(config, session.current_treasure),
+        None => 0,
+    };
+    // The referral fee only applies to the treasure, never the jackpot
+    let user_payout = owed - referral_fee;
+
     // Manual lamport transfer from vault to user
     // Cannot use system_program::transfer() because vault has data
-    let vault_lamports = house_vault.to_account_info().lamports();
-    let user_lamports = ctx.accounts.user.lamports();
-
-    **house_vault.to_account_info().try_borrow_mut_lamports()? = vault_lamports
-        .checked_sub(session.current_treasure)
-        .ok_or(GameError::Overflow)?;
-
-    **ctx.accounts.user.try_borrow_mut_lamports()? = user_lamports
-        .checked_add(session.current_treasure)
-        .ok_or(GameError::Overflow)?;
+    let vault_info = house_vault.to_account_info();
+    emit!(pay_from_vault(
+        &vault_info,
+        user,
+        user_payout,
+        FlowReason::CashOut
+    )?);
+    if let Some(referrer) = referrer {
+        emit!(pay_from_vault(
+            &vault_info,
+            referrer,
+            referral_fee,
+            FlowReason::ReferralFee
+        )?);
+    }
 
     // Use helper methods for fund release and state transition
-    house_vault.release(session.max_payout)?;
+    house_vault.release(session.reserved_amount())?;
     session.mark_cashed_out()?;
+    user_stats.record_win(owed, session.total_withdrawn, session.dive_number)?;
+    let total_payout = owed.saturating_add(session.total_withdrawn);
+    leaderboard.record(session.user, total_payout, session.dive_number);
+    user_stats.close_session();
+    house_vault.close_session();
+    house_stats.record_payout(owed)?;
+    record_payout_streak(config, house_vault, owed, clock);
+
+    if jackpot > 0 {
+        house_vault.take_jackpot();
+        emit!(JackpotWonEvent {
+            house_vault: house_vault.key(),
+            session: session.key(),
+            user: session.user,
+            amount: jackpot,
+            timestamp: clock.unix_timestamp,
+        });
+    }
 
     emit!(SessionCashedOutEvent {
         session: session.key(),
         user: session.user,
         house_vault: session.house_vault,
         payout_amount: session.current_treasure,
+        referral_fee,
+        payout_usd_cents: game_math::lamports_to_usd_cents(
+            session.current_treasure,
+            session.locked_price_micro_usd,
+        ),
         final_dive_number: session.dive_number,
+        sequence: house_vault.next_sequence()?,
         timestamp: clock.unix_timestamp,
     });
+    if let Some(event) = revealed_seed_event(session, clock) {
+        emit!(event);
+    }
 
     // Manually close the session account by transferring its rent to user
     // This avoids the "from must not carry data" error from Anchor's close constraint
-    let session_lamports = session.to_account_info().lamports();
-    **session.to_account_info().try_borrow_mut_lamports()? = 0;
-    **ctx
-        .accounts
-        .user
-        .to_account_info()
-        .try_borrow_mut_lamports()? += session_lamports;
-
-    Ok(())
+    close_session_to(user, &session.to_account_info())?;
+
+    Ok(user_payout)
+}
+/// The event to report when the vault can't cover what the session is owed, if it can't
+pub(crate) fn vault_insolvency(
+    session: &Account<GameSession>,
+    house_vault: Pubkey,
+    vault_balance: u64,
+    owed: u64,
+    clock: &Clock,
+) -> Option<VaultInsolventEvent> {
+    (vault_balance < owed).then(|| VaultInsolventEvent {
+        session: session.key(),
+        user: session.user,
+        house_vault,
+        owed_amount: owed,
+        available_balance: vault_balance,
+        timestamp: clock.unix_timestamp,
+    })
+}
+/// Finds the session's declared referrer among the remaining accounts
+/// Sessions without a referrer ignore remaining accounts entirely
+pub(crate) fn referrer_account<'a, 'info>(
+    referrer: Option<Pubkey>,
+    remaining_accounts: &'a [AccountInfo<'info>],
+) -> Result<Option<&'a AccountInfo<'info>>> {
+    let Some(referrer) = referrer else {
+        return Ok(None);
+    };
+    let account = remaining_accounts
+        .iter()
+        .find(|account| account.key == &referrer)
+        .ok_or(GameError::MissingReferrer)?;
+    require!(account.is_writable, GameError::MissingReferrer);
+    Ok(Some(account))
+}
+/// Feeds a payout into the vault's circuit breaker
+/// A run of large payouts may mean broken odds; the vault locks until the authority has looked
+pub(crate) fn record_payout_streak(
+    config: &GameConfig,
+    house_vault: &mut Account<HouseVault>,
+    payout: u64,
+    clock: &Clock,
+) {
+    if house_vault.record_payout_streak(
+        payout,
+        config.big_payout_lamports,
+        config.max_consecutive_big_payouts,
+    ) {
+        msg!(
+            "CIRCUIT_BREAKER_TRIPPED run={} vault={}",
+            house_vault.consecutive_big_payouts,
+            house_vault.key()
+        );
+        emit!(CircuitBreakerTrippedEvent {
+            house_vault: house_vault.key(),
+            consecutive_big_payouts: house_vault.consecutive_big_payouts,
+            last_payout: payout,
+            timestamp: clock.unix_timestamp,
+        });
+    }
 }
+
 #[derive(Accounts)]
 pub struct CashOut<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
     #[account(
         mut,
         has_one = user,
@@ -87,4 +257,483 @@ pub struct CashOut<'info> {
     pub session: Account<'info, GameSession>,
     #[account(mut)]
     pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = leaderboard.bump,
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_insolvent_vault_emits_event_and_pays_nothing() {
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                current_treasure: 80_000_000,
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let mut vault_acc = TestAccount::program(&test_vault(), 50_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        let event = vault_insolvency(
+            &session,
+            vault.key(),
+            50_000_000,
+            80_000_000,
+            &test_clock(100),
+        )
+        .unwrap();
+        assert_eq!(event.session, session_info.key());
+        assert_eq!(event.house_vault, vault_info.key());
+        assert_eq!(event.owed_amount, 80_000_000);
+        assert_eq!(event.available_balance, 50_000_000);
+        assert!(vault_insolvency(
+            &session,
+            vault.key(),
+            80_000_000,
+            80_000_000,
+            &test_clock(100)
+        )
+        .is_none());
+
+        let err = pay_out_session(
+            &test_config(5),
+            &mut session,
+            &mut vault,
+            &mut test_user_stats(),
+            &mut test_house_stats(),
+            &mut test_leaderboard(),
+            &user_info,
+            None,
+            &test_clock(100),
+        )
+        .unwrap_err();
+
+        assert_eq!(err, GameError::InsufficientVaultBalance.into());
+        assert_eq!(vault_info.lamports(), 50_000_000);
+        assert_eq!(user_info.lamports(), 0);
+        assert_eq!(session.status, SessionStatus::Active);
+    }
+
+    /// Cashes out an 80_000_000 treasure at `dive` from a vault holding `jackpot_pool`
+    /// Returns (vault after, user lamports after)
+    fn cash_out_at(dive: u16, vault: HouseVault) -> (HouseVault, u64) {
+        cash_out_with(&test_config(5), dive, vault)
+    }
+
+    fn cash_out_with(config: &GameConfig, dive: u16, vault: HouseVault) -> (HouseVault, u64) {
+        let session = GameSession {
+            dive_number: dive,
+            current_treasure: 80_000_000,
+            ..test_session()
+        };
+        pay_out_at(config, &session, vault)
+    }
+
+    fn pay_out_at(
+        config: &GameConfig,
+        session: &GameSession,
+        vault: HouseVault,
+    ) -> (HouseVault, u64) {
+        let mut session_acc = TestAccount::program(session, 2_000_000);
+        let mut vault_acc = TestAccount::program(&vault, 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        pay_out_session(
+            config,
+            &mut session,
+            &mut vault,
+            &mut test_user_stats(),
+            &mut test_house_stats(),
+            &mut test_leaderboard(),
+            &user_info,
+            None,
+            &test_clock(100),
+        )
+        .unwrap();
+        ((*vault).clone(), user_info.lamports())
+    }
+
+    #[test]
+    fn test_jackpot_follows_the_session_dive_limit() {
+        let funded = HouseVault {
+            jackpot_pool: 300_000_000,
+            ..test_vault()
+        };
+        let at = |dive_number, max_dives_override| GameSession {
+            dive_number,
+            current_treasure: 80_000_000,
+            max_dives_override,
+            ..test_session()
+        };
+        let config = test_config(5);
+
+        // A shorter session reaches its own limit before config.max_dives
+        let (vault, _) = pay_out_at(&config, &at(3, Some(3)), funded.clone());
+        assert_eq!(vault.jackpot_pool, 0);
+
+        // A longer one hasn't reached it at config.max_dives
+        let (vault, _) = pay_out_at(&config, &at(5, Some(8)), funded.clone());
+        assert_eq!(vault.jackpot_pool, 300_000_000);
+        let (vault, _) = pay_out_at(&config, &at(8, Some(8)), funded);
+        assert_eq!(vault.jackpot_pool, 0);
+    }
+
+    #[test]
+    fn test_jackpot_paid_once_on_max_dive_cash_out() {
+        let funded = HouseVault {
+            jackpot_pool: 300_000_000,
+            ..test_vault()
+        };
+        // Below max_dives the pool is left alone
+        let (vault, user_lamports) = cash_out_at(4, funded.clone());
+        assert_eq!(vault.jackpot_pool, 300_000_000);
+        assert_eq!(user_lamports, 80_000_000 + 2_000_000);
+
+        let (vault, user_lamports) = cash_out_at(5, funded);
+        assert_eq!(vault.jackpot_pool, 0);
+        assert_eq!(user_lamports, 80_000_000 + 300_000_000 + 2_000_000);
+
+        // The next max-dive winner finds an empty pool
+        let (vault, user_lamports) = cash_out_at(5, vault);
+        assert_eq!(vault.jackpot_pool, 0);
+        assert_eq!(user_lamports, 80_000_000 + 2_000_000);
+    }
+
+    #[test]
+    fn test_run_of_big_payouts_locks_vault() {
+        let config = GameConfig {
+            big_payout_lamports: 50_000_000,
+            max_consecutive_big_payouts: 2,
+            ..test_config(5)
+        };
+        let (vault, _) = cash_out_with(&config, 3, test_vault());
+        let (vault, _) = cash_out_with(&config, 3, vault);
+        assert_eq!(vault.consecutive_big_payouts, 2);
+        assert!(!vault.locked);
+
+        // The third 0.08 SOL payout in a row trips the breaker
+        let (vault, _) = cash_out_with(&config, 3, vault);
+        assert_eq!(vault.consecutive_big_payouts, 3);
+        assert!(vault.locked);
+    }
+
+    #[test]
+    fn test_small_payout_ends_the_run() {
+        let config = GameConfig {
+            big_payout_lamports: 50_000_000,
+            max_consecutive_big_payouts: 1,
+            ..test_config(5)
+        };
+        let (vault, _) = cash_out_with(&config, 3, test_vault());
+        let small = GameConfig {
+            big_payout_lamports: 80_000_000,
+            ..config.clone()
+        };
+        // Exactly at the threshold isn't a big payout
+        let (vault, _) = cash_out_with(&small, 3, vault);
+        assert_eq!(vault.consecutive_big_payouts, 0);
+        let (vault, _) = cash_out_with(&config, 3, vault);
+        assert!(!vault.locked);
+    }
+
+    #[test]
+    fn test_payout_return_matches_user_balance_delta() {
+        let config = GameConfig {
+            referral_fee_bps: 100_000,
+            ..test_config(5)
+        };
+        let vault = HouseVault {
+            jackpot_pool: 300_000_000,
+            ..test_vault()
+        };
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                dive_number: 5,
+                current_treasure: 80_000_000,
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let mut vault_acc = TestAccount::program(&vault, 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(1_000);
+        let mut referrer_acc = TestAccount::wallet(0);
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let referrer_info = referrer_acc.info();
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        let paid = pay_out_session(
+            &config,
+            &mut session,
+            &mut vault,
+            &mut test_user_stats(),
+            &mut test_house_stats(),
+            &mut test_leaderboard(),
+            &user_info,
+            Some(&referrer_info),
+            &test_clock(100),
+        )
+        .unwrap();
+
+        // Treasure plus jackpot, less the 10% fee on the treasure
+        assert_eq!(paid, 80_000_000 + 300_000_000 - 8_000_000);
+        // The session's rent comes back on top of the payout
+        assert_eq!(user_info.lamports() - 1_000 - 2_000_000, paid);
+        assert_eq!(referrer_info.lamports(), 8_000_000);
+    }
+
+    /// Runs Anchor's account validation for cash_out with a config PDA of `config_difficulty`
+    fn validate_accounts(config_difficulty: u8, session_difficulty: u8) -> Result<()> {
+        use anchor_lang::Bumps;
+        use std::collections::BTreeSet;
+
+        let pda = |seeds: &[&[u8]], acc: &mut TestAccount| {
+            let (key, bump) = Pubkey::find_program_address(seeds, &crate::ID);
+            acc.key = key;
+            bump
+        };
+        let mut user_acc = TestAccount::wallet(1_000_000_000);
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut config_acc = TestAccount::wallet(0);
+        let config_bump = pda(
+            &[
+                GAME_CONFIG_SEED.as_bytes(),
+                difficulty_seed(&config_difficulty),
+            ],
+            &mut config_acc,
+        );
+        config_acc = TestAccount {
+            key: config_acc.key,
+            ..TestAccount::program(
+                &GameConfig {
+                    difficulty: config_difficulty,
+                    bump: config_bump,
+                    ..test_config(5)
+                },
+                1_000_000,
+            )
+        };
+        let mut user_stats_acc = TestAccount::wallet(0);
+        let user_stats_bump = pda(
+            &[USER_STATS_SEED.as_bytes(), user_acc.key.as_ref()],
+            &mut user_stats_acc,
+        );
+        user_stats_acc = TestAccount {
+            key: user_stats_acc.key,
+            ..TestAccount::program(
+                &UserStats {
+                    bump: user_stats_bump,
+                    ..test_user_stats()
+                },
+                1_000_000,
+            )
+        };
+        let mut house_stats_acc = TestAccount::wallet(0);
+        let house_stats_bump = pda(
+            &[HOUSE_STATS_SEED.as_bytes(), vault_acc.key.as_ref()],
+            &mut house_stats_acc,
+        );
+        house_stats_acc = TestAccount {
+            key: house_stats_acc.key,
+            ..TestAccount::program(
+                &HouseStats {
+                    bump: house_stats_bump,
+                    ..test_house_stats()
+                },
+                1_000_000,
+            )
+        };
+        let mut leaderboard_acc = TestAccount::wallet(0);
+        let leaderboard_bump = pda(
+            &[LEADERBOARD_SEED.as_bytes(), vault_acc.key.as_ref()],
+            &mut leaderboard_acc,
+        );
+        leaderboard_acc = TestAccount {
+            key: leaderboard_acc.key,
+            ..TestAccount::program(
+                &Leaderboard {
+                    bump: leaderboard_bump,
+                    ..test_leaderboard()
+                },
+                1_000_000,
+            )
+        };
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                user: user_acc.key,
+                house_vault: vault_acc.key,
+                difficulty: session_difficulty,
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let infos = [
+            user_acc.info(),
+            config_acc.info(),
+            session_acc.info(),
+            vault_acc.info(),
+            user_stats_acc.info(),
+            house_stats_acc.info(),
+            leaderboard_acc.info(),
+        ];
+        let mut accounts: &[AccountInfo] = &infos;
+        CashOut::try_accounts(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            &mut <CashOut as Bumps>::Bumps::default(),
+            &mut BTreeSet::new(),
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn test_session_loads_its_bound_difficulty_config() {
+        assert!(validate_accounts(0, 0).is_ok());
+        assert!(validate_accounts(2, 2).is_ok());
+        for (config, session) in [(0, 2), (2, 0), (1, 2)] {
+            let err = validate_accounts(config, session).unwrap_err();
+            assert_eq!(err, anchor_lang::error::ErrorCode::ConstraintSeeds.into());
+        }
+    }
+
+    /// Runs cash_out on a dive-1 session whose treasure still equals its bet
+    /// Returns the result with (user lamports, vault reserved) afterwards
+    fn breakeven_exit(allow_breakeven_exit: bool) -> (Result<u64>, u64, u64) {
+        cash_out_session(allow_breakeven_exit, test_session())
+    }
+
+    fn cash_out_session(
+        allow_breakeven_exit: bool,
+        session: GameSession,
+    ) -> (Result<u64>, u64, u64) {
+        install_syscall_stubs();
+        let mut user_acc = TestAccount::wallet(0);
+        let mut config_acc = TestAccount::program(
+            &GameConfig {
+                allow_breakeven_exit,
+                min_dives_to_cash_out: 3,
+                ..test_config(5)
+            },
+            1_000_000,
+        );
+        let mut session_acc = TestAccount::program(&session, 2_000_000);
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut user_stats_acc = TestAccount::program(&test_user_stats(), 1_000_000);
+        let mut house_stats_acc = TestAccount::program(&test_house_stats(), 1_000_000);
+        let mut leaderboard_acc = TestAccount::program(&test_leaderboard(), 1_000_000);
+        let user_info = user_acc.info();
+        let config_info = config_acc.info();
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_stats_info = user_stats_acc.info();
+        let house_stats_info = house_stats_acc.info();
+        let leaderboard_info = leaderboard_acc.info();
+        let mut accounts = CashOut {
+            user: Signer::try_from(&user_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            session: Account::try_from(&session_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            user_stats: Account::try_from(&user_stats_info).unwrap(),
+            house_stats: Account::try_from(&house_stats_info).unwrap(),
+            leaderboard: Account::try_from(&leaderboard_info).unwrap(),
+        };
+        let result = cash_out(
+            Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+            0,
+        );
+        (
+            result,
+            user_info.lamports(),
+            accounts.house_vault.total_reserved,
+        )
+    }
+
+    #[test]
+    fn test_breakeven_exit_refunds_bet_and_releases_reservation() {
+        let (result, user_lamports, reserved) = breakeven_exit(true);
+        // The 0.01 SOL bet plus the session's rent
+        assert_eq!(result.unwrap(), 10_000_000);
+        assert_eq!(user_lamports, 10_000_000 + 2_000_000);
+        assert_eq!(reserved, 0);
+    }
+
+    #[test]
+    fn test_breakeven_exit_blocked_without_flag() {
+        let (result, user_lamports, reserved) = breakeven_exit(false);
+        assert_eq!(result.unwrap_err(), GameError::InsufficientTreasure.into());
+        assert_eq!(user_lamports, 0);
+        assert_eq!(reserved, test_vault().total_reserved);
+    }
+
+    #[test]
+    fn test_cash_out_blocked_while_vrf_request_pending() {
+        let session = GameSession {
+            pending_vrf: Some(Pubkey::new_unique()),
+            vrf_request_slot: 101,
+            ..test_session()
+        };
+        let (result, user_lamports, reserved) = cash_out_session(true, session);
+        assert_eq!(result.unwrap_err(), GameError::VrfRequestPending.into());
+        assert_eq!(user_lamports, 0);
+        assert_eq!(reserved, test_vault().total_reserved);
+    }
+
+    #[test]
+    fn test_no_referrer_ignores_remaining_accounts() {
+        let mut other = TestAccount::wallet(0);
+        let infos = [other.info()];
+        assert!(referrer_account(None, &infos).unwrap().is_none());
+        assert!(referrer_account(None, &[]).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_declared_referrer_is_found() {
+        let mut other = TestAccount::wallet(0);
+        let mut referrer = TestAccount::wallet(0);
+        let key = referrer.key;
+        let infos = [other.info(), referrer.info()];
+        let found = referrer_account(Some(key), &infos).unwrap().unwrap();
+        assert_eq!(found.key, &key);
+    }
+
+    #[test]
+    fn test_missing_referrer_is_rejected() {
+        let mut other = TestAccount::wallet(0);
+        let infos = [other.info()];
+        let err = referrer_account(Some(Pubkey::new_unique()), &infos).unwrap_err();
+        assert_eq!(err, GameError::MissingReferrer.into());
+        assert!(referrer_account(Some(Pubkey::new_unique()), &[]).is_err());
+    }
 }
@@ -0,0 +1,132 @@
+use crate::errors::GameError;
+use crate::events::{BonusClaimedEvent, FlowReason};
+use crate::states::*;
+use crate::util::pay_from_vault;
+use anchor_lang::prelude::*;
+
+/// Pays the player's locked bonus once total_bet reaches config.bonus_wagering_multiple times it
+pub fn claim_bonus(ctx: Context<ClaimBonus>) -> Result<()> {
+    let clock = Clock::get()?;
+    let user_stats = &mut ctx.accounts.user_stats;
+    let house_vault = &mut ctx.accounts.house_vault;
+    require_keys_eq!(
+        user_stats.bonus_house_vault,
+        house_vault.key(),
+        GameError::BonusVaultMismatch
+    );
+    let amount = user_stats.unlock_bonus(ctx.accounts.config.bonus_wagering_multiple)?;
+    house_vault.bonus_pool = house_vault.bonus_pool.saturating_sub(amount);
+    emit!(pay_from_vault(
+        &house_vault.to_account_info(),
+        &ctx.accounts.user.to_account_info(),
+        amount,
+        FlowReason::BonusClaim,
+    )?);
+
+    emit!(BonusClaimedEvent {
+        house_vault: house_vault.key(),
+        user: user_stats.user,
+        amount,
+        total_bet: user_stats.total_bet,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct ClaimBonus<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// Default difficulty config, read for bonus_wagering_multiple
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump = user_stats.bump,
+        has_one = user,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    /// Claims a 1 SOL bonus with 10x wagering after `total_bet` lamports were wagered
+    /// Returns the result with the user's lamports and the vault's bonus_pool afterwards
+    fn claim_after(total_bet: u64) -> (Result<()>, u64, u64) {
+        install_syscall_stubs();
+        let mut user_acc = TestAccount::wallet(0);
+        let mut config_acc = TestAccount::program(
+            &GameConfig {
+                bonus_wagering_multiple: 10,
+                ..test_config(5)
+            },
+            1_000_000,
+        );
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                bonus_pool: 1_000_000_000,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut stats_acc = TestAccount::program(
+            &UserStats {
+                user: user_acc.key,
+                total_bet,
+                bonus_locked_lamports: 1_000_000_000,
+                bonus_house_vault: vault_acc.key,
+                ..test_user_stats()
+            },
+            1_000_000,
+        );
+        let user_info = user_acc.info();
+        let config_info = config_acc.info();
+        let vault_info = vault_acc.info();
+        let stats_info = stats_acc.info();
+        let mut accounts = ClaimBonus {
+            user: Signer::try_from(&user_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            user_stats: Account::try_from(&stats_info).unwrap(),
+        };
+        let result = claim_bonus(Context::new(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            Default::default(),
+        ));
+        (
+            result,
+            user_info.lamports(),
+            accounts.house_vault.bonus_pool,
+        )
+    }
+
+    #[test]
+    fn test_bonus_stays_locked_until_wagered() {
+        let (result, user_lamports, bonus_pool) = claim_after(9_999_999_999);
+        assert_eq!(result.unwrap_err(), GameError::BonusWageringNotMet.into());
+        assert_eq!(user_lamports, 0);
+        assert_eq!(bonus_pool, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_bonus_paid_once_wagered() {
+        let (result, user_lamports, bonus_pool) = claim_after(10_000_000_000);
+        result.unwrap();
+        assert_eq!(user_lamports, 1_000_000_000);
+        assert_eq!(bonus_pool, 0);
+    }
+}
@@ -1,20 +1,20 @@
 use crate::errors::GameError;
-use crate::events::SessionCleanedEvent;
+use crate::events::{FlowReason, SessionCleanedEvent};
 use crate::states::*;
+use crate::util::pay_from_vault;
 use anchor_lang::prelude::*;
 
-/// Timeout duration in slots
-/// Reduced to 5 minutes for faster cleanup (750 slots at 400ms per slot)
-/// This prevents capital from being locked for too long in abandoned sessions
-pub const TIMEOUT_SLOTS: u64 = 750;
+use super::cash_out::record_payout_streak;
 
 /// Cleans up an expired session that was abandoned by the player
 ///
-/// This is a permissionless instruction that:
-/// 1. Checks if session has been inactive for TIMEOUT_SLOTS
+/// This is permissionless unless the vault names an authorized_crank. It:
+/// 1. Checks the session is past its expires_at_slot deadline
 /// 2. Releases reserved funds back to house vault
-/// 3. Closes the session account
-/// 4. Sends rent to the caller as incentive
+/// 3. In AutoCashOut mode, refunds the session's current treasure to the player, who never
+///    lost a roll, when cash_out would have let them exit; a locked vault refuses the refund
+/// 4. Closes the session account
+/// 5. Sends rent, plus config.crank_reward_lamports when the vault can spare it, to the caller
 ///
 /// This prevents capital from being locked forever in abandoned sessions
 pub fn clean_expired_session(ctx: Context<CleanExpired>) -> Result<()> {
@@ -23,12 +23,7 @@ pub fn clean_expired_session(ctx: Context<CleanExpired>) -> Result<()> {
     let clock = Clock::get()?;
 
     // Verify session has expired
-    let slots_inactive = clock
-        .slot
-        .checked_sub(session.last_active_slot)
-        .ok_or(GameError::Overflow)?;
-
-    require!(slots_inactive > TIMEOUT_SLOTS, GameError::SessionNotExpired);
+    let slots_inactive = session.expired_slots(clock.slot)?;
 
     // Only clean Active sessions (Lost/CashedOut already closed)
     require!(
@@ -36,21 +31,142 @@ pub fn clean_expired_session(ctx: Context<CleanExpired>) -> Result<()> {
         GameError::InvalidSessionStatus
     );
 
-    // Release reserved funds
-    house_vault.release(session.max_payout)?;
+    // Release reserved funds and, in AutoCashOut mode, refund the player's stake
+    let refunded_amount = release_expired(
+        &ctx.accounts.config,
+        session,
+        house_vault,
+        &mut ctx.accounts.user_stats,
+        &mut ctx.accounts.house_stats,
+        &ctx.accounts.user.to_account_info(),
+        &clock,
+    )?;
+    ctx.accounts.user_stats.close_session();
+
+    let rent_exempt = Rent::get()?.minimum_balance(house_vault.to_account_info().data_len());
+    let crank_reward = pay_crank_reward(
+        ctx.accounts.config.crank_reward_lamports,
+        house_vault,
+        &ctx.accounts.crank.to_account_info(),
+        &session.user,
+        rent_exempt,
+    )?;
 
     // Emit cleanup event for monitoring
     emit!(SessionCleanedEvent {
         session: session.key(),
         user: session.user,
         crank: ctx.accounts.crank.key(),
-        released_amount: session.max_payout,
+        released_amount: session.reserved_amount(),
+        refunded_amount,
         slots_inactive,
+        crank_reward,
     });
+    ctx.accounts.config.close_sessions(1);
 
     Ok(())
 }
 
+/// Ends an expired session the way config.expiry_mode asks, returning the treasure refunded
+/// A refund goes through cash_out's gates and bookkeeping: it needs an unlocked vault and a
+/// session cash_out would let exit, and is booked as a win, a house payout and toward the
+/// circuit breaker. Sessions that couldn't exit only release their reservation.
+/// A session left idle on an unplayed VRF request is never cashed out, or walking away from
+/// a bad roll would be as good as cashing out before it
+pub(crate) fn release_expired<'info>(
+    config: &GameConfig,
+    session: &GameSession,
+    house_vault: &mut Account<'info, HouseVault>,
+    user_stats: &mut UserStats,
+    house_stats: &mut HouseStats,
+    user: &AccountInfo<'info>,
+    clock: &Clock,
+) -> Result<u64> {
+    match config.expiry_mode {
+        ExpiryMode::AutoCashOut if session.pending_vrf.is_none() && may_exit(config, session) => {
+            // Letting a session expire must not get past the lock or the circuit breaker
+            require!(!house_vault.locked, GameError::HouseLocked);
+            release_and_refund(session, house_vault, user)?;
+            user_stats.record_win(
+                session.current_treasure,
+                session.total_withdrawn,
+                session.dive_number,
+            )?;
+            house_stats.record_payout(session.current_treasure)?;
+            record_payout_streak(config, house_vault, session.current_treasure, clock);
+            Ok(session.current_treasure)
+        }
+        _ => {
+            house_vault.close_session();
+            house_vault.release(session.reserved_amount())?;
+            Ok(0)
+        }
+    }
+}
+
+/// Whether cash_out's min_dives_to_cash_out and allow_breakeven_exit rules let the session exit
+fn may_exit(config: &GameConfig, session: &GameSession) -> bool {
+    if session.current_treasure <= session.bet_amount {
+        config.allow_breakeven_exit
+    } else {
+        session.dive_number >= config.min_dives_to_cash_out
+    }
+}
+
+/// Releases the session's reservation and pays its current treasure from the vault to the user
+/// Also frees the session's slot in the vault's active_sessions count
+pub(crate) fn release_and_refund<'info>(
+    session: &GameSession,
+    house_vault: &mut Account<'info, HouseVault>,
+    user: &AccountInfo<'info>,
+) -> Result<()> {
+    let vault_info = house_vault.to_account_info();
+    let vault_balance = vault_info.lamports();
+    require!(
+        vault_balance >= session.current_treasure,
+        GameError::InsufficientVaultBalance
+    );
+
+    // Manual lamport transfer: the vault carries data
+    emit!(pay_from_vault(
+        &vault_info,
+        user,
+        session.current_treasure,
+        FlowReason::ExpiryRefund
+    )?);
+
+    house_vault.close_session();
+    house_vault.release(session.reserved_amount())
+}
+
+/// Pays `reward` from the vault to the crank if it fits in the vault's withdrawable balance
+/// Returns the amount paid; a vault that can't spare it pays nothing rather than failing
+/// A player cranking their own session gets no reward, or idling out would earn a bonus
+pub(crate) fn pay_crank_reward<'info>(
+    reward: u64,
+    house_vault: &Account<'info, HouseVault>,
+    crank: &AccountInfo<'info>,
+    session_user: &Pubkey,
+    rent_exempt: u64,
+) -> Result<u64> {
+    let vault_info = house_vault.to_account_info();
+    let vault_balance = vault_info.lamports();
+    if reward == 0
+        || crank.key == session_user
+        || house_vault.withdrawable(vault_balance, rent_exempt) < reward
+    {
+        return Ok(0);
+    }
+
+    emit!(pay_from_vault(
+        &vault_info,
+        crank,
+        reward,
+        FlowReason::CrankReward
+    )?);
+    Ok(reward)
+}
+
 #[derive(Accounts)]
 pub struct CleanExpired<'info> {
     /// The crank/keeper calling this instruction
@@ -58,16 +174,399 @@ pub struct CleanExpired<'info> {
     #[account(mut)]
     pub crank: Signer<'info>,
 
+    #[account(
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
     /// The house vault to release funds to
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = house_vault.crank_allowed(&crank.key()) @ GameError::UnauthorizedCrank,
+    )]
     pub house_vault: Account<'info, HouseVault>,
 
+    /// CHECK: The session owner receiving the treasure refund, validated by has_one on session
+    #[account(mut)]
+    pub user: UncheckedAccount<'info>,
+
     /// The expired session to clean up
     /// Account closes automatically and rent goes to crank
     #[account(
         mut,
         has_one = house_vault,
+        has_one = user,
         close = crank,
     )]
     pub session: Account<'info, GameSession>,
+
+    /// The session owner's stats, so the closed session stops counting as active
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    /// The vault's stats, which book a refund as a payout
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use anchor_lang::Bumps;
+    use std::collections::BTreeSet;
+
+    /// Runs Anchor's account validation for clean_expired_session with `crank` signing
+    fn validate_accounts(authorized_crank: Option<Pubkey>, crank: Pubkey) -> Result<()> {
+        let (config_key, config_bump) =
+            Pubkey::find_program_address(&[GAME_CONFIG_SEED.as_bytes()], &crate::ID);
+        let mut config_acc = TestAccount::program(
+            &GameConfig {
+                bump: config_bump,
+                ..test_config(5)
+            },
+            1_000_000,
+        );
+        config_acc.key = config_key;
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                authorized_crank,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut user_acc = TestAccount::wallet(0);
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                user: user_acc.key,
+                house_vault: vault_acc.key,
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let (user_stats_key, user_stats_bump) = Pubkey::find_program_address(
+            &[USER_STATS_SEED.as_bytes(), user_acc.key.as_ref()],
+            &crate::ID,
+        );
+        let mut user_stats_acc = TestAccount::program(
+            &UserStats {
+                bump: user_stats_bump,
+                ..test_user_stats()
+            },
+            1_000_000,
+        );
+        user_stats_acc.key = user_stats_key;
+        let (house_stats_key, house_stats_bump) = Pubkey::find_program_address(
+            &[HOUSE_STATS_SEED.as_bytes(), vault_acc.key.as_ref()],
+            &crate::ID,
+        );
+        let mut house_stats_acc = TestAccount::program(
+            &HouseStats {
+                bump: house_stats_bump,
+                ..test_house_stats()
+            },
+            1_000_000,
+        );
+        house_stats_acc.key = house_stats_key;
+        let mut crank_acc = TestAccount::wallet(0);
+        crank_acc.key = crank;
+        let infos = [
+            crank_acc.info(),
+            config_acc.info(),
+            vault_acc.info(),
+            user_acc.info(),
+            session_acc.info(),
+            user_stats_acc.info(),
+            house_stats_acc.info(),
+        ];
+        let mut accounts: &[AccountInfo] = &infos;
+        let mut bumps = <CleanExpired as Bumps>::Bumps::default();
+        CleanExpired::try_accounts(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            &mut bumps,
+            &mut BTreeSet::new(),
+        )
+        .map(|_| ())
+    }
+
+    #[test]
+    fn test_any_crank_may_clean_by_default() {
+        assert!(validate_accounts(None, Pubkey::new_unique()).is_ok());
+    }
+
+    #[test]
+    fn test_authorized_crank_restricts_cleanup() {
+        let crank = Pubkey::new_unique();
+        assert!(validate_accounts(Some(crank), crank).is_ok());
+
+        let err = validate_accounts(Some(crank), Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, GameError::UnauthorizedCrank.into());
+    }
+
+    #[test]
+    fn test_refund_returns_treasure_to_user() {
+        let session = GameSession {
+            current_treasure: 19_000_000,
+            ..test_session()
+        };
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                active_sessions: 1,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut user_acc = TestAccount::wallet(1_000);
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        release_and_refund(&session, &mut vault, &user_info).unwrap();
+
+        assert_eq!(user_info.lamports(), 19_001_000);
+        assert_eq!(vault_info.lamports(), 5_000_000_000 - 19_000_000);
+        assert_eq!(vault.total_reserved, 0);
+        assert_eq!(vault.active_sessions, 0);
+    }
+
+    /// Expires `session` from a 5 SOL vault, returning the refund, the vault's locked flag and
+    /// the vault and user balances afterwards
+    fn expire(
+        config: &GameConfig,
+        session: &GameSession,
+        vault: HouseVault,
+    ) -> Result<(u64, bool, u64, u64)> {
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                active_sessions: 1,
+                ..vault
+            },
+            5_000_000_000,
+        );
+        let mut user_acc = TestAccount::wallet(1_000);
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let mut user_stats = test_user_stats();
+        let mut house_stats = test_house_stats();
+        let refunded = release_expired(
+            config,
+            session,
+            &mut vault,
+            &mut user_stats,
+            &mut house_stats,
+            &user_info,
+            &test_clock(1_000),
+        )?;
+        assert_eq!(vault.total_reserved, 0);
+        assert_eq!(vault.active_sessions, 0);
+        // Whatever is refunded is booked on both sides
+        assert_eq!(user_stats.total_won, refunded);
+        assert_eq!(house_stats.total_paid_out, refunded);
+        Ok((
+            refunded,
+            vault.locked,
+            vault_info.lamports(),
+            user_info.lamports(),
+        ))
+    }
+
+    fn auto_cash_out() -> GameConfig {
+        GameConfig {
+            expiry_mode: ExpiryMode::AutoCashOut,
+            ..test_config(5)
+        }
+    }
+
+    #[test]
+    fn test_expiry_modes_fund_flows() {
+        let idle = GameSession {
+            current_treasure: 19_000_000,
+            ..test_session()
+        };
+        let awaiting_vrf = GameSession {
+            pending_vrf: Some(Pubkey::new_unique()),
+            vrf_request_slot: 101,
+            ..idle.clone()
+        };
+        let refunded = (19_000_000, false, 5_000_000_000 - 19_000_000, 19_001_000);
+        let released = (0, false, 5_000_000_000, 1_000);
+
+        assert_eq!(
+            expire(&auto_cash_out(), &idle, test_vault()).unwrap(),
+            refunded
+        );
+        // ReleaseOnly is the default: the treasure stays in the vault as unreserved balance
+        assert_eq!(
+            expire(&test_config(5), &idle, test_vault()).unwrap(),
+            released
+        );
+        // Abandoning an unplayed VRF roll forfeits the treasure like a loss
+        assert_eq!(
+            expire(&auto_cash_out(), &awaiting_vrf, test_vault()).unwrap(),
+            released
+        );
+    }
+
+    #[test]
+    fn test_auto_cash_out_applies_cash_out_gates() {
+        let idle = GameSession {
+            current_treasure: 19_000_000,
+            dive_number: 1,
+            ..test_session()
+        };
+        let released = (0, false, 5_000_000_000, 1_000);
+
+        // A session short of min_dives_to_cash_out couldn't have cashed out either
+        let min_dives = GameConfig {
+            min_dives_to_cash_out: 2,
+            ..auto_cash_out()
+        };
+        assert_eq!(expire(&min_dives, &idle, test_vault()).unwrap(), released);
+
+        // An untouched bet is only refunded when breakeven exits are allowed
+        let breakeven = test_session();
+        assert_eq!(
+            expire(&auto_cash_out(), &breakeven, test_vault()).unwrap(),
+            released
+        );
+        let allowed = GameConfig {
+            allow_breakeven_exit: true,
+            ..auto_cash_out()
+        };
+        assert_eq!(
+            expire(&allowed, &breakeven, test_vault()).unwrap().0,
+            breakeven.bet_amount
+        );
+    }
+
+    #[test]
+    fn test_auto_cash_out_respects_lock_and_breaker() {
+        let idle = GameSession {
+            current_treasure: 19_000_000,
+            ..test_session()
+        };
+        let locked = HouseVault {
+            locked: true,
+            ..test_vault()
+        };
+        let err = expire(&auto_cash_out(), &idle, locked).err().unwrap();
+        assert_eq!(err, GameError::HouseLocked.into());
+
+        // The refund counts toward the circuit breaker like a cash out
+        let breaker = GameConfig {
+            big_payout_lamports: 10_000_000,
+            max_consecutive_big_payouts: 1,
+            ..auto_cash_out()
+        };
+        let streak = HouseVault {
+            consecutive_big_payouts: 1,
+            ..test_vault()
+        };
+        let (refunded, locked, _, _) = expire(&breaker, &idle, streak).unwrap();
+        assert_eq!(refunded, 19_000_000);
+        assert!(locked);
+    }
+
+    #[test]
+    fn test_refund_requires_vault_balance() {
+        let session = GameSession {
+            current_treasure: 19_000_000,
+            ..test_session()
+        };
+        let mut vault_acc = TestAccount::program(&test_vault(), 1_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        let err = release_and_refund(&session, &mut vault, &user_info).unwrap_err();
+        assert_eq!(err, GameError::InsufficientVaultBalance.into());
+        assert_eq!(user_info.lamports(), 0);
+    }
+
+    #[test]
+    fn test_crank_reward_paid_from_free_balance() {
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                total_reserved: 4_000_000_000,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        // Rent already moved over by the session close
+        let mut crank_acc = TestAccount::wallet(2_000_000);
+        let vault_info = vault_acc.info();
+        let crank_info = crank_acc.info();
+        let vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        let paid =
+            pay_crank_reward(5_000, &vault, &crank_info, &Pubkey::new_unique(), 1_000_000).unwrap();
+
+        assert_eq!(paid, 5_000);
+        assert_eq!(crank_info.lamports(), 2_000_000 + 5_000);
+        assert_eq!(vault_info.lamports(), 5_000_000_000 - 5_000);
+    }
+
+    #[test]
+    fn test_crank_reward_skipped_when_vault_cannot_spare_it() {
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                total_reserved: 4_999_000_000,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut crank_acc = TestAccount::wallet(2_000_000);
+        let vault_info = vault_acc.info();
+        let crank_info = crank_acc.info();
+        let vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        // 1 SOL over reserved, but all of it is the rent-exempt minimum
+        let paid = pay_crank_reward(
+            5_000,
+            &vault,
+            &crank_info,
+            &Pubkey::new_unique(),
+            1_000_000_000,
+        )
+        .unwrap();
+
+        assert_eq!(paid, 0);
+        assert_eq!(crank_info.lamports(), 2_000_000);
+        assert_eq!(vault_info.lamports(), 5_000_000_000);
+    }
+
+    #[test]
+    fn test_own_session_earns_no_crank_reward() {
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                total_reserved: 0,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut crank_acc = TestAccount::wallet(2_000_000);
+        let vault_info = vault_acc.info();
+        let crank_info = crank_acc.info();
+        let vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        let paid = pay_crank_reward(5_000, &vault, &crank_info, crank_info.key, 1_000_000).unwrap();
+
+        assert_eq!(paid, 0);
+        assert_eq!(crank_info.lamports(), 2_000_000);
+        assert_eq!(vault_info.lamports(), 5_000_000_000);
+    }
 }
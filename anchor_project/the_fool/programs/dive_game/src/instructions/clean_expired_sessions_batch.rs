@@ -0,0 +1,417 @@
+use crate::errors::GameError;
+use crate::events::SessionCleanedEvent;
+use crate::states::*;
+use crate::util::close_session_to;
+use anchor_lang::prelude::*;
+
+use super::clean_expired_session::{pay_crank_reward, release_expired};
+
+/// Cleans every expired session passed in remaining_accounts in one transaction
+///
+/// remaining_accounts holds `[session, user, user_stats]` triples. Each session must be a
+/// writable GameSession belonging to `house_vault` followed by its user and the user's
+/// writable UserStats; anything else fails the batch. Sessions that are not yet expired (or
+/// no longer Active) are skipped so one stale entry doesn't abort the rest. Treasure is
+/// refunded as clean_expired_session would, and rent of every cleaned session goes to the
+/// crank, with config.crank_reward_lamports per session when the vault can spare it.
+pub fn clean_expired_sessions_batch<'info>(
+    ctx: Context<'_, '_, 'info, 'info, CleanExpiredBatch<'info>>,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let rent_exempt =
+        Rent::get()?.minimum_balance(ctx.accounts.house_vault.to_account_info().data_len());
+    let cleaned = clean_sessions(
+        &ctx.accounts.config,
+        &mut ctx.accounts.house_vault,
+        &mut ctx.accounts.house_stats,
+        &ctx.accounts.crank.to_account_info(),
+        ctx.remaining_accounts,
+        rent_exempt,
+        &clock,
+    )?;
+    ctx.accounts.config.close_sessions(cleaned as u64);
+    msg!(
+        "BATCH_CLEANED cleaned={} skipped={}",
+        cleaned,
+        (ctx.remaining_accounts.len() / 3) as u32 - cleaned
+    );
+    Ok(())
+}
+
+/// Refunds, releases and closes each expired session, returning how many were cleaned
+pub(crate) fn clean_sessions<'info>(
+    config: &GameConfig,
+    house_vault: &mut Account<'info, HouseVault>,
+    house_stats: &mut HouseStats,
+    crank: &AccountInfo<'info>,
+    accounts: &'info [AccountInfo<'info>],
+    rent_exempt: u64,
+    clock: &Clock,
+) -> Result<u32> {
+    require!(
+        accounts.len().is_multiple_of(3),
+        anchor_lang::error::ErrorCode::AccountNotEnoughKeys
+    );
+    let mut cleaned = 0u32;
+    for triple in accounts.chunks(3) {
+        let (info, user, stats_info) = (&triple[0], &triple[1], &triple[2]);
+        require!(info.is_writable, GameError::InvalidSessionStatus);
+        let session = Account::<GameSession>::try_from(info)?;
+        require_keys_eq!(session.house_vault, house_vault.key());
+        require_keys_eq!(session.user, user.key());
+        require_eq!(session.difficulty, config.difficulty);
+        // UserStats.user is only ever written by start_session for the PDA's own user
+        require!(stats_info.is_writable, GameError::InvalidSessionStatus);
+        let mut user_stats = Account::<UserStats>::try_from(stats_info)?;
+        require_keys_eq!(user_stats.user, user.key());
+
+        if session.status != SessionStatus::Active {
+            continue;
+        }
+        let Ok(slots_inactive) = session.expired_slots(clock.slot) else {
+            continue;
+        };
+
+        let refunded_amount = release_expired(
+            config,
+            &session,
+            house_vault,
+            &mut user_stats,
+            house_stats,
+            user,
+            clock,
+        )?;
+        user_stats.close_session();
+        user_stats.exit(&crate::ID)?;
+        let crank_reward = pay_crank_reward(
+            config.crank_reward_lamports,
+            house_vault,
+            crank,
+            &session.user,
+            rent_exempt,
+        )?;
+
+        emit!(SessionCleanedEvent {
+            session: session.key(),
+            user: session.user,
+            crank: crank.key(),
+            released_amount: session.reserved_amount(),
+            refunded_amount,
+            slots_inactive,
+            crank_reward,
+        });
+
+        // Close to the crank: move rent and wipe the data so the account can't be reused
+        close_session_to(crank, info)?;
+        info.try_borrow_mut_data()?.fill(0);
+
+        cleaned += 1;
+    }
+    Ok(cleaned)
+}
+
+#[derive(Accounts)]
+pub struct CleanExpiredBatch<'info> {
+    /// The crank/keeper calling this instruction
+    /// Receives the rent of every cleaned session as incentive
+    #[account(mut)]
+    pub crank: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&config.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    /// The house vault every session in remaining_accounts must belong to
+    #[account(
+        mut,
+        constraint = house_vault.crank_allowed(&crank.key()) @ GameError::UnauthorizedCrank,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+
+    /// The vault's stats, which book refunds as payouts
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn session_for(vault: Pubkey, user: Pubkey, last_active_slot: u64) -> TestAccount {
+        let mut session = GameSession {
+            user,
+            house_vault: vault,
+            current_treasure: 19_000_000,
+            ..test_session()
+        };
+        session.record_activity(last_active_slot, test_config(5).timeout_slots);
+        TestAccount::program(&session, 2_000_000)
+    }
+
+    fn auto_cash_out() -> GameConfig {
+        GameConfig {
+            expiry_mode: ExpiryMode::AutoCashOut,
+            ..test_config(5)
+        }
+    }
+
+    fn stats_for(user: Pubkey, active_session_count: u16) -> TestAccount {
+        let stats = UserStats {
+            user,
+            active_session_count,
+            ..test_user_stats()
+        };
+        TestAccount::program(&stats, 1_000_000)
+    }
+
+    #[test]
+    fn test_batch_pays_crank_rent_plus_reward() {
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                total_reserved: 2_000_000_000,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let vault_key = vault_acc.key;
+        let mut user_a = TestAccount::wallet(0);
+        let mut user_b = TestAccount::wallet(0);
+        let mut expired_a = session_for(vault_key, user_a.key, 0);
+        let mut expired_b = session_for(vault_key, user_b.key, 100);
+        let mut stats_a = stats_for(user_a.key, 1);
+        let mut stats_b = stats_for(user_b.key, 1);
+        let mut crank_acc = TestAccount::wallet(0);
+        let vault_info = vault_acc.info();
+        let crank_info = crank_acc.info();
+        let accounts = [
+            expired_a.info(),
+            user_a.info(),
+            stats_a.info(),
+            expired_b.info(),
+            user_b.info(),
+            stats_b.info(),
+        ];
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let config = GameConfig {
+            crank_reward_lamports: 5_000,
+            ..auto_cash_out()
+        };
+
+        let cleaned = clean_sessions(
+            &config,
+            &mut vault,
+            &mut test_house_stats(),
+            &crank_info,
+            &accounts,
+            1_000_000,
+            &test_clock(1_000),
+        )
+        .unwrap();
+
+        assert_eq!(cleaned, 2);
+        assert_eq!(crank_info.lamports(), 2 * (2_000_000 + 5_000));
+        assert_eq!(
+            vault_info.lamports(),
+            5_000_000_000 - 2 * 19_000_000 - 2 * 5_000
+        );
+    }
+
+    #[test]
+    fn test_batch_cleans_expired_and_skips_fresh() {
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                total_reserved: 3_000_000_000,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let vault_key = vault_acc.key;
+        let mut user_a = TestAccount::wallet(0);
+        let mut user_b = TestAccount::wallet(0);
+        let mut expired_a = session_for(vault_key, user_a.key, 0);
+        let mut fresh = session_for(vault_key, user_b.key, 900);
+        let mut expired_b = session_for(vault_key, user_b.key, 100);
+        let mut user_b_again = TestAccount {
+            key: user_b.key,
+            ..TestAccount::wallet(0)
+        };
+        let mut stats_a = stats_for(user_a.key, 1);
+        let mut stats_b = stats_for(user_b.key, 2);
+        let mut stats_b_again = TestAccount {
+            key: stats_b.key,
+            ..stats_for(user_b.key, 2)
+        };
+        let mut crank_acc = TestAccount::wallet(0);
+        let vault_info = vault_acc.info();
+        let crank_info = crank_acc.info();
+        let accounts = [
+            expired_a.info(),
+            user_a.info(),
+            stats_a.info(),
+            fresh.info(),
+            user_b_again.info(),
+            stats_b_again.info(),
+            expired_b.info(),
+            user_b.info(),
+            stats_b.info(),
+        ];
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let mut house_stats = test_house_stats();
+
+        let cleaned = clean_sessions(
+            &auto_cash_out(),
+            &mut vault,
+            &mut house_stats,
+            &crank_info,
+            &accounts,
+            0,
+            &test_clock(1_000),
+        )
+        .unwrap();
+
+        assert_eq!(cleaned, 2);
+        assert_eq!(house_stats.total_paid_out, 38_000_000);
+        assert_eq!(vault.total_reserved, 1_000_000_000);
+        assert_eq!(vault_info.lamports(), 5_000_000_000 - 38_000_000);
+        // Crank gets only the rent, users get their treasure
+        assert_eq!(crank_info.lamports(), 4_000_000);
+        assert_eq!(accounts[1].lamports(), 19_000_000);
+        assert_eq!(accounts[4].lamports(), 0);
+        assert_eq!(accounts[7].lamports(), 19_000_000);
+        assert_eq!(accounts[0].lamports(), 0);
+        assert_eq!(accounts[6].lamports(), 0);
+        assert_eq!(accounts[3].lamports(), 2_000_000);
+        assert!(accounts[0]
+            .try_borrow_data()
+            .unwrap()
+            .iter()
+            .all(|b| *b == 0));
+        assert!(Account::<GameSession>::try_from(&accounts[3]).is_ok());
+        // Only cleaned sessions stop counting as active
+        let stats_a = Account::<UserStats>::try_from(&accounts[2]).unwrap();
+        let stats_b = Account::<UserStats>::try_from(&accounts[8]).unwrap();
+        assert_eq!(stats_a.active_session_count, 0);
+        assert_eq!(stats_b.active_session_count, 1);
+        assert_eq!(stats_a.total_won, 19_000_000);
+        assert_eq!(stats_b.total_won, 19_000_000);
+    }
+
+    #[test]
+    fn test_batch_rejects_session_of_other_vault() {
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut user = TestAccount::wallet(0);
+        let mut foreign = session_for(Pubkey::new_unique(), user.key, 0);
+        let mut stats = stats_for(user.key, 1);
+        let mut crank_acc = TestAccount::wallet(0);
+        let vault_info = vault_acc.info();
+        let crank_info = crank_acc.info();
+        let accounts = [foreign.info(), user.info(), stats.info()];
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        assert!(clean_sessions(
+            &test_config(5),
+            &mut vault,
+            &mut test_house_stats(),
+            &crank_info,
+            &accounts,
+            0,
+            &test_clock(1_000)
+        )
+        .is_err());
+        assert_eq!(accounts[0].lamports(), 2_000_000);
+    }
+
+    #[test]
+    fn test_batch_rejects_wrong_user() {
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let vault_key = vault_acc.key;
+        let mut session = session_for(vault_key, Pubkey::new_unique(), 0);
+        let mut impostor = TestAccount::wallet(0);
+        let mut stats = stats_for(impostor.key, 1);
+        let mut crank_acc = TestAccount::wallet(0);
+        let vault_info = vault_acc.info();
+        let crank_info = crank_acc.info();
+        let accounts = [session.info(), impostor.info(), stats.info()];
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        assert!(clean_sessions(
+            &test_config(5),
+            &mut vault,
+            &mut test_house_stats(),
+            &crank_info,
+            &accounts,
+            0,
+            &test_clock(1_000)
+        )
+        .is_err());
+        assert_eq!(accounts[1].lamports(), 0);
+    }
+
+    #[test]
+    fn test_batch_rejects_other_users_stats() {
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let vault_key = vault_acc.key;
+        let mut user = TestAccount::wallet(0);
+        let mut session = session_for(vault_key, user.key, 0);
+        let mut stats = stats_for(Pubkey::new_unique(), 1);
+        let mut crank_acc = TestAccount::wallet(0);
+        let vault_info = vault_acc.info();
+        let crank_info = crank_acc.info();
+        let accounts = [session.info(), user.info(), stats.info()];
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        assert!(clean_sessions(
+            &test_config(5),
+            &mut vault,
+            &mut test_house_stats(),
+            &crank_info,
+            &accounts,
+            0,
+            &test_clock(1_000)
+        )
+        .is_err());
+        assert_eq!(accounts[0].lamports(), 2_000_000);
+    }
+
+    #[test]
+    fn test_batch_rejects_non_session_account() {
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut not_a_session = TestAccount::program(&test_vault(), 1_000_000);
+        let mut user = TestAccount::wallet(0);
+        let mut stats = stats_for(user.key, 1);
+        let mut crank_acc = TestAccount::wallet(0);
+        let vault_info = vault_acc.info();
+        let crank_info = crank_acc.info();
+        let accounts = [not_a_session.info(), user.info(), stats.info()];
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        assert!(clean_sessions(
+            &test_config(5),
+            &mut vault,
+            &mut test_house_stats(),
+            &crank_info,
+            &accounts,
+            0,
+            &test_clock(1_000)
+        )
+        .is_err());
+        assert!(clean_sessions(
+            &test_config(5),
+            &mut vault,
+            &mut test_house_stats(),
+            &crank_info,
+            &accounts[..2],
+            0,
+            &test_clock(1_000)
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,32 @@
+use crate::errors::GameError;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Stores the game keeper's commitment to the server seed for the next round
+/// The seed is revealed in a later slot through play_round
+pub fn commit_round(ctx: Context<CommitRound>, commitment: [u8; 32]) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let clock = Clock::get()?;
+
+    require!(!ctx.accounts.house_vault.locked, GameError::HouseLocked);
+    session.ensure_active()?;
+
+    session.set_commitment(commitment, clock.slot);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct CommitRound<'info> {
+    pub game_keeper: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = house_vault,
+        constraint = session.keeper == game_keeper.key() @ GameError::UnauthorizedKeeper,
+    )]
+    pub session: Account<'info, GameSession>,
+
+    #[account(has_one = game_keeper @ GameError::UnauthorizedKeeper)]
+    pub house_vault: Account<'info, HouseVault>,
+}
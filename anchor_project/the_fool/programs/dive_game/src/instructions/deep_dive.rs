@@ -0,0 +1,206 @@
+use crate::rng;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+use super::auto_play::run_auto_play;
+use super::cash_out::{pay_out_session, referrer_account};
+use super::play_round::{prepare_round, PlayRound};
+
+/// All-or-nothing: rolls every remaining dive up to `config.max_dives` in one call
+/// Surviving all of them cashes out the treasure the curve reached at max_dives immediately,
+/// capped at max_payout like any cash out; the first failed roll closes the session as a loss
+/// exactly like play_round
+/// Rolls are independent per dive via `rng::random_roll_bps(seed, dive_number)`
+pub fn deep_dive<'info>(
+    ctx: Context<'_, '_, 'info, 'info, PlayRound<'info>>,
+    server_seed: u64,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = ctx.accounts;
+    let slot_hash = prepare_round(accounts, server_seed, &clock)?;
+    let seed = rng::generate_seed(&slot_hash, &accounts.session.key(), server_seed);
+    let referrer = referrer_account(accounts.session.referrer, ctx.remaining_accounts)?;
+
+    run_deep_dive(
+        &accounts.config,
+        &mut accounts.session,
+        &mut accounts.house_vault,
+        &mut accounts.user_stats,
+        &mut accounts.house_stats,
+        &mut accounts.leaderboard,
+        &accounts.user.to_account_info(),
+        referrer,
+        &seed,
+        &clock,
+    )?;
+    accounts.config.release_ended(&accounts.session);
+
+    Ok(())
+}
+
+/// Plays to max_dives and pays out on full survival
+/// Returns whether the player won
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn run_deep_dive<'info>(
+    config: &GameConfig,
+    session: &mut Account<'info, GameSession>,
+    house_vault: &mut Account<'info, HouseVault>,
+    user_stats: &mut UserStats,
+    house_stats: &mut HouseStats,
+    leaderboard: &mut Leaderboard,
+    user: &AccountInfo<'info>,
+    referrer: Option<&AccountInfo<'info>>,
+    seed: &[u8; 32],
+    clock: &Clock,
+) -> Result<bool> {
+    if !run_auto_play(
+        config,
+        session,
+        house_vault,
+        user_stats,
+        house_stats,
+        user,
+        seed,
+        session.max_dives(config),
+        clock,
+    )? {
+        return Ok(false);
+    }
+
+    pay_out_session(
+        config,
+        session,
+        house_vault,
+        user_stats,
+        house_stats,
+        leaderboard,
+        user,
+        referrer,
+        clock,
+    )?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game_math;
+    use crate::rng;
+    use crate::test_utils::*;
+
+    /// Runs a deep dive on a fresh test_session and returns (won, session lamports, user lamports, vault lamports)
+    fn deep_dive_with(config: &GameConfig, seed: &[u8; 32]) -> (bool, u64, u64, u64) {
+        let mut session_acc = TestAccount::program(&test_session(), 2_000_000);
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let mut stats = test_user_stats();
+        let mut house_stats = test_house_stats();
+        let mut leaderboard = test_leaderboard();
+
+        let won = run_deep_dive(
+            config,
+            &mut session,
+            &mut vault,
+            &mut stats,
+            &mut house_stats,
+            &mut leaderboard,
+            &user_info,
+            None,
+            seed,
+            &test_clock(100),
+        )
+        .unwrap();
+
+        assert_eq!(vault.total_reserved, 0);
+        if won {
+            let payout = game_math::treasure_for_dive(config, session.bet_amount, config.max_dives);
+            assert_eq!(session.current_treasure, payout);
+            assert_eq!(stats.total_won, payout);
+            assert_eq!(house_stats.total_paid_out, payout);
+            assert_eq!(leaderboard.entries[0].payout, payout);
+            assert_eq!(leaderboard.entries[0].user, session.user);
+        } else {
+            assert_eq!(stats.total_lost, session.bet_amount);
+            assert_eq!(house_stats.total_losses, 1);
+        }
+        (
+            won,
+            session_info.lamports(),
+            user_info.lamports(),
+            vault_info.lamports(),
+        )
+    }
+
+    #[test]
+    fn test_surviving_every_dive_pays_curve_treasure() {
+        let config = fixed_odds_config(1_000_000, 5);
+        let (won, session_lamports, user_lamports, vault_lamports) =
+            deep_dive_with(&config, &[3u8; 32]);
+
+        // 1.9^5 of the 0.01 SOL bet, well short of the 100x cap
+        let payout = game_math::treasure_for_dive(&config, 10_000_000, 5);
+        assert!(payout < test_session().max_payout);
+        assert!(won);
+        assert_eq!(session_lamports, 0);
+        assert_eq!(user_lamports, payout + 2_000_000);
+        assert_eq!(vault_lamports, 5_000_000_000 - payout);
+    }
+
+    #[test]
+    fn test_deep_dive_ev_stays_below_bet() {
+        // 40% per dive against a 1.9x multiplier: the house keeps the edge over a full dive
+        let config = fixed_odds_config(400_000, 5);
+        let (_, _, user_lamports, _) = deep_dive_with(&fixed_odds_config(1_000_000, 5), &[3u8; 32]);
+        let payout = (user_lamports - 2_000_000) as f64;
+        let survive_all: f64 = (1..config.max_dives)
+            .map(|dive| game_math::survival_probability_bps(&config, dive) as f64 / 1_000_000.0)
+            .product();
+        let bet = test_session().bet_amount as f64;
+        assert!(payout * survive_all < bet);
+        // Paying the cap instead would hand the player more than the bet back on average
+        assert!(test_session().max_payout as f64 * survive_all > bet);
+    }
+
+    #[test]
+    fn test_any_failed_roll_loses_everything() {
+        let config = fixed_odds_config(500_000, 5);
+        let mut outcomes = [0u32; 2];
+        for i in 0..32u8 {
+            let seed = [i; 32];
+            let survives_all = (1..config.max_dives).all(|dive| {
+                rng::random_roll_bps(&seed, dive)
+                    < game_math::survival_probability_bps(&config, dive)
+            });
+            let (won, session_lamports, user_lamports, vault_lamports) =
+                deep_dive_with(&config, &seed);
+
+            assert_eq!(won, survives_all);
+            assert_eq!(session_lamports, 0);
+            if won {
+                let payout = game_math::treasure_for_dive(&config, 10_000_000, 5);
+                assert_eq!(user_lamports, payout + 2_000_000);
+                assert_eq!(vault_lamports, 5_000_000_000 - payout);
+            } else {
+                // Only the session rent comes back; the vault keeps the bet
+                assert_eq!(user_lamports, 2_000_000);
+                assert_eq!(vault_lamports, 5_000_000_000);
+            }
+            outcomes[won as usize] += 1;
+        }
+        assert!(outcomes[0] > 0 && outcomes[1] > 0);
+    }
+
+    #[test]
+    fn test_certain_loss_on_first_dive() {
+        let config = fixed_odds_config(0, 5);
+        let (won, _, user_lamports, vault_lamports) = deep_dive_with(&config, &[3u8; 32]);
+        assert!(!won);
+        assert_eq!(user_lamports, 2_000_000);
+        assert_eq!(vault_lamports, 5_000_000_000);
+    }
+}
@@ -0,0 +1,107 @@
+use crate::errors::GameError;
+use crate::events::{FlowDirection, FlowReason, HouseDepositEvent};
+use crate::states::*;
+use crate::util::vault_flow;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Tops up a house vault's bankroll
+/// Anyone may deposit (e.g. to crowdfund the house); the depositor is recorded in the event.
+/// Deposited lamports become unreserved balance withdrawable by the house authority.
+pub fn deposit_house(ctx: Context<DepositHouse>, amount: u64) -> Result<()> {
+    require!(amount > 0, GameError::InvalidDepositAmount);
+    let clock = Clock::get()?;
+
+    let transfer_ix = system_program::Transfer {
+        from: ctx.accounts.depositor.to_account_info(),
+        to: ctx.accounts.house_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+    system_program::transfer(cpi_ctx, amount)?;
+    emit!(vault_flow(
+        &ctx.accounts.house_vault.to_account_info(),
+        FlowDirection::In,
+        amount,
+        FlowReason::Deposit
+    ));
+
+    emit!(HouseDepositEvent {
+        house_vault: ctx.accounts.house_vault.key(),
+        depositor: ctx.accounts.depositor.key(),
+        amount,
+        new_balance: ctx.accounts.house_vault.to_account_info().lamports(),
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct DepositHouse<'info> {
+    #[account(mut)]
+    pub depositor: Signer<'info>,
+
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use anchor_lang::Bumps;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_any_depositor_passes_account_validation() {
+        let mut depositor = TestAccount::wallet(1_000_000_000);
+        let mut vault = TestAccount::program(
+            &HouseVault {
+                house_authority: Pubkey::new_unique(),
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut system = TestAccount::system_program();
+        let infos = [depositor.info(), vault.info(), system.info()];
+
+        let validated = validated_deposit(&infos);
+
+        assert_ne!(
+            validated.depositor.key(),
+            validated.house_vault.house_authority
+        );
+    }
+
+    fn validated_deposit<'info>(infos: &'info [AccountInfo<'info>]) -> DepositHouse<'info> {
+        let mut accounts = infos;
+        DepositHouse::try_accounts(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            &mut <DepositHouse as Bumps>::Bumps::default(),
+            &mut BTreeSet::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_zero_deposit_is_rejected() {
+        install_syscall_stubs();
+        let mut depositor = TestAccount::wallet(1_000_000_000);
+        let mut vault = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut system = TestAccount::system_program();
+        let infos = [depositor.info(), vault.info(), system.info()];
+        let mut validated = validated_deposit(&infos);
+
+        let err = deposit_house(
+            Context::new(&crate::ID, &mut validated, &[], Default::default()),
+            0,
+        )
+        .unwrap_err();
+        assert_eq!(err, GameError::InvalidDepositAmount.into());
+        assert_eq!(infos[1].lamports(), 5_000_000_000);
+    }
+}
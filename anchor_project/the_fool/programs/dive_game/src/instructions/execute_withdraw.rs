@@ -0,0 +1,85 @@
+use crate::events::WithdrawExecutedEvent;
+use anchor_lang::prelude::*;
+
+use super::withdraw_house::WithdrawHouse;
+
+/// Second step of a timelocked house withdrawal
+/// Pays the pending request once its unlock slot is reached, under the same solvency rule as
+/// withdraw_house at the time of execution
+pub fn execute_withdraw(ctx: Context<WithdrawHouse>) -> Result<()> {
+    let clock = Clock::get()?;
+    let amount = ctx
+        .accounts
+        .house_vault
+        .take_unlocked_withdraw(clock.slot)?;
+    ctx.accounts.pay_withdrawal(amount)?;
+    emit!(WithdrawExecutedEvent {
+        house_vault: ctx.accounts.house_vault.key(),
+        destination: ctx.accounts.destination().key(),
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::GameError;
+    use crate::states::*;
+    use crate::test_utils::*;
+
+    /// Executes a 1 SOL request unlocking at `unlock_slot` at the stubbed clock's slot
+    /// Returns the result with the authority's lamports afterwards
+    fn execute_at(unlock_slot: u64) -> (Result<()>, u64) {
+        install_syscall_stubs();
+        let mut authority_acc = TestAccount::wallet(0);
+        let mut config_acc = TestAccount::program(
+            &GameConfig {
+                withdraw_delay_slots: 100,
+                ..test_config(5)
+            },
+            1_000_000,
+        );
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                house_authority: authority_acc.key,
+                pending_withdraw_amount: 1_000_000_000,
+                withdraw_unlock_slot: unlock_slot,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let authority_info = authority_acc.info();
+        let config_info = config_acc.info();
+        let vault_info = vault_acc.info();
+        let mut accounts = WithdrawHouse {
+            house_authority: Signer::try_from(&authority_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            destination: None,
+            cosigner: None,
+        };
+        let result = execute_withdraw(Context::new(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            Default::default(),
+        ));
+        (result, authority_info.lamports())
+    }
+
+    #[test]
+    fn test_execute_before_unlock_is_rejected() {
+        let (result, authority_lamports) = execute_at(STUB_SLOT + 1);
+        assert_eq!(result.unwrap_err(), GameError::WithdrawalNotUnlocked.into());
+        assert_eq!(authority_lamports, 0);
+    }
+
+    #[test]
+    fn test_execute_after_unlock_pays_request() {
+        let (result, authority_lamports) = execute_at(STUB_SLOT);
+        result.unwrap();
+        assert_eq!(authority_lamports, 1_000_000_000);
+    }
+}
@@ -0,0 +1,230 @@
+use crate::errors::GameError;
+use crate::events::TreasureGambledEvent;
+use crate::game_math;
+use crate::rng;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+use super::play_round::{prepare_keeper_roll, settle_loss, PlayRound};
+
+/// Double or nothing once the session has reached max_dives
+/// A single roll wins with probability `config.final_gamble_bps`: the treasure doubles, capped
+/// where the dive curve is (max_payout after the house edge, less partial withdrawals), and
+/// the session stays active; otherwise the session is lost exactly like a failed dive
+/// The cap keeps every possible payout inside max_payout, which ReservationMode::Full reserves
+/// in full at start_session, so no extra vault coverage is needed; configs reserving under
+/// ReservationMode::Expected reject the gamble instead
+pub fn gamble_treasure(ctx: Context<PlayRound>, server_seed: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let accounts = ctx.accounts;
+    require!(
+        accounts.session.dive_number >= accounts.session.max_dives(&accounts.config),
+        GameError::GambleNotAvailable
+    );
+    let slot_hash = prepare_keeper_roll(accounts, server_seed, &clock)?;
+    let seed = rng::generate_seed(&slot_hash, &accounts.session.key(), server_seed);
+    let roll = rng::random_roll_bps(&seed, accounts.session.dive_number);
+
+    settle_gamble(
+        &accounts.config,
+        &mut accounts.session,
+        &mut accounts.house_vault,
+        &mut accounts.user_stats,
+        &mut accounts.house_stats,
+        &accounts.user.to_account_info(),
+        roll,
+        &clock,
+    )?;
+    accounts.config.release_ended(&accounts.session);
+
+    Ok(())
+}
+
+/// Treasure after a winning gamble; GambleNotAvailable if doubling can't add anything
+/// Expected-mode sessions reserve less than max_payout, which a doubled treasure could pass
+pub(crate) fn doubled_treasure(config: &GameConfig, session: &GameSession) -> Result<u64> {
+    require!(
+        config.reservation_mode == ReservationMode::Full,
+        GameError::GambleNotAvailable
+    );
+    let cap = game_math::apply_house_edge(config, session.max_payout)
+        .saturating_sub(session.total_withdrawn);
+    let doubled = session.current_treasure.saturating_mul(2).min(cap);
+    require!(
+        doubled > session.current_treasure,
+        GameError::GambleNotAvailable
+    );
+    Ok(doubled)
+}
+
+/// Applies a gamble roll; returns whether the player won
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn settle_gamble<'info>(
+    config: &GameConfig,
+    session: &mut Account<'info, GameSession>,
+    house_vault: &mut Account<'info, HouseVault>,
+    user_stats: &mut UserStats,
+    house_stats: &mut HouseStats,
+    user: &AccountInfo<'info>,
+    roll: u32,
+    clock: &Clock,
+) -> Result<bool> {
+    let doubled = doubled_treasure(config, session)?;
+    let won = roll < config.final_gamble_bps;
+
+    if won {
+        session.current_treasure = doubled;
+        session.record_activity(clock.slot, config.timeout_slots);
+    }
+    emit!(TreasureGambledEvent {
+        session: session.key(),
+        user: session.user,
+        roll,
+        win_threshold: config.final_gamble_bps,
+        won,
+        current_treasure: if won { doubled } else { 0 },
+        timestamp: clock.unix_timestamp,
+    });
+    if !won {
+        settle_loss(
+            config,
+            session,
+            house_vault,
+            user_stats,
+            house_stats,
+            user,
+            0,
+            clock,
+        )?;
+    }
+    Ok(won)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn gamble_with(treasure: u64, roll: u32) -> (Result<bool>, GameSession, HouseVault, u64) {
+        let config = test_config(5);
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                dive_number: 5,
+                current_treasure: treasure,
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+
+        let result = settle_gamble(
+            &config,
+            &mut session,
+            &mut vault,
+            &mut test_user_stats(),
+            &mut test_house_stats(),
+            &user_info,
+            roll,
+            &test_clock(100),
+        );
+        (
+            result,
+            (*session).clone(),
+            (*vault).clone(),
+            user_info.lamports(),
+        )
+    }
+
+    #[test]
+    fn test_winning_gamble_doubles_treasure() {
+        let (result, session, vault, user_lamports) = gamble_with(80_000_000, 499_999);
+        assert!(result.unwrap());
+        assert_eq!(session.current_treasure, 160_000_000);
+        assert_eq!(session.status, SessionStatus::Active);
+        assert_eq!(session.last_active_slot, 100);
+        assert_eq!(vault.total_reserved, 1_000_000_000);
+        assert_eq!(user_lamports, 0);
+    }
+
+    #[test]
+    fn test_losing_gamble_closes_session() {
+        let (result, _, vault, user_lamports) = gamble_with(80_000_000, 500_000);
+        assert!(!result.unwrap());
+        assert_eq!(vault.total_reserved, 0);
+        // Only the session rent comes back
+        assert_eq!(user_lamports, 2_000_000);
+    }
+
+    #[test]
+    fn test_winning_gamble_is_capped_at_reservation() {
+        let (result, session, _, _) = gamble_with(700_000_000, 0);
+        assert!(result.unwrap());
+        assert_eq!(session.current_treasure, 1_000_000_000);
+
+        let mut withdrawn = test_session();
+        withdrawn.current_treasure = 500_000_000;
+        withdrawn.total_withdrawn = 300_000_000;
+        assert_eq!(
+            doubled_treasure(&test_config(5), &withdrawn).unwrap(),
+            700_000_000
+        );
+    }
+
+    #[test]
+    fn test_gamble_at_cap_is_rejected() {
+        let (result, session, vault, _) = gamble_with(1_000_000_000, 0);
+        assert_eq!(result.unwrap_err(), GameError::GambleNotAvailable.into());
+        assert_eq!(session.current_treasure, 1_000_000_000);
+        assert_eq!(vault.total_reserved, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_gamble_rejected_under_expected_reservations() {
+        let config = GameConfig {
+            reservation_mode: ReservationMode::Expected,
+            ..test_config(5)
+        };
+        let session = GameSession {
+            dive_number: 5,
+            current_treasure: 80_000_000,
+            reserved_payout: 100_000_000,
+            ..test_session()
+        };
+        let err = doubled_treasure(&config, &session).unwrap_err();
+        assert_eq!(err, GameError::GambleNotAvailable.into());
+    }
+
+    #[test]
+    fn test_winning_gamble_keeps_the_house_edge() {
+        // 2% edge: the curve tops out at 0.98 SOL of the 1 SOL max_payout
+        let config = GameConfig {
+            house_edge_bps: 20_000,
+            ..test_config(5)
+        };
+        let session = GameSession {
+            current_treasure: 700_000_000,
+            ..test_session()
+        };
+        assert_eq!(doubled_treasure(&config, &session).unwrap(), 980_000_000);
+
+        let withdrawn = GameSession {
+            current_treasure: 500_000_000,
+            total_withdrawn: 300_000_000,
+            ..test_session()
+        };
+        assert_eq!(doubled_treasure(&config, &withdrawn).unwrap(), 680_000_000);
+
+        let at_curve_cap = GameSession {
+            current_treasure: 980_000_000,
+            ..test_session()
+        };
+        let err = doubled_treasure(&config, &at_curve_cap).unwrap_err();
+        assert_eq!(err, GameError::GambleNotAvailable.into());
+    }
+}
@@ -0,0 +1,63 @@
+use crate::events::MaxDivesViewEvent;
+use crate::game_math;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Read-only view: how many dives `bet_amount` can take before hitting the payout cap
+/// The rake is applied first, matching the session start_session would open
+/// Returned via Anchor return data and mirrored in MaxDivesViewEvent, so clients never
+/// reimplement game_math::max_dives_for_bet off-chain
+pub fn get_max_dives(ctx: Context<ConfigView>, bet_amount: u64) -> Result<u16> {
+    let config = &ctx.accounts.config;
+    let max_dives =
+        game_math::max_dives_for_bet(config, game_math::effective_bet(config, bet_amount));
+    emit!(MaxDivesViewEvent {
+        bet_amount,
+        max_dives,
+    });
+    Ok(max_dives)
+}
+
+/// Accounts for read-only views over the game config
+#[derive(Accounts)]
+pub struct ConfigView<'info> {
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&config.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_view_matches_pure_function() {
+        for config in [
+            test_config(5),
+            test_config(50),
+            fixed_odds_config(500_000, 200),
+            GameConfig {
+                rake_bps: 500_000,
+                ..test_config(50)
+            },
+        ] {
+            let mut config_acc = TestAccount::program(&config, 1_000_000);
+            let config_info = config_acc.info();
+            let mut accounts = ConfigView {
+                config: Account::try_from(&config_info).unwrap(),
+            };
+            for bet in [1u64, 10_000_000, 1_000_000_000, u64::MAX / 100] {
+                let viewed = get_max_dives(
+                    Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+                    bet,
+                )
+                .unwrap();
+                let effective = game_math::effective_bet(&config, bet);
+                assert_eq!(viewed, game_math::max_dives_for_bet(&config, effective));
+            }
+        }
+    }
+}
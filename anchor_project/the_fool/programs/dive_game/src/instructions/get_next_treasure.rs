@@ -0,0 +1,82 @@
+use crate::events::NextTreasureViewEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+use super::play_round::next_treasure;
+
+/// Read-only view: the treasure the session holds if it survives the current dive
+/// Uses the same next_treasure as settle_round, so the preview is the capped curve net of
+/// partial withdrawals
+pub fn get_next_treasure(ctx: Context<SessionView>) -> Result<u64> {
+    let session = &ctx.accounts.session;
+    session.ensure_active()?;
+    let treasure = next_treasure(&ctx.accounts.config, session)?.min(session.max_payout);
+    emit!(NextTreasureViewEvent {
+        session: session.key(),
+        dive_number: session.dive_number,
+        next_treasure: treasure,
+    });
+    Ok(treasure)
+}
+
+/// Accounts for read-only views over a session
+#[derive(Accounts)]
+pub struct SessionView<'info> {
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    pub session: Account<'info, GameSession>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::play_round::settle_round;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_preview_matches_treasure_after_winning_round() {
+        let config = fixed_odds_config(1_000_000, 200);
+        let mut config_acc = TestAccount::program(&config, 1_000_000);
+        let mut session_acc = TestAccount::program(&test_session(), 2_000_000);
+        let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let config_info = config_acc.info();
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_info = user_acc.info();
+        let mut view = SessionView {
+            config: Account::try_from(&config_info).unwrap(),
+            session: Account::try_from(&session_info).unwrap(),
+        };
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let mut stats = test_user_stats();
+        let mut house_stats = test_house_stats();
+
+        // Walk far enough to hit the payout cap
+        for _ in 0..100 {
+            let preview =
+                get_next_treasure(Context::new(&crate::ID, &mut view, &[], Default::default()))
+                    .unwrap();
+            let outcome = settle_round(
+                &config,
+                &mut session,
+                &mut vault,
+                &mut stats,
+                &mut house_stats,
+                &user_info,
+                0,
+                &test_clock(100),
+            )
+            .unwrap();
+            assert!(outcome.survived);
+            assert_eq!(preview, session.current_treasure);
+            assert!(preview <= session.max_payout);
+            view.session.set_inner((*session).clone());
+        }
+    }
+}
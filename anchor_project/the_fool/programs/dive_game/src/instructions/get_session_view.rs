@@ -0,0 +1,83 @@
+use crate::events::SessionSnapshotEvent;
+use crate::game_math;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+use super::get_next_treasure::SessionView;
+use super::play_round::next_treasure;
+
+/// Read-only view: the session together with its current survival odds, next treasure and
+/// remaining time before it can be cleaned up; mirrored in SessionSnapshotEvent
+pub fn get_session_view(ctx: Context<SessionView>) -> Result<SessionSnapshot> {
+    let clock = Clock::get()?;
+    let snapshot = session_snapshot(&ctx.accounts.config, &ctx.accounts.session, clock.slot)?;
+    emit!(SessionSnapshotEvent {
+        snapshot: snapshot.clone(),
+    });
+    Ok(snapshot)
+}
+
+/// Derived values use the same helpers as play_round and the cleanup instructions
+pub(crate) fn session_snapshot(
+    config: &GameConfig,
+    session: &GameSession,
+    current_slot: u64,
+) -> Result<SessionSnapshot> {
+    session.ensure_active()?;
+    Ok(SessionSnapshot {
+        session: session.clone(),
+        survival_ppm: game_math::survival_probability_bps(config, session.dive_number),
+        next_treasure: next_treasure(config, session)?.min(session.max_payout),
+        slots_until_expiry: session.expires_at_slot.saturating_sub(current_slot),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_derived_fields_match_math_module() {
+        install_syscall_stubs();
+        let config = test_config(5);
+        let mut session = GameSession {
+            dive_number: 3,
+            current_treasure: 36_100_000,
+            ..test_session()
+        };
+        session.record_activity(STUB_SLOT - 100, config.timeout_slots);
+        let mut config_acc = TestAccount::program(&config, 1_000_000);
+        let mut session_acc = TestAccount::program(&session, 2_000_000);
+        let config_info = config_acc.info();
+        let session_info = session_acc.info();
+        let mut view = SessionView {
+            config: Account::try_from(&config_info).unwrap(),
+            session: Account::try_from(&session_info).unwrap(),
+        };
+
+        let snapshot =
+            get_session_view(Context::new(&crate::ID, &mut view, &[], Default::default())).unwrap();
+
+        assert_eq!(snapshot.session.dive_number, 3);
+        assert_eq!(snapshot.session.current_treasure, 36_100_000);
+        assert_eq!(
+            snapshot.survival_ppm,
+            game_math::survival_probability_bps(&config, 3)
+        );
+        assert_eq!(
+            snapshot.next_treasure,
+            game_math::treasure_for_dive(&config, session.bet_amount, 4)
+        );
+        assert_eq!(snapshot.slots_until_expiry, config.timeout_slots - 100);
+    }
+
+    #[test]
+    fn test_expired_session_reports_zero_slots_left() {
+        let config = test_config(5);
+        let mut session = test_session();
+        session.record_activity(0, config.timeout_slots);
+        let snapshot = session_snapshot(&config, &session, config.timeout_slots + 1).unwrap();
+        assert_eq!(snapshot.slots_until_expiry, 0);
+    }
+}
@@ -0,0 +1,45 @@
+use crate::events::SurvivalProbabilityViewEvent;
+use crate::game_math;
+use anchor_lang::prelude::*;
+
+use super::get_max_dives::ConfigView;
+
+/// Read-only view: the exact roll threshold play_round uses for `dive_number`
+/// The event also carries the next dive's odds so UIs can show both without a second call
+pub fn get_survival_probability(ctx: Context<ConfigView>, dive_number: u16) -> Result<u32> {
+    let config = &ctx.accounts.config;
+    let survival_bps = game_math::survival_probability_bps(config, dive_number);
+    emit!(SurvivalProbabilityViewEvent {
+        dive_number,
+        survival_bps,
+        next_dive_survival_bps: game_math::survival_probability_bps(
+            config,
+            dive_number.saturating_add(1)
+        ),
+    });
+    Ok(survival_bps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_view_matches_pure_function() {
+        let config = test_config(50);
+        let mut config_acc = TestAccount::program(&config, 1_000_000);
+        let config_info = config_acc.info();
+        let mut accounts = ConfigView {
+            config: Account::try_from(&config_info).unwrap(),
+        };
+        for dive in [0u16, 1, 2, 10, 49, 50, 500, u16::MAX] {
+            let viewed = get_survival_probability(
+                Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+                dive,
+            )
+            .unwrap();
+            assert_eq!(viewed, game_math::survival_probability_bps(&config, dive));
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::errors::GameError;
+use crate::events::BonusGrantedEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Grants `amount` of promotional funds to `user`, locked until claim_bonus sees enough wagering
+/// The lamports stay in the vault, set aside in bonus_pool so they can't be withdrawn or
+/// reserved for sessions; they must come out of the vault's unreserved balance
+pub fn grant_bonus(ctx: Context<GrantBonus>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let house_vault = &mut ctx.accounts.house_vault;
+    let vault_info = house_vault.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+    require!(
+        amount <= house_vault.withdrawable(vault_info.lamports(), rent_exempt),
+        GameError::InsufficientVaultBalance
+    );
+
+    let user_stats = &mut ctx.accounts.user_stats;
+    user_stats.user = ctx.accounts.user.key();
+    user_stats.bump = ctx.bumps.user_stats;
+    user_stats.grant_bonus(house_vault.key(), amount)?;
+    house_vault.bonus_pool = house_vault
+        .bonus_pool
+        .checked_add(amount)
+        .ok_or(GameError::Overflow)?;
+
+    emit!(BonusGrantedEvent {
+        house_vault: house_vault.key(),
+        user: user_stats.user,
+        amount,
+        bonus_locked_lamports: user_stats.bonus_locked_lamports,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct GrantBonus<'info> {
+    #[account(mut)]
+    pub house_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = house_authority,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+
+    /// CHECK: only used as the user_stats seed; the bonus is paid to this key on claim
+    pub user: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = house_authority,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+
+    pub system_program: Program<'info, System>,
+}
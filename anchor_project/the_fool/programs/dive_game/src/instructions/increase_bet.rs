@@ -0,0 +1,257 @@
+use crate::errors::GameError;
+use crate::events::{BetIncreasedEvent, FlowDirection, FlowReason};
+use crate::game_math;
+use crate::states::*;
+use crate::util::vault_flow;
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Adds `additional` lamports to a session's bet before its first dive resolves
+///
+/// The extra stake goes through the same rake and jackpot split as start_session, and the
+/// session's max_payout and treasure are recomputed from the new bet. Only the growth in the
+/// session's reservation is reserved, under the same coverage and exposure checks as a new
+/// session, and the new total bet must fit under the vault bet cap like a new one.
+pub fn increase_bet(ctx: Context<IncreaseBet>, additional: u64) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let house_vault = &mut ctx.accounts.house_vault;
+    let session = &mut ctx.accounts.session;
+    let clock = Clock::get()?;
+    config.ensure_not_paused()?;
+    require!(!house_vault.locked, GameError::HouseLocked);
+
+    let transfer_ix = system_program::Transfer {
+        from: ctx.accounts.user.to_account_info(),
+        to: house_vault.to_account_info(),
+    };
+    let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+    system_program::transfer(cpi_ctx, additional)?;
+    emit!(vault_flow(
+        &house_vault.to_account_info(),
+        FlowDirection::In,
+        additional,
+        FlowReason::BetIncrease
+    ));
+
+    let vault_balance = house_vault.to_account_info().lamports();
+    apply_bet_increase(config, session, house_vault, vault_balance, additional)?;
+    ctx.accounts.user_stats.record_bet_increase(additional)?;
+    ctx.accounts.house_stats.record_bet_increase(additional)?;
+
+    emit!(BetIncreasedEvent {
+        session: session.key(),
+        user: session.user,
+        additional,
+        bet_amount: session.bet_amount,
+        max_payout: session.max_payout,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Recomputes the session for its bet plus `additional` and reserves the growth in its
+/// reservation
+/// `vault_balance` already includes the transferred lamports
+pub(crate) fn apply_bet_increase(
+    config: &GameConfig,
+    session: &mut GameSession,
+    house_vault: &mut HouseVault,
+    vault_balance: u64,
+    additional: u64,
+) -> Result<()> {
+    session.ensure_active()?;
+    session.ensure_no_pending_vrf()?;
+    require!(session.dive_number == 1, GameError::BetIncreaseNotAllowed);
+    // Denominations are fixed while the whitelist is on
+    require!(!config.use_bet_whitelist, GameError::BetIncreaseNotAllowed);
+    // USD bets are priced once at start_session; a lamport top-up has no locked USD value
+    require!(!config.usd_denominated, GameError::BetIncreaseNotAllowed);
+    require!(additional > 0, GameError::InvalidBetAmount);
+    // Top-ups stay on the grid so the total stake does too
+    config.validate_bet_step(additional)?;
+
+    let new_bet = session
+        .bet_amount
+        .checked_add(game_math::effective_bet(config, additional))
+        .ok_or(GameError::Overflow)?;
+    require!(
+        new_bet <= game_math::effective_bet(config, config.max_bet),
+        GameError::InvalidBetAmount
+    );
+    // Measured without the top-up, as start_session measures before the bet lands
+    config.validate_bet_vault_cap(new_bet, vault_balance.saturating_sub(additional))?;
+    let new_max_payout = game_math::max_payout_for_bet(config, new_bet);
+    let new_reserved = game_math::reservation_for_bet(config, new_bet, session.max_dives(config));
+    let delta = new_reserved.saturating_sub(session.reserved_payout);
+
+    house_vault.add_to_jackpot(game_math::jackpot_for_bet(config, additional))?;
+    let available = vault_balance
+        .checked_sub(house_vault.committed())
+        .ok_or(GameError::InsufficientVaultBalance)?;
+    require!(
+        available >= config.required_coverage(delta),
+        GameError::InsufficientVaultBalance
+    );
+    house_vault.check_exposure(vault_balance, delta, config.max_exposure_bps)?;
+    house_vault.reserve(delta)?;
+
+    session.bet_amount = new_bet;
+    session.max_payout = new_max_payout;
+    session.reserved_payout = new_reserved;
+    session.current_treasure = new_bet.saturating_sub(session.total_withdrawn);
+    session.gross_treasure = 0;
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct IncreaseBet<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        has_one = user,
+        has_one = house_vault,
+    )]
+    pub session: Account<'info, GameSession>,
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn reserved_vault() -> HouseVault {
+        HouseVault {
+            total_reserved: 1_000_000_000,
+            ..test_vault()
+        }
+    }
+
+    #[test]
+    fn test_increase_recomputes_treasure_and_reservation() {
+        let config = GameConfig {
+            rake_bps: 10_000,
+            ..test_config(5)
+        };
+        let mut session = test_session();
+        let mut vault = reserved_vault();
+
+        apply_bet_increase(&config, &mut session, &mut vault, 5_000_000_000, 20_000_000).unwrap();
+
+        // 1% rake on the extra 0.02 SOL
+        let new_bet = 10_000_000 + 19_800_000;
+        assert_eq!(session.bet_amount, new_bet);
+        assert_eq!(session.current_treasure, new_bet);
+        assert_eq!(
+            session.max_payout,
+            game_math::max_payout_for_bet(&config, new_bet)
+        );
+        // Only the growth in max_payout is added to the existing reservation
+        assert_eq!(vault.total_reserved, session.max_payout);
+        assert_eq!(session.reserved_payout, session.max_payout);
+    }
+
+    #[test]
+    fn test_increase_rejected_after_first_dive() {
+        let config = test_config(5);
+        let mut session = GameSession {
+            dive_number: 2,
+            ..test_session()
+        };
+        let mut vault = reserved_vault();
+
+        let err =
+            apply_bet_increase(&config, &mut session, &mut vault, 5_000_000_000, 1).unwrap_err();
+        assert_eq!(err, GameError::BetIncreaseNotAllowed.into());
+        assert_eq!(vault.total_reserved, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_increase_rejected_when_vault_cannot_cover_delta() {
+        let config = GameConfig {
+            require_full_coverage: true,
+            ..test_config(5)
+        };
+        let mut session = test_session();
+        let mut vault = reserved_vault();
+
+        // 0.5 SOL free, but the extra 0.01 SOL needs another 1 SOL of max_payout covered
+        let err = apply_bet_increase(&config, &mut session, &mut vault, 1_500_000_000, 10_000_000)
+            .unwrap_err();
+        assert_eq!(err, GameError::InsufficientVaultBalance.into());
+        assert_eq!(session.bet_amount, 10_000_000);
+    }
+
+    #[test]
+    fn test_increase_rejected_above_vault_bet_cap() {
+        // 1% of the balance backs bets up to balance / 10_000 at a 100x max payout
+        let config = GameConfig {
+            max_bet_vault_bps: 10_000,
+            ..test_config(5)
+        };
+        let mut session = test_session();
+        let mut vault = reserved_vault();
+
+        // 500 SOL before the top-up caps the total bet at 0.05 SOL
+        let err = apply_bet_increase(
+            &config,
+            &mut session,
+            &mut vault,
+            500_000_000_000 + 50_000_000,
+            50_000_000,
+        )
+        .unwrap_err();
+        assert_eq!(err, GameError::BetExceedsVaultCap.into());
+        assert_eq!(session.bet_amount, 10_000_000);
+
+        apply_bet_increase(
+            &config,
+            &mut session,
+            &mut vault,
+            500_000_000_000 + 40_000_000,
+            40_000_000,
+        )
+        .unwrap();
+        assert_eq!(session.bet_amount, 50_000_000);
+    }
+
+    #[test]
+    fn test_increase_rejected_for_usd_bets() {
+        let config = GameConfig {
+            usd_denominated: true,
+            price_feed: Pubkey::new_unique(),
+            ..test_config(5)
+        };
+        let mut session = GameSession {
+            locked_price_micro_usd: 150_000_000,
+            ..test_session()
+        };
+        let mut vault = reserved_vault();
+
+        let err = apply_bet_increase(&config, &mut session, &mut vault, 5_000_000_000, 10_000_000)
+            .unwrap_err();
+        assert_eq!(err, GameError::BetIncreaseNotAllowed.into());
+        assert_eq!(session.bet_amount, 10_000_000);
+        assert_eq!(vault.total_reserved, 1_000_000_000);
+    }
+}
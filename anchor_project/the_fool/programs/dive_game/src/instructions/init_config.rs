@@ -1,6 +1,15 @@
+use super::update_config::config_updated_event;
 use crate::states::*;
 use anchor_lang::prelude::*;
+
+#[cfg(feature = "tsify")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
 pub struct GameConfigParams {
     pub base_survival_ppm: Option<u32>,
     pub decay_per_dive_ppm: Option<u32>,
@@ -9,21 +18,63 @@ pub struct GameConfigParams {
     pub treasure_multiplier_den: Option<u16>,
     pub max_payout_multiplier: Option<u16>,
     pub max_dives: Option<u16>,
-    pub fixed_bet: Option<u64>,
+    pub min_bet: Option<u64>,
+    pub max_bet: Option<u64>,
+    pub vrf_enabled: Option<bool>,
+    pub house_edge_bps: Option<u32>,
+    pub curve_mode: Option<CurveMode>,
+    pub linear_step_bps: Option<u32>,
+    pub referral_fee_bps: Option<u32>,
+    pub max_exposure_bps: Option<u32>,
+    pub timeout_slots: Option<u64>,
+    pub require_full_coverage: Option<bool>,
+    pub min_dives_to_cash_out: Option<u16>,
+    pub rake_bps: Option<u32>,
+    pub final_gamble_bps: Option<u32>,
+    pub jackpot_bps: Option<u32>,
+    pub max_payout_lamports: Option<u64>,
+    pub allowed_bets: Option<[u64; 8]>,
+    pub use_bet_whitelist: Option<bool>,
+    pub crank_reward_lamports: Option<u64>,
+    pub max_active_sessions_per_user: Option<u16>,
+    pub decay_mode: Option<DecayMode>,
+    pub insurance_payout_bps: Option<u32>,
+    pub verbose_logging: Option<bool>,
+    pub first_dive_guaranteed: Option<bool>,
+    pub allow_breakeven_exit: Option<bool>,
+    pub absolute_max_dives: Option<u16>,
+    pub min_slots_before_play: Option<u64>,
+    pub loss_rebate_bps: Option<u32>,
+    pub bet_step: Option<u64>,
+    pub withdraw_delay_slots: Option<u64>,
+    pub max_bet_vault_bps: Option<u32>,
+    pub milestone_dive: Option<u16>,
+    pub milestone_bonus_bps: Option<u32>,
+    pub usd_denominated: Option<bool>,
+    pub price_feed: Option<Pubkey>,
+    pub max_price_age_secs: Option<u64>,
+    pub max_total_sessions: Option<u32>,
+    pub rounding_mode: Option<RoundingMode>,
+    pub bonus_wagering_multiple: Option<u16>,
+    pub max_boost_bps: Option<u32>,
+    pub expiry_mode: Option<ExpiryMode>,
+    pub big_payout_lamports: Option<u64>,
+    pub max_consecutive_big_payouts: Option<u16>,
+    pub reservation_mode: Option<ReservationMode>,
 }
-pub fn init_config(ctx: Context<InitializeConfig>, params: GameConfigParams) -> Result<()> {
+/// Creates the config for one difficulty tier; tier 0 is the original single config
+pub fn init_config(
+    ctx: Context<InitializeConfig>,
+    difficulty: u8,
+    params: GameConfigParams,
+) -> Result<()> {
     let config = &mut ctx.accounts.config;
-    let defaults = GameConfig::default_config();
-    config.admin = ctx.accounts.admin.key();
-    config.base_survival_ppm = params.base_survival_ppm.unwrap_or(defaults.0);
-    config.decay_per_dive_ppm = params.decay_per_dive_ppm.unwrap_or(defaults.1);
-    config.min_survival_ppm = params.min_survival_ppm.unwrap_or(defaults.2);
-    config.treasure_multiplier_num = params.treasure_multiplier_num.unwrap_or(defaults.3);
-    config.treasure_multiplier_den = params.treasure_multiplier_den.unwrap_or(defaults.4);
-    config.max_payout_multiplier = params.max_payout_multiplier.unwrap_or(defaults.5);
-    config.max_dives = params.max_dives.unwrap_or(defaults.6);
-    config.fixed_bet = params.fixed_bet.unwrap_or(defaults.7);
-    config.bump = ctx.bumps.config;
+    config.set_inner(new_config(
+        ctx.accounts.admin.key(),
+        difficulty,
+        ctx.bumps.config,
+        params,
+    ));
 
     // Validate all config parameters using centralized validation
     config.validate()?;
@@ -49,9 +100,101 @@ pub fn init_config(ctx: Context<InitializeConfig>, params: GameConfigParams) ->
         config.treasure_multiplier_den
     );
     msg!("  Max payout multiplier: {}x", config.max_payout_multiplier);
+    msg!(
+        "  Bet range: {}-{} lamports",
+        config.min_bet,
+        config.max_bet
+    );
+    msg!("  VRF enabled: {}", config.vrf_enabled);
+    msg!("  House edge: {}ppm", config.house_edge_bps);
+    msg!(
+        "  Curve: {:?} (linear step {}ppm)",
+        config.curve_mode,
+        config.linear_step_bps
+    );
+    msg!("  Decay: {:?}", config.decay_mode);
+    msg!("  Rounding: {:?}", config.rounding_mode);
+    msg!("  Expiry: {:?}", config.expiry_mode);
+    msg!("  Reservation: {:?}", config.reservation_mode);
+    emit!(config_updated_event(
+        config.key(),
+        config,
+        Clock::get()?.unix_timestamp
+    ));
     Ok(())
 }
+/// A config built from `params`, with init_config's defaults for the ones left unset
+pub(crate) fn new_config(
+    admin: Pubkey,
+    difficulty: u8,
+    bump: u8,
+    params: GameConfigParams,
+) -> GameConfig {
+    let defaults = GameConfig::default_config();
+    GameConfig {
+        admin,
+        base_survival_ppm: params.base_survival_ppm.unwrap_or(defaults.0),
+        decay_per_dive_ppm: params.decay_per_dive_ppm.unwrap_or(defaults.1),
+        min_survival_ppm: params.min_survival_ppm.unwrap_or(defaults.2),
+        treasure_multiplier_num: params.treasure_multiplier_num.unwrap_or(defaults.3),
+        treasure_multiplier_den: params.treasure_multiplier_den.unwrap_or(defaults.4),
+        max_payout_multiplier: params.max_payout_multiplier.unwrap_or(defaults.5),
+        max_dives: params.max_dives.unwrap_or(defaults.6),
+        min_bet: params.min_bet.unwrap_or(defaults.7),
+        max_bet: params.max_bet.unwrap_or(defaults.8),
+        vrf_enabled: params.vrf_enabled.unwrap_or(false),
+        house_edge_bps: params.house_edge_bps.unwrap_or(0),
+        curve_mode: params.curve_mode.unwrap_or(CurveMode::Geometric),
+        linear_step_bps: params.linear_step_bps.unwrap_or(0),
+        referral_fee_bps: params.referral_fee_bps.unwrap_or(0),
+        max_exposure_bps: params.max_exposure_bps.unwrap_or(1_000_000),
+        timeout_slots: params.timeout_slots.unwrap_or(defaults.9),
+        paused: false,
+        pending_admin: None,
+        require_full_coverage: params.require_full_coverage.unwrap_or(false),
+        min_dives_to_cash_out: params.min_dives_to_cash_out.unwrap_or(0),
+        version: CONFIG_VERSION,
+        difficulty,
+        rake_bps: params.rake_bps.unwrap_or(0),
+        final_gamble_bps: params.final_gamble_bps.unwrap_or(500_000),
+        jackpot_bps: params.jackpot_bps.unwrap_or(0),
+        max_payout_lamports: params.max_payout_lamports.unwrap_or(0),
+        allowed_bets: params.allowed_bets.unwrap_or([0; 8]),
+        use_bet_whitelist: params.use_bet_whitelist.unwrap_or(false),
+        crank_reward_lamports: params.crank_reward_lamports.unwrap_or(0),
+        max_active_sessions_per_user: params.max_active_sessions_per_user.unwrap_or(0),
+        decay_mode: params.decay_mode.unwrap_or(DecayMode::Linear),
+        insurance_payout_bps: params.insurance_payout_bps.unwrap_or(0),
+        verbose_logging: params.verbose_logging.unwrap_or(false),
+        first_dive_guaranteed: params.first_dive_guaranteed.unwrap_or(false),
+        allow_breakeven_exit: params.allow_breakeven_exit.unwrap_or(false),
+        absolute_max_dives: params.absolute_max_dives.unwrap_or(0),
+        min_slots_before_play: params.min_slots_before_play.unwrap_or(0),
+        loss_rebate_bps: params.loss_rebate_bps.unwrap_or(0),
+        bet_step: params.bet_step.unwrap_or(0),
+        withdraw_delay_slots: params.withdraw_delay_slots.unwrap_or(0),
+        max_bet_vault_bps: params.max_bet_vault_bps.unwrap_or(0),
+        milestone_dive: params.milestone_dive.unwrap_or(0),
+        milestone_bonus_bps: params.milestone_bonus_bps.unwrap_or(0),
+        usd_denominated: params.usd_denominated.unwrap_or(false),
+        price_feed: params.price_feed.unwrap_or_default(),
+        max_price_age_secs: params.max_price_age_secs.unwrap_or(60),
+        max_total_sessions: params.max_total_sessions.unwrap_or(0),
+        rounding_mode: params.rounding_mode.unwrap_or(RoundingMode::Floor),
+        bonus_wagering_multiple: params.bonus_wagering_multiple.unwrap_or(0),
+        max_boost_bps: params.max_boost_bps.unwrap_or(0),
+        expiry_mode: params.expiry_mode.unwrap_or(ExpiryMode::ReleaseOnly),
+        big_payout_lamports: params.big_payout_lamports.unwrap_or(0),
+        max_consecutive_big_payouts: params.max_consecutive_big_payouts.unwrap_or(0),
+        reservation_mode: params.reservation_mode.unwrap_or(ReservationMode::Full),
+        active_sessions: 0,
+        withdraw_delay_floor_slots: 0,
+        withdraw_delay_floor_until: 0,
+        bump,
+    }
+}
 #[derive(Accounts)]
+#[instruction(difficulty: u8)]
 pub struct InitializeConfig<'info> {
     #[account(mut)]
     pub admin: Signer<'info>,
@@ -59,7 +202,7 @@ pub struct InitializeConfig<'info> {
         init,
         payer = admin,
         space = 8 + GameConfig::INIT_SPACE,
-        seeds = [GAME_CONFIG_SEED.as_bytes()],
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&difficulty)],
         bump
     )]
     pub config: Account<'info, GameConfig>,
@@ -68,6 +211,7 @@ pub struct InitializeConfig<'info> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::test_utils::*;
     fn default_params() -> GameConfigParams {
         GameConfigParams {
             base_survival_ppm: Some(990_000),
@@ -77,103 +221,87 @@ mod tests {
             treasure_multiplier_den: Some(10),
             max_payout_multiplier: Some(100),
             max_dives: Some(200),
-            fixed_bet: Some(10_000_000), // 0.01 SOL
+            min_bet: Some(10_000_000),    // 0.01 SOL
+            max_bet: Some(1_000_000_000), // 1 SOL
+            ..Default::default()
         }
     }
+    fn config_from_params(params: GameConfigParams) -> GameConfig {
+        new_config(Pubkey::default(), 0, 0, params)
+    }
+    #[test]
+    fn test_initialized_config_carries_current_version() {
+        install_syscall_stubs();
+        let mut admin = TestAccount::wallet(1_000_000_000);
+        let mut config_acc = TestAccount::program(
+            &GameConfig {
+                version: 0,
+                ..test_config(5)
+            },
+            1_000_000,
+        );
+        let mut system = TestAccount::system_program();
+        let admin_info = admin.info();
+        let config_info = config_acc.info();
+        let system_info = system.info();
+        let mut accounts = InitializeConfig {
+            admin: Signer::try_from(&admin_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            system_program: Program::try_from(&system_info).unwrap(),
+        };
+        init_config(
+            Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+            0,
+            default_params(),
+        )
+        .unwrap();
+        assert_eq!(accounts.config.version, CONFIG_VERSION);
+    }
+    #[test]
+    fn test_default_params_produce_valid_config() {
+        assert!(config_from_params(default_params()).validate().is_ok());
+    }
     #[test]
     fn test_config_validation_zero_denominator() {
         let config = GameConfig {
-            admin: Pubkey::default(),
-            base_survival_ppm: 990_000,
-            decay_per_dive_ppm: 5_000,
-            min_survival_ppm: 100_000,
-            treasure_multiplier_num: 11,
             treasure_multiplier_den: 0,
-            max_payout_multiplier: 100,
-            max_dives: 200,
-            fixed_bet: 10_000_000,
-            bump: 0,
+            ..test_config(200)
         };
         assert!(config.validate().is_err());
     }
     #[test]
     fn test_config_validation_inverted_probabilities() {
         let config = GameConfig {
-            admin: Pubkey::default(),
             base_survival_ppm: 100_000,
-            decay_per_dive_ppm: 5_000,
             min_survival_ppm: 990_000,
-            treasure_multiplier_num: 11,
-            treasure_multiplier_den: 10,
-            max_payout_multiplier: 100,
-            max_dives: 200,
-            fixed_bet: 10_000_000,
-            bump: 0,
+            ..test_config(200)
         };
         assert!(config.validate().is_err());
     }
     #[test]
     fn test_config_validation_probability_exceeds_100_percent() {
         let config = GameConfig {
-            admin: Pubkey::default(),
             base_survival_ppm: 1_500_000,
-            decay_per_dive_ppm: 5_000,
-            min_survival_ppm: 100_000,
-            treasure_multiplier_num: 11,
-            treasure_multiplier_den: 10,
-            max_payout_multiplier: 100,
-            max_dives: 200,
-            fixed_bet: 10_000_000,
-            bump: 0,
+            ..test_config(200)
         };
         assert!(config.validate().is_err());
     }
     #[test]
-    fn test_config_validation_fixed_bet_works() {
-        let config = GameConfig {
-            admin: Pubkey::default(),
-            base_survival_ppm: 990_000,
-            decay_per_dive_ppm: 5_000,
-            min_survival_ppm: 100_000,
-            treasure_multiplier_num: 11,
-            treasure_multiplier_den: 10,
-            max_payout_multiplier: 100,
-            max_dives: 200,
-            fixed_bet: 10_000_000, // 0.01 SOL
-            bump: 0,
-        };
-        assert!(config.validate().is_ok());
-    }
-    #[test]
     fn test_config_validation_zero_max_dives() {
-        let config = GameConfig {
-            admin: Pubkey::default(),
-            base_survival_ppm: 990_000,
-            decay_per_dive_ppm: 5_000,
-            min_survival_ppm: 100_000,
-            treasure_multiplier_num: 11,
-            treasure_multiplier_den: 10,
-            max_payout_multiplier: 100,
-            max_dives: 0,
-            fixed_bet: 10_000_000,
-            bump: 0,
-        };
+        let config = test_config(0);
         assert!(config.validate().is_err());
     }
     #[test]
-    fn test_config_validation_valid_config_passes() {
+    fn test_config_validation_bet_range_works() {
         let config = GameConfig {
-            admin: Pubkey::default(),
-            base_survival_ppm: 990_000,
-            decay_per_dive_ppm: 5_000,
-            min_survival_ppm: 100_000,
-            treasure_multiplier_num: 11,
-            treasure_multiplier_den: 10,
-            max_payout_multiplier: 100,
-            max_dives: 200,
-            fixed_bet: 10_000_000,
-            bump: 0,
+            min_bet: 10_000_000, // 0.01 SOL
+            max_bet: 1_000_000_000,
+            ..test_config(200)
         };
         assert!(config.validate().is_ok());
     }
+    #[test]
+    fn test_config_validation_valid_config_passes() {
+        assert!(test_config(200).validate().is_ok());
+    }
 }
@@ -1,19 +1,61 @@
-use crate::events::InitializeHouseVaultEvent;
+use crate::errors::GameError;
+use crate::events::{FlowDirection, FlowReason, InitializeHouseVaultEvent};
 use crate::states::*;
+use crate::util::vault_flow;
 use anchor_lang::prelude::*;
-pub fn init_house_vault(ctx: Context<InitializeHouseVault>, locked: bool) -> Result<()> {
+use anchor_lang::system_program;
+/// `seed_amount` is transferred from the authority on top of the rent paid by `init`, so the
+/// vault can cover sessions from the first start_session
+pub fn init_house_vault(
+    ctx: Context<InitializeHouseVault>,
+    locked: bool,
+    game_keeper: Pubkey,
+    strict_accounting: bool,
+    seed_amount: u64,
+) -> Result<()> {
+    // An empty vault would fail every start_session's coverage check
+    require!(
+        seed_amount > 0 || !ctx.accounts.config.require_full_coverage,
+        GameError::VaultSeedRequired
+    );
+    if seed_amount > 0 {
+        let transfer_ix = system_program::Transfer {
+            from: ctx.accounts.house_authority.to_account_info(),
+            to: ctx.accounts.house_vault.to_account_info(),
+        };
+        let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
+        system_program::transfer(cpi_ctx, seed_amount)?;
+        emit!(vault_flow(
+            &ctx.accounts.house_vault.to_account_info(),
+            FlowDirection::In,
+            seed_amount,
+            FlowReason::Seed
+        ));
+    }
+
     let house_vault = &mut ctx.accounts.house_vault;
     let clock = Clock::get()?;
     house_vault.house_authority = ctx.accounts.house_authority.key();
-    // Default keeper to authority, can be changed later via separate instruction if needed
-    house_vault.game_keeper = ctx.accounts.house_authority.key();
+    // Hot wallet that signs play_round, rotatable via set_game_keeper
+    house_vault.set_game_keeper(game_keeper)?;
     house_vault.locked = locked;
     house_vault.total_reserved = 0;
+    house_vault.pending_authority = None;
+    house_vault.cosigner = None;
+    house_vault.authorized_crank = None;
+    house_vault.consecutive_big_payouts = 0;
+    // Opt-in: reservation underflow errors instead of clamping
+    house_vault.strict_accounting = strict_accounting;
+    house_vault.version = VAULT_VERSION;
+    house_vault.sequence = 0;
+    house_vault.active_sessions = 0;
     house_vault.bump = ctx.bumps.house_vault;
     emit!(InitializeHouseVaultEvent {
         house_vault: house_vault.key(),
         house_authority: house_vault.house_authority,
+        game_keeper: house_vault.game_keeper,
         locked: house_vault.locked,
+        seed_amount,
         timestamp: clock.unix_timestamp,
     });
     Ok(())
@@ -22,6 +64,12 @@ pub fn init_house_vault(ctx: Context<InitializeHouseVault>, locked: bool) -> Res
 pub struct InitializeHouseVault<'info> {
     #[account(mut)]
     pub house_authority: Signer<'info>,
+    /// Default difficulty config, read for require_full_coverage
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
     #[account(
         init,
         payer = house_authority,
@@ -32,3 +80,74 @@ pub struct InitializeHouseVault<'info> {
     pub house_vault: Account<'info, HouseVault>,
     pub system_program: Program<'info, System>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn run_init(config: GameConfig, seed_amount: u64) -> (Result<()>, HouseVault, u64, u64) {
+        install_syscall_stubs();
+        let mut authority = TestAccount::wallet(1_000_000_000);
+        let mut config_acc = TestAccount::program(&config, 1_000_000);
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                version: 0,
+                ..test_vault()
+            },
+            1_000_000,
+        );
+        let mut system = TestAccount::system_program();
+        let authority_info = authority.info();
+        let config_info = config_acc.info();
+        let vault_info = vault_acc.info();
+        let system_info = system.info();
+        let mut accounts = InitializeHouseVault {
+            house_authority: Signer::try_from(&authority_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            system_program: Program::try_from(&system_info).unwrap(),
+        };
+        let result = init_house_vault(
+            Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+            false,
+            Pubkey::new_unique(),
+            false,
+            seed_amount,
+        );
+        let vault = (*accounts.house_vault).clone();
+        (
+            result,
+            vault,
+            vault_info.lamports(),
+            authority_info.lamports(),
+        )
+    }
+
+    #[test]
+    fn test_initialized_vault_carries_current_version() {
+        let (result, vault, _, _) = run_init(test_config(5), 0);
+        result.unwrap();
+        assert_eq!(vault.version, VAULT_VERSION);
+        assert_eq!(vault.total_reserved, 0);
+    }
+
+    #[test]
+    fn test_unseeded_vault_allowed_without_full_coverage() {
+        let (result, _, vault_lamports, authority_lamports) = run_init(test_config(5), 0);
+        result.unwrap();
+        assert_eq!(vault_lamports, 1_000_000);
+        assert_eq!(authority_lamports, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_full_coverage_requires_seed() {
+        let config = GameConfig {
+            require_full_coverage: true,
+            ..test_config(5)
+        };
+        let (result, _, vault_lamports, _) = run_init(config, 0);
+        assert_eq!(result.unwrap_err(), GameError::VaultSeedRequired.into());
+        assert_eq!(vault_lamports, 1_000_000);
+    }
+}
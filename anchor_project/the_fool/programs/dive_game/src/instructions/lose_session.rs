@@ -1,34 +1,135 @@
-use crate::events::SessionLostEvent;
 use crate::states::*;
 use anchor_lang::prelude::*;
+
+use super::play_round::settle_loss;
+
 pub fn lose_session(ctx: Context<LoseSession>) -> Result<()> {
-    let session = &mut ctx.accounts.session;
-    let house_vault = &mut ctx.accounts.house_vault;
     let clock = Clock::get()?;
-    session.ensure_active()?;
-    house_vault.release(session.max_payout)?;
-    session.mark_lost()?;
-    emit!(SessionLostEvent {
-        session: session.key(),
-        user: session.user,
-        house_vault: session.house_vault,
-        bet_amount: session.bet_amount,
-        final_dive_number: session.dive_number,
-        timestamp: clock.unix_timestamp,
-    });
+    ctx.accounts.session.ensure_active()?;
+    settle_loss(
+        &ctx.accounts.config,
+        &mut ctx.accounts.session,
+        &mut ctx.accounts.house_vault,
+        &mut ctx.accounts.user_stats,
+        &mut ctx.accounts.house_stats,
+        &ctx.accounts.user.to_account_info(),
+        0,
+        &clock,
+    )?;
+    ctx.accounts.config.close_sessions(1);
     Ok(())
 }
 #[derive(Accounts)]
 pub struct LoseSession<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
+    #[account(
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+    /// Closed by settle_loss, the same atomic close as play_round's loss branch
     #[account(
         mut,
         has_one = user,
         has_one = house_vault,
-        close = user,
     )]
     pub session: Account<'info, GameSession>,
     #[account(mut)]
     pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    /// Forfeits a test_session under `config`
+    /// Returns (session lamports, user lamports, vault lamports, user stats, house stats)
+    fn lose_with(config: GameConfig) -> (u64, u64, u64, UserStats, HouseStats) {
+        install_syscall_stubs();
+        let mut user_acc = TestAccount::wallet(5);
+        let mut config_acc = TestAccount::program(&config, 1_000_000);
+        let mut session_acc = TestAccount::program(&test_session(), 2_000_000);
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                total_reserved: 1_000_000_000,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut user_stats_acc = TestAccount::program(&test_user_stats(), 1_000_000);
+        let mut house_stats_acc = TestAccount::program(&test_house_stats(), 1_000_000);
+        let user_info = user_acc.info();
+        let config_info = config_acc.info();
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let user_stats_info = user_stats_acc.info();
+        let house_stats_info = house_stats_acc.info();
+        let mut accounts = LoseSession {
+            user: Signer::try_from(&user_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            session: Account::try_from(&session_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            user_stats: Account::try_from(&user_stats_info).unwrap(),
+            house_stats: Account::try_from(&house_stats_info).unwrap(),
+        };
+
+        lose_session(Context::new(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            Default::default(),
+        ))
+        .unwrap();
+
+        assert_eq!(accounts.house_vault.total_reserved, 0);
+        (
+            session_info.lamports(),
+            user_info.lamports(),
+            vault_info.lamports(),
+            (*accounts.user_stats).clone(),
+            (*accounts.house_stats).clone(),
+        )
+    }
+
+    #[test]
+    fn test_lose_session_closes_session_to_user() {
+        let (session_lamports, user_lamports, vault_lamports, user_stats, house_stats) =
+            lose_with(test_config(5));
+
+        assert_eq!(session_lamports, 0);
+        assert_eq!(user_lamports, 2_000_005);
+        assert_eq!(vault_lamports, 5_000_000_000);
+        assert_eq!(user_stats.total_lost, 10_000_000);
+        assert_eq!(house_stats.total_losses, 1);
+        assert_eq!(house_stats.total_paid_out, 0);
+    }
+
+    #[test]
+    fn test_loss_rebate_refunds_share_of_bet() {
+        let (_, user_lamports, vault_lamports, _, house_stats) = lose_with(GameConfig {
+            loss_rebate_bps: 100_000,
+            ..test_config(5)
+        });
+
+        // 10% of the 0.01 SOL bet on top of the session's rent
+        assert_eq!(user_lamports, 2_000_005 + 1_000_000);
+        assert_eq!(vault_lamports, 5_000_000_000 - 1_000_000);
+        assert_eq!(house_stats.total_paid_out, 1_000_000);
+        assert_eq!(house_stats.net_profit_lamports, -1_000_000);
+    }
 }
@@ -0,0 +1,204 @@
+use crate::legacy::{self, GameConfigV0, GameConfigV2};
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Upgrades the config account to CONFIG_VERSION
+/// Reads the raw account, since an older layout doesn't deserialize as GameConfig, then resizes
+/// it to the current layout with the admin paying any extra rent
+pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+    let info = ctx.accounts.config.to_account_info();
+    let (from, config) = load_config(&info, &ctx.accounts.admin.key())?;
+    if from < CONFIG_VERSION {
+        legacy::store_upgraded(
+            &info,
+            &config,
+            &ctx.accounts.admin.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+    msg!("CONFIG_MIGRATED from={} to={}", from, config.version);
+    Ok(())
+}
+
+/// Decodes the config at any supported version and checks the accounts constraints would have
+pub(crate) fn load_config(info: &AccountInfo, admin: &Pubkey) -> Result<(u8, GameConfig)> {
+    let (from, config) = legacy::load_versioned(
+        info,
+        8 + GameConfigV0::INIT_SPACE,
+        CONFIG_VERSION,
+        |version, data| match version {
+            0 => Ok(GameConfigV0::deserialize(data)?.upgrade()),
+            2 => Ok(GameConfigV2::deserialize(data)?.upgrade()),
+            _ => err!(ErrorCode::AccountDidNotDeserialize),
+        },
+    )?;
+    let address = Pubkey::create_program_address(
+        &[
+            GAME_CONFIG_SEED.as_bytes(),
+            difficulty_seed(&config.difficulty),
+            &[config.bump],
+        ],
+        &crate::ID,
+    )
+    .map_err(|_| ErrorCode::ConstraintSeeds)?;
+    require_keys_eq!(address, info.key(), ErrorCode::ConstraintSeeds);
+    require_keys_eq!(config.admin, *admin, ErrorCode::ConstraintHasOne);
+    Ok((from, config))
+}
+
+#[derive(Accounts)]
+pub struct MigrateConfig<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    /// CHECK: owner, discriminator, seeds and admin are checked by load_config, which decodes
+    /// older layouts that Account<GameConfig> can't
+    #[account(mut)]
+    pub config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::GameError;
+    use crate::test_utils::{test_config, TestAccount};
+    use anchor_lang::Discriminator;
+
+    /// The tier 0 config address, with its data laid out byte for byte as the baseline program
+    /// allocated it
+    fn baseline_config_account(admin: Pubkey) -> TestAccount {
+        let (key, bump) = Pubkey::find_program_address(&[GAME_CONFIG_SEED.as_bytes()], &crate::ID);
+        let mut data = GameConfig::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(admin.as_ref());
+        data.extend_from_slice(&900_000u32.to_le_bytes()); // base_survival_ppm
+        data.extend_from_slice(&8_000u32.to_le_bytes()); // decay_per_dive_ppm
+        data.extend_from_slice(&50_000u32.to_le_bytes()); // min_survival_ppm
+        data.extend_from_slice(&19u16.to_le_bytes()); // treasure_multiplier_num
+        data.extend_from_slice(&10u16.to_le_bytes()); // treasure_multiplier_den
+        data.extend_from_slice(&100u16.to_le_bytes()); // max_payout_multiplier
+        data.extend_from_slice(&5u16.to_le_bytes()); // max_dives
+        data.extend_from_slice(&10_000_000u64.to_le_bytes()); // fixed_bet
+        data.push(bump);
+        assert_eq!(data.len(), 69);
+        TestAccount {
+            key,
+            owner: crate::ID,
+            lamports: 1_000_000,
+            data,
+            executable: false,
+        }
+    }
+
+    #[test]
+    fn test_baseline_config_upgrades_with_its_settings() {
+        let admin = Pubkey::new_unique();
+        let mut config_acc = baseline_config_account(admin);
+        let info = config_acc.info();
+
+        let (from, config) = load_config(&info, &admin).unwrap();
+
+        assert_eq!(from, 0);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.admin, admin);
+        assert_eq!(config.base_survival_ppm, 900_000);
+        assert_eq!(config.max_dives, 5);
+        // The fixed bet becomes the whole bet range
+        assert_eq!(config.min_bet, 10_000_000);
+        assert_eq!(config.max_bet, 10_000_000);
+        // Settings added since the baseline take their init_config defaults
+        assert_eq!(config.house_edge_bps, 0);
+        assert_eq!(config.timeout_slots, 750);
+        assert!(!config.paused);
+        assert_eq!(config.final_gamble_bps, 500_000);
+        assert_eq!(config.reservation_mode, ReservationMode::Full);
+        assert_eq!(config.difficulty, 0);
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_current_config_loads_unchanged() {
+        let admin = Pubkey::new_unique();
+        let mut baseline_acc = baseline_config_account(admin);
+        let (_, upgraded) = load_config(&baseline_acc.info(), &admin).unwrap();
+        let mut config_acc = TestAccount::program(&upgraded, 1_000_000);
+        config_acc.key = baseline_acc.key;
+        let info = config_acc.info();
+        assert_eq!(
+            info.try_borrow_data().unwrap()[legacy::VERSION_OFFSET],
+            CONFIG_VERSION
+        );
+
+        let (from, config) = load_config(&info, &admin).unwrap();
+
+        assert_eq!(from, CONFIG_VERSION);
+        assert_eq!(config.base_survival_ppm, 900_000);
+    }
+
+    #[test]
+    fn test_v2_config_upgrades_without_a_held_delay() {
+        let admin = Pubkey::new_unique();
+        let (key, bump) = Pubkey::find_program_address(&[GAME_CONFIG_SEED.as_bytes()], &crate::ID);
+        let current = GameConfig {
+            version: 2,
+            admin,
+            withdraw_delay_slots: 100,
+            bump,
+            ..test_config(5)
+        };
+        // Version 2 is the current layout without the two trailing withdraw delay floor fields
+        let mut data = Vec::new();
+        current.try_serialize(&mut data).unwrap();
+        let bump_at = data.len() - 1;
+        data.drain(bump_at - 16..bump_at);
+        data.resize(8 + GameConfigV2::INIT_SPACE, 0);
+        let mut config_acc = TestAccount {
+            key,
+            owner: crate::ID,
+            lamports: 1_000_000,
+            data,
+            executable: false,
+        };
+
+        let (from, config) = load_config(&config_acc.info(), &admin).unwrap();
+
+        assert_eq!(from, 2);
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.withdraw_delay_slots, 100);
+        assert_eq!(config.withdraw_delay_floor_until, 0);
+        assert_eq!(config.bump, bump);
+    }
+
+    #[test]
+    fn test_migrate_config_checks_admin_and_address() {
+        let admin = Pubkey::new_unique();
+        let mut config_acc = baseline_config_account(admin);
+        let err = load_config(&config_acc.info(), &Pubkey::new_unique())
+            .err()
+            .unwrap();
+        assert_eq!(err, ErrorCode::ConstraintHasOne.into());
+
+        config_acc.key = Pubkey::new_unique();
+        let err = load_config(&config_acc.info(), &admin).err().unwrap();
+        assert_eq!(err, ErrorCode::ConstraintSeeds.into());
+    }
+
+    #[test]
+    fn test_migrate_config_rejects_newer_versions() {
+        let admin = Pubkey::new_unique();
+        let (key, bump) = Pubkey::find_program_address(&[GAME_CONFIG_SEED.as_bytes()], &crate::ID);
+        let mut config_acc = TestAccount::program(
+            &GameConfig {
+                version: CONFIG_VERSION + 1,
+                admin,
+                bump,
+                ..test_config(5)
+            },
+            1_000_000,
+        );
+        config_acc.key = key;
+        let err = load_config(&config_acc.info(), &admin).err().unwrap();
+        assert_eq!(err, GameError::UnsupportedAccountVersion.into());
+    }
+}
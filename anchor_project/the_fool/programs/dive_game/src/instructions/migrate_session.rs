@@ -0,0 +1,191 @@
+use crate::legacy::{self, GameSessionV0};
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Upgrades a session account to SESSION_VERSION
+/// Reads the raw account like migrate_config, with the player paying any extra rent
+/// The vault and config must already be migrated; an upgraded session that is still active is
+/// counted back into both, since their counters started at zero
+pub fn migrate_session(ctx: Context<MigrateSession>) -> Result<()> {
+    let accounts = ctx.accounts;
+    let info = accounts.session.to_account_info();
+    let (from, session) = load_session(
+        &info,
+        &accounts.user.key(),
+        &accounts.house_vault,
+        &accounts.config,
+    )?;
+    if from < SESSION_VERSION {
+        if session.status == SessionStatus::Active {
+            accounts.config.open_sessions(1)?;
+            accounts.house_vault.open_session(0)?;
+        }
+        legacy::store_upgraded(
+            &info,
+            &session,
+            &accounts.user.to_account_info(),
+            &accounts.system_program.to_account_info(),
+        )?;
+    }
+    msg!(
+        "SESSION_MIGRATED from={} to={} session={}",
+        from,
+        session.version,
+        info.key()
+    );
+    Ok(())
+}
+
+/// Decodes the session at any supported version and checks it belongs to `user` and
+/// `house_vault`
+pub(crate) fn load_session(
+    info: &AccountInfo,
+    user: &Pubkey,
+    house_vault: &Account<HouseVault>,
+    config: &GameConfig,
+) -> Result<(u8, GameSession)> {
+    let (from, session) = legacy::load_versioned(
+        info,
+        8 + GameSessionV0::INIT_SPACE,
+        SESSION_VERSION,
+        |version, data| match version {
+            0 => Ok(GameSessionV0::deserialize(data)?
+                .upgrade(house_vault.game_keeper, config.timeout_slots)),
+            _ => err!(ErrorCode::AccountDidNotDeserialize),
+        },
+    )?;
+    require_keys_eq!(session.user, *user, ErrorCode::ConstraintHasOne);
+    require_keys_eq!(
+        session.house_vault,
+        house_vault.key(),
+        ErrorCode::ConstraintHasOne
+    );
+    Ok((from, session))
+}
+
+#[derive(Accounts)]
+pub struct MigrateSession<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    /// CHECK: owner, discriminator, user and house_vault are checked by load_session
+    #[account(mut)]
+    pub session: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+
+    /// Baseline sessions all played the tier 0 config
+    #[account(
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{test_config, test_session, test_vault, TestAccount};
+    use anchor_lang::Discriminator;
+
+    /// A session with its data laid out byte for byte as the baseline program allocated it
+    fn baseline_session_account(user: Pubkey, house_vault: Pubkey) -> TestAccount {
+        let mut data = GameSession::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(user.as_ref());
+        data.extend_from_slice(house_vault.as_ref());
+        data.push(0); // status: Active
+        data.extend_from_slice(&10_000_000u64.to_le_bytes()); // bet_amount
+        data.extend_from_slice(&12_100_000u64.to_le_bytes()); // current_treasure
+        data.extend_from_slice(&1_000_000_000u64.to_le_bytes()); // max_payout
+        data.extend_from_slice(&3u16.to_le_bytes()); // dive_number
+        data.push(253); // bump
+        data.extend_from_slice(&500u64.to_le_bytes()); // last_active_slot
+        assert_eq!(data.len(), 108);
+        TestAccount {
+            key: Pubkey::new_unique(),
+            owner: crate::ID,
+            lamports: 2_000_000,
+            data,
+            executable: false,
+        }
+    }
+
+    #[test]
+    fn test_baseline_session_upgrades_mid_dive() {
+        let user = Pubkey::new_unique();
+        let keeper = Pubkey::new_unique();
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                game_keeper: keeper,
+                ..test_vault()
+            },
+            1_000_000_000,
+        );
+        let vault_info = vault_acc.info();
+        let vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let mut session_acc = baseline_session_account(user, vault.key());
+        let config = test_config(5);
+
+        let (from, session) = load_session(&session_acc.info(), &user, &vault, &config).unwrap();
+
+        assert_eq!(from, 0);
+        assert_eq!(session.version, SESSION_VERSION);
+        assert_eq!(session.status, SessionStatus::Active);
+        assert_eq!(session.bet_amount, 10_000_000);
+        assert_eq!(session.dive_number, 3);
+        assert_eq!(session.current_treasure, 12_100_000);
+        assert_eq!(session.bump, 253);
+        assert_eq!(session.pending_commitment, [0u8; 32]);
+        assert_eq!(session.keeper, keeper);
+        assert_eq!(session.reserved_amount(), 1_000_000_000);
+        assert_eq!(session.expires_at_slot, 500 + config.timeout_slots);
+    }
+
+    #[test]
+    fn test_migrate_session_checks_user_and_vault() {
+        let user = Pubkey::new_unique();
+        let mut vault_acc = TestAccount::program(&test_vault(), 1_000_000_000);
+        let vault_info = vault_acc.info();
+        let vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let config = test_config(5);
+
+        let mut session_acc = baseline_session_account(user, vault.key());
+        let err = load_session(&session_acc.info(), &Pubkey::new_unique(), &vault, &config)
+            .err()
+            .unwrap();
+        assert_eq!(err, ErrorCode::ConstraintHasOne.into());
+
+        let mut other_vault_session = baseline_session_account(user, Pubkey::new_unique());
+        let err = load_session(&other_vault_session.info(), &user, &vault, &config)
+            .err()
+            .unwrap();
+        assert_eq!(err, ErrorCode::ConstraintHasOne.into());
+    }
+
+    #[test]
+    fn test_current_session_loads_unchanged() {
+        let user = Pubkey::new_unique();
+        let mut vault_acc = TestAccount::program(&test_vault(), 1_000_000_000);
+        let vault_info = vault_acc.info();
+        let vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                user,
+                house_vault: vault.key(),
+                dive_number: 4,
+                ..test_session()
+            },
+            2_000_000,
+        );
+
+        let (from, session) =
+            load_session(&session_acc.info(), &user, &vault, &test_config(5)).unwrap();
+
+        assert_eq!(from, SESSION_VERSION);
+        assert_eq!(session.dive_number, 4);
+    }
+}
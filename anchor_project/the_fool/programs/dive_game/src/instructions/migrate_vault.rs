@@ -0,0 +1,125 @@
+use crate::legacy::{self, HouseVaultV0};
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Upgrades a house vault account to VAULT_VERSION
+/// Reads the raw account like migrate_config, with the house authority paying any extra rent
+pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+    let info = ctx.accounts.house_vault.to_account_info();
+    let (from, vault) = load_vault(&info, &ctx.accounts.house_authority.key())?;
+    if from < VAULT_VERSION {
+        legacy::store_upgraded(
+            &info,
+            &vault,
+            &ctx.accounts.house_authority.to_account_info(),
+            &ctx.accounts.system_program.to_account_info(),
+        )?;
+    }
+    msg!(
+        "VAULT_MIGRATED from={} to={} vault={}",
+        from,
+        vault.version,
+        info.key()
+    );
+    Ok(())
+}
+
+/// Decodes the vault at any supported version and checks it belongs to `house_authority`
+pub(crate) fn load_vault(info: &AccountInfo, house_authority: &Pubkey) -> Result<(u8, HouseVault)> {
+    let (from, vault) = legacy::load_versioned(
+        info,
+        8 + HouseVaultV0::INIT_SPACE,
+        VAULT_VERSION,
+        |version, data| match version {
+            0 => Ok(HouseVaultV0::deserialize(data)?.upgrade()),
+            _ => err!(ErrorCode::AccountDidNotDeserialize),
+        },
+    )?;
+    require_keys_eq!(
+        vault.house_authority,
+        *house_authority,
+        ErrorCode::ConstraintHasOne
+    );
+    Ok((from, vault))
+}
+
+#[derive(Accounts)]
+pub struct MigrateVault<'info> {
+    #[account(mut)]
+    pub house_authority: Signer<'info>,
+
+    /// CHECK: owner, discriminator and house_authority are checked by load_vault
+    #[account(mut)]
+    pub house_vault: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{test_vault, TestAccount};
+    use anchor_lang::Discriminator;
+
+    /// A vault with its data laid out byte for byte as the baseline program allocated it
+    fn baseline_vault_account(house_authority: Pubkey) -> TestAccount {
+        let mut data = HouseVault::DISCRIMINATOR.to_vec();
+        data.extend_from_slice(house_authority.as_ref());
+        data.extend_from_slice(Pubkey::new_unique().as_ref()); // game_keeper
+        data.push(1); // locked
+        data.extend_from_slice(&3_000_000u64.to_le_bytes()); // total_reserved
+        data.push(254); // bump
+        assert_eq!(data.len(), 82);
+        TestAccount {
+            key: Pubkey::new_unique(),
+            owner: crate::ID,
+            lamports: 1_000_000_000,
+            data,
+            executable: false,
+        }
+    }
+
+    #[test]
+    fn test_baseline_vault_upgrades_with_its_reservations() {
+        let authority = Pubkey::new_unique();
+        let mut vault_acc = baseline_vault_account(authority);
+
+        let (from, vault) = load_vault(&vault_acc.info(), &authority).unwrap();
+
+        assert_eq!(from, 0);
+        assert_eq!(vault.version, VAULT_VERSION);
+        assert!(vault.locked);
+        assert_eq!(vault.total_reserved, 3_000_000);
+        assert!(!vault.strict_accounting);
+        assert_eq!(vault.bump, 254);
+        assert_eq!(vault.cosigner, None);
+    }
+
+    #[test]
+    fn test_migrate_vault_checks_authority() {
+        let mut vault_acc = baseline_vault_account(Pubkey::new_unique());
+        let err = load_vault(&vault_acc.info(), &Pubkey::new_unique())
+            .err()
+            .unwrap();
+        assert_eq!(err, ErrorCode::ConstraintHasOne.into());
+    }
+
+    #[test]
+    fn test_other_accounts_are_rejected() {
+        let authority = Pubkey::new_unique();
+        let mut session_acc = TestAccount::program(&crate::test_utils::test_session(), 1_000_000);
+        let err = load_vault(&session_acc.info(), &authority).err().unwrap();
+        assert_eq!(err, ErrorCode::AccountDiscriminatorMismatch.into());
+
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                house_authority: authority,
+                ..test_vault()
+            },
+            1_000_000,
+        );
+        vault_acc.owner = Pubkey::new_unique();
+        let err = load_vault(&vault_acc.info(), &authority).err().unwrap();
+        assert_eq!(err, ErrorCode::AccountOwnedByWrongProgram.into());
+    }
+}
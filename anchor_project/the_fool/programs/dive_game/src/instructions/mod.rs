@@ -4,19 +4,101 @@ pub mod init_house_vault;
 pub use init_house_vault::*;
 pub mod start_session;
 pub use start_session::*;
+pub mod batch_start_sessions;
+pub use batch_start_sessions::*;
+pub mod commit_round;
+pub use commit_round::*;
 pub mod play_round;
 pub use play_round::*;
+pub mod auto_play;
+pub use auto_play::*;
+pub mod deep_dive;
+pub use deep_dive::*;
+pub mod gamble_treasure;
+pub use gamble_treasure::*;
+pub mod play_round_vrf;
+pub use play_round_vrf::*;
+pub mod request_vrf_round;
+pub use request_vrf_round::*;
 pub mod lose_session;
 pub use lose_session::*;
 pub mod cash_out;
 pub use cash_out::*;
+pub mod partial_cash_out;
+pub use partial_cash_out::*;
 pub mod toggle_house_lock;
 pub use toggle_house_lock::*;
 pub mod clean_expired_session;
 pub use clean_expired_session::*;
+pub mod clean_expired_sessions_batch;
+pub use clean_expired_sessions_batch::*;
+pub mod admin_close_session;
+pub use admin_close_session::*;
+pub mod set_game_keeper;
+pub use set_game_keeper::*;
+pub mod set_cosigner;
+pub use set_cosigner::*;
+pub mod set_authorized_crank;
+pub use set_authorized_crank::*;
+pub mod rebind_session_keeper;
+pub use rebind_session_keeper::*;
+pub mod propose_house_authority;
+pub use propose_house_authority::*;
+pub mod accept_house_authority;
+pub use accept_house_authority::*;
 pub mod withdraw_house;
 pub use withdraw_house::*;
+pub mod withdraw_house_all;
+pub use withdraw_house_all::*;
+pub mod sweep_dust;
+pub use sweep_dust::*;
+pub mod request_withdraw;
+pub use request_withdraw::*;
+pub mod execute_withdraw;
+pub use execute_withdraw::*;
+pub mod cancel_withdraw;
+pub use cancel_withdraw::*;
+pub mod deposit_house;
+pub use deposit_house::*;
+pub mod grant_bonus;
+pub use grant_bonus::*;
+pub mod claim_bonus;
+pub use claim_bonus::*;
 pub mod reset_vault_reserved;
 pub use reset_vault_reserved::*;
+pub mod reconcile_reserved;
+pub use reconcile_reserved::*;
 pub mod update_config;
 pub use update_config::*;
+pub mod set_paused;
+pub use set_paused::*;
+pub mod propose_admin;
+pub use propose_admin::*;
+pub mod accept_admin;
+pub use accept_admin::*;
+pub mod migrate_config;
+pub use migrate_config::*;
+pub mod migrate_session;
+pub use migrate_session::*;
+pub mod migrate_vault;
+pub use migrate_vault::*;
+pub mod get_max_dives;
+pub use get_max_dives::*;
+pub mod get_survival_probability;
+pub use get_survival_probability::*;
+pub mod get_next_treasure;
+pub use get_next_treasure::*;
+pub mod get_session_view;
+pub use get_session_view::*;
+pub mod simulate_session;
+pub use simulate_session::*;
+pub mod verify_outcome;
+pub use verify_outcome::*;
+pub mod vault_health;
+pub use vault_health::*;
+pub mod increase_bet;
+pub use increase_bet::*;
+pub mod buy_insurance;
+pub use buy_insurance::*;
+pub mod apply_boost;
+pub use apply_boost::*;
@@ -0,0 +1,242 @@
+use crate::errors::GameError;
+use crate::events::{FlowReason, PartialCashOutEvent};
+use crate::states::*;
+use crate::util::pay_from_vault;
+use anchor_lang::prelude::*;
+
+use super::cash_out::record_payout_streak;
+
+/// Withdraws part of the current treasure while the session stays Active
+/// The withdrawn amount comes off the vault reservation and is deducted from
+/// future treasure so the session can never pay out more than max_payout in total
+/// It is booked as won right away and counts toward the circuit breaker like a cash out
+/// Returns the lamports paid to the user
+pub fn partial_cash_out(ctx: Context<PartialCashOut>, amount: u64) -> Result<u64> {
+    let session = &mut ctx.accounts.session;
+    let house_vault = &mut ctx.accounts.house_vault;
+    let clock = Clock::get()?;
+
+    require!(!house_vault.locked, GameError::HouseLocked);
+    // Same gate as cash_out, so withdrawals can't skip min_dives_to_cash_out
+    ctx.accounts.config.ensure_min_dives(session.dive_number)?;
+
+    session.ensure_no_pending_vrf()?;
+    let reserved_before = session.reserved_amount();
+    session.apply_partial_cash_out(amount)?;
+    session.record_activity(clock.slot, ctx.accounts.config.timeout_slots);
+
+    let vault_balance = house_vault.to_account_info().lamports();
+    require!(vault_balance >= amount, GameError::InsufficientVaultBalance);
+
+    // Manual lamport transfer from vault to user
+    // Cannot use system_program::transfer() because vault has data
+    emit!(pay_from_vault(
+        &house_vault.to_account_info(),
+        &ctx.accounts.user.to_account_info(),
+        amount,
+        FlowReason::PartialCashOut,
+    )?);
+
+    // An Expected reservation can run out before the withdrawals do
+    house_vault.release(reserved_before - session.reserved_amount())?;
+    ctx.accounts.house_stats.record_payout(amount)?;
+    ctx.accounts.user_stats.record_withdrawal(amount)?;
+    record_payout_streak(&ctx.accounts.config, house_vault, amount, &clock);
+
+    emit!(PartialCashOutEvent {
+        session: session.key(),
+        user: session.user,
+        house_vault: session.house_vault,
+        amount,
+        remaining_treasure: session.current_treasure,
+        dive_number: session.dive_number,
+        sequence: house_vault.next_sequence()?,
+        timestamp: clock.unix_timestamp,
+    });
+
+    Ok(amount)
+}
+#[derive(Accounts)]
+pub struct PartialCashOut<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+    #[account(
+        mut,
+        has_one = user,
+        has_one = house_vault,
+    )]
+    pub session: Account<'info, GameSession>,
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    /// Runs `f` on PartialCashOut accounts holding `config`, `session` and a 5 SOL `vault`,
+    /// with an empty user wallet
+    fn with_accounts<R>(
+        config: GameConfig,
+        session: GameSession,
+        vault: HouseVault,
+        f: impl for<'a> FnOnce(&mut PartialCashOut<'a>) -> R,
+    ) -> R {
+        install_syscall_stubs();
+        let mut user_acc = TestAccount::wallet(0);
+        let mut config_acc = TestAccount::program(&config, 1_000_000);
+        let mut session_acc = TestAccount::program(&session, 2_000_000);
+        let mut vault_acc = TestAccount::program(&vault, 5_000_000_000);
+        let mut house_stats_acc = TestAccount::program(&test_house_stats(), 1_000_000);
+        let mut user_stats_acc = TestAccount::program(&test_user_stats(), 1_000_000);
+        let user_info = user_acc.info();
+        let config_info = config_acc.info();
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        let house_stats_info = house_stats_acc.info();
+        let user_stats_info = user_stats_acc.info();
+        f(&mut PartialCashOut {
+            user: Signer::try_from(&user_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            session: Account::try_from(&session_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            house_stats: Account::try_from(&house_stats_info).unwrap(),
+            user_stats: Account::try_from(&user_stats_info).unwrap(),
+        })
+    }
+
+    fn run(accounts: &mut PartialCashOut, amount: u64) -> Result<u64> {
+        partial_cash_out(
+            Context::new(&crate::ID, accounts, &[], Default::default()),
+            amount,
+        )
+    }
+
+    fn session_at(dive_number: u16, current_treasure: u64) -> GameSession {
+        GameSession {
+            dive_number,
+            current_treasure,
+            ..test_session()
+        }
+    }
+
+    #[test]
+    fn test_partial_cash_out_respects_min_dives() {
+        let config = GameConfig {
+            min_dives_to_cash_out: 3,
+            ..test_config(5)
+        };
+        with_accounts(
+            config,
+            session_at(2, 19_000_000),
+            test_vault(),
+            |accounts| {
+                let err = run(accounts, 5_000_000).unwrap_err();
+                assert_eq!(err, GameError::MinDivesNotReached.into());
+                assert_eq!(accounts.user.lamports(), 0);
+            },
+        );
+    }
+
+    #[test]
+    fn test_partial_cash_out_blocked_while_vrf_request_pending() {
+        let session = GameSession {
+            pending_vrf: Some(Pubkey::new_unique()),
+            vrf_request_slot: 101,
+            ..session_at(3, 36_100_000)
+        };
+        with_accounts(test_config(5), session, test_vault(), |accounts| {
+            let err = run(accounts, 5_000_000).unwrap_err();
+            assert_eq!(err, GameError::VrfRequestPending.into());
+            assert_eq!(accounts.user.lamports(), 0);
+        });
+    }
+
+    #[test]
+    fn test_partial_cash_out_returns_amount_paid() {
+        let session = session_at(3, 36_100_000);
+        with_accounts(test_config(5), session, test_vault(), |accounts| {
+            let paid = run(accounts, 5_000_000).unwrap();
+            assert_eq!(paid, 5_000_000);
+            assert_eq!(accounts.user.lamports(), paid);
+            // The withdrawal counts as activity, so the expiry moves with it
+            assert_eq!(accounts.session.last_active_slot, STUB_SLOT);
+            assert_eq!(
+                accounts.session.expires_at_slot,
+                STUB_SLOT + accounts.config.timeout_slots
+            );
+        });
+    }
+
+    #[test]
+    fn test_partial_cash_out_is_booked_as_won_and_sequenced() {
+        let session = session_at(3, 36_100_000);
+        with_accounts(test_config(5), session, test_vault(), |accounts| {
+            run(accounts, 5_000_000).unwrap();
+            run(accounts, 1_000_000).unwrap();
+            // Counted now, so a later loss or expiry can't drop it from total_won
+            assert_eq!(accounts.user_stats.total_won, 6_000_000);
+            assert_eq!(accounts.house_stats.total_paid_out, 6_000_000);
+            assert_eq!(accounts.house_vault.sequence, 2);
+        });
+    }
+
+    #[test]
+    fn test_partial_cash_outs_feed_the_circuit_breaker() {
+        let config = GameConfig {
+            big_payout_lamports: 1_000_000,
+            max_consecutive_big_payouts: 1,
+            ..test_config(5)
+        };
+        let session = session_at(3, 36_100_000);
+        with_accounts(config, session, test_vault(), |accounts| {
+            run(accounts, 5_000_000).unwrap();
+            assert!(!accounts.house_vault.locked);
+            run(accounts, 5_000_000).unwrap();
+            assert!(accounts.house_vault.locked);
+            let err = run(accounts, 5_000_000).unwrap_err();
+            assert_eq!(err, GameError::HouseLocked.into());
+        });
+    }
+
+    #[test]
+    fn test_partial_cash_out_releases_at_most_the_reservation() {
+        // An Expected reservation smaller than what the session has already won
+        let session = GameSession {
+            reserved_payout: 20_000_000,
+            ..session_at(3, 36_100_000)
+        };
+        let vault = HouseVault {
+            total_reserved: 20_000_000,
+            ..test_vault()
+        };
+        with_accounts(test_config(5), session, vault, |accounts| {
+            run(accounts, 15_000_000).unwrap();
+            assert_eq!(accounts.house_vault.total_reserved, 5_000_000);
+            assert_eq!(accounts.session.reserved_amount(), 5_000_000);
+
+            run(accounts, 10_000_000).unwrap();
+            assert_eq!(accounts.house_vault.total_reserved, 0);
+            assert_eq!(accounts.session.reserved_amount(), 0);
+            assert_eq!(accounts.user.lamports(), 25_000_000);
+        });
+    }
+}
@@ -1,23 +1,79 @@
 use crate::errors::GameError;
-use crate::events::{RoundPlayedEvent, SessionLostEvent};
+use crate::events::{
+    FlowReason, RevealedSeedEvent, RoundPlayedEvent, RoundRolledEvent, RoundSeedRevealedEvent,
+    SessionLostEvent,
+};
 use crate::game_math;
+use crate::rng;
 use crate::states::*;
+use crate::util::{close_session_to, pay_from_vault};
 use anchor_lang::prelude::*;
-use solana_program::hash::hash;
-use solana_program::sysvar::slot_hashes::SlotHashes;
-
-/// Simple On-Chain RNG
-/// Uses slot + timestamp + session data for randomness
-/// WARNING: This is predictable and suitable only for homework/demo purposes
-/// For production, use SlotHashes sysvar or Switchboard VRF
-pub fn play_round(ctx: Context<PlayRound>) -> Result<()> {
-    let config = &ctx.accounts.config;
-    let session = &mut ctx.accounts.session;
-    let house_vault = &mut ctx.accounts.house_vault;
+use solana_program::sysvar::slot_hashes;
+
+/// On-Chain RNG
+/// Rolls are derived from the most recent SlotHashes entry, the session PDA, the keeper-revealed
+/// server seed and the dive number via `rng::generate_seed` + `rng::random_roll_bps`
+/// The server seed must match the commitment stored by commit_round in an earlier slot
+/// `expected_dive` must equal the session's current dive so retried transactions built
+/// against a stale session fail with RoundMismatch instead of playing another round
+/// WARNING: Slot hashes are known to the validator producing the block
+/// For production, use Switchboard VRF
+pub fn play_round(ctx: Context<PlayRound>, server_seed: u64, expected_dive: u16) -> Result<()> {
     let clock = Clock::get()?;
+    let accounts = ctx.accounts;
+    accounts.session.ensure_dive(expected_dive)?;
+    let slot_hash = prepare_round(accounts, server_seed, &clock)?;
+
+    let outcome = resolve_round(
+        &accounts.config,
+        &accounts.session.key(),
+        accounts.session.dive_number,
+        &slot_hash,
+        server_seed,
+    );
+
+    settle_round(
+        &accounts.config,
+        &mut accounts.session,
+        &mut accounts.house_vault,
+        &mut accounts.user_stats,
+        &mut accounts.house_stats,
+        &accounts.user.to_account_info(),
+        outcome.roll,
+        &clock,
+    )?;
+    accounts.config.release_ended(&accounts.session);
+
+    Ok(())
+}
+
+/// Checks shared by keeper-driven rounds and reads the slot hash the rolls derive from
+/// Consumes the pending commitment, so it must run once per instruction
+pub(crate) fn prepare_round(
+    accounts: &mut PlayRound,
+    server_seed: u64,
+    clock: &Clock,
+) -> Result<[u8; 32]> {
+    require!(
+        accounts.session.dive_number < accounts.session.max_dives(&accounts.config),
+        GameError::MaxDivesReached
+    );
+    prepare_keeper_roll(accounts, server_seed, clock)
+}
+
+/// prepare_round without the dive limit, for rolls made once max_dives is reached
+pub(crate) fn prepare_keeper_roll(
+    accounts: &mut PlayRound,
+    server_seed: u64,
+    clock: &Clock,
+) -> Result<[u8; 32]> {
+    let config = &accounts.config;
+    let session = &mut accounts.session;
+
+    config.ensure_not_paused()?;
 
     // Strict lock: house lock blocks all operations except lose_session
-    require!(!house_vault.locked, GameError::HouseLocked);
+    require!(!accounts.house_vault.locked, GameError::HouseLocked);
 
     // Manual check that session is active (more efficient than helper method)
     require!(
@@ -25,135 +81,282 @@ pub fn play_round(ctx: Context<PlayRound>) -> Result<()> {
         GameError::InvalidSessionStatus
     );
 
-    require!(
-        session.dive_number < config.max_dives,
-        GameError::MaxDivesReached
-    );
+    // VRF-enabled houses resolve rounds through play_round_vrf
+    require!(!config.vrf_enabled, GameError::InvalidConfig);
 
-    // --- FIXED RNG: Use XOR of multiple entropy sources ---
-    // Combine slot, session PDA, user pubkey, and dive number
-    // This approach avoids concat() which may have caused issues
-    let slot_bytes = clock.slot.to_le_bytes();
-    let session_bytes = session.key().to_bytes();
-    let user_bytes = session.user.to_bytes();
-    let dive_bytes = session.dive_number.to_le_bytes();
-    
-    // Create entropy by XOR-ing bytes together (simpler than concat)
-    let mut entropy_seed = [0u8; 32];
-    for i in 0..8 {
-        entropy_seed[i] = slot_bytes[i % 8];
-        entropy_seed[i + 8] = session_bytes[i];
-        entropy_seed[i + 16] = user_bytes[i];
-        entropy_seed[i + 24] = dive_bytes[i % 2];
-    }
-    
-    // Add more entropy from later bytes
-    for i in 0..24 {
-        entropy_seed[i] ^= session_bytes[i + 8];
-    }
-    
-    msg!("ENTROPY_SEED first_8_bytes=[{},{},{},{},{},{},{},{}]", 
-        entropy_seed[0], entropy_seed[1], entropy_seed[2], entropy_seed[3],
-        entropy_seed[4], entropy_seed[5], entropy_seed[6], entropy_seed[7]);
-
-    let hash_result = hash(&entropy_seed).to_bytes();
-
-    // Convert first 8 bytes of hash to u64, then mod 1,000,000
-    let mut random_bytes = [0u8; 8];
-    random_bytes.copy_from_slice(&hash_result[0..8]);
-    let raw_u64 = u64::from_le_bytes(random_bytes);
-    let roll = (raw_u64 % 1_000_000) as u32;
-    
-    msg!(
-        "RNG_DEBUG slot={} ts={} dive={} hash_first_8_bytes=[{},{},{},{},{},{},{},{}] raw_u64={} roll_after_mod={}",
-        clock.slot,
-        clock.unix_timestamp,
-        session.dive_number,
-        random_bytes[0], random_bytes[1], random_bytes[2], random_bytes[3],
-        random_bytes[4], random_bytes[5], random_bytes[6], random_bytes[7],
-        raw_u64,
-        roll
-    );
-    // ---------------------------------
+    session.ensure_play_delay(clock.slot, config.min_slots_before_play)?;
+    session.consume_commitment(server_seed, clock.slot)?;
 
-    let survival_prob = game_math::survival_probability_bps(config, session.dive_number);
+    let slot_hash = rng::latest_slot_hash(&accounts.slot_hashes.try_borrow_data()?, clock.slot)?;
+    emit!(round_seed_event(session, server_seed, &slot_hash, clock));
+    Ok(slot_hash)
+}
 
-    msg!(
-        "RNG_COMPARE base_survival={} decay={} min_survival={} dive={} calculated_survival={}",
-        config.base_survival_ppm,
-        config.decay_per_dive_ppm,
-        config.min_survival_ppm,
-        session.dive_number,
-        survival_prob
-    );
+/// Discloses the seed and slot hash behind the round about to be rolled
+pub(crate) fn round_seed_event(
+    session: &Account<GameSession>,
+    server_seed: u64,
+    slot_hash: &[u8; 32],
+    clock: &Clock,
+) -> RoundSeedRevealedEvent {
+    RoundSeedRevealedEvent {
+        session: session.key(),
+        dive_number: session.dive_number,
+        server_seed,
+        slot_hash: *slot_hash,
+        timestamp: clock.unix_timestamp,
+    }
+}
 
-    msg!(
-        "RNG_ROLL dive={} roll={} threshold={} comparison={}_{} survived={}",
-        session.dive_number,
-        roll,
-        survival_prob,
-        roll,
-        survival_prob,
-        roll < survival_prob
+/// Result of a single roll against the survival threshold
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RoundOutcome {
+    pub roll: u32,
+    pub survival_threshold: u32,
+    pub survived: bool,
+}
+
+/// Outcome of `roll` on `dive_number`
+pub fn round_outcome(config: &GameConfig, dive_number: u16, roll: u32) -> RoundOutcome {
+    boosted_outcome(config, dive_number, roll, 0)
+}
+
+/// Outcome of `roll` on `dive_number` with `boost_bps` added to the survival threshold
+pub fn boosted_outcome(
+    config: &GameConfig,
+    dive_number: u16,
+    roll: u32,
+    boost_bps: u32,
+) -> RoundOutcome {
+    let survival_threshold = game_math::boosted_survival_bps(
+        game_math::survival_probability_bps(config, dive_number),
+        boost_bps,
     );
+    RoundOutcome {
+        roll,
+        survival_threshold,
+        survived: roll < survival_threshold,
+    }
+}
 
-    // EXPLICIT DEBUG: manually check the comparison
-    let should_survive = roll < survival_prob;
-    msg!("SURVIVAL_CHECK: roll={} < threshold={} = {}", roll, survival_prob, should_survive);
+/// The whole survival decision for one round, from the same inputs play_round reads on-chain
+/// Lets a round be replayed off-chain once the keeper's server seed is revealed
+pub fn resolve_round(
+    config: &GameConfig,
+    session_key: &Pubkey,
+    dive_number: u16,
+    slot_hash: &[u8; 32],
+    server_seed: u64,
+) -> RoundOutcome {
+    let seed = rng::generate_seed(slot_hash, session_key, server_seed);
+    round_outcome(
+        config,
+        dive_number,
+        rng::random_roll_bps(&seed, dive_number),
+    )
+}
+
+/// Applies a roll to an active session
+/// On survival the session advances one dive; on loss the reservation is released and the
+/// session is closed to the user atomically.
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn settle_round<'info>(
+    config: &GameConfig,
+    session: &mut Account<'info, GameSession>,
+    house_vault: &mut Account<'info, HouseVault>,
+    user_stats: &mut UserStats,
+    house_stats: &mut HouseStats,
+    user: &AccountInfo<'info>,
+    roll: u32,
+    clock: &Clock,
+) -> Result<RoundOutcome> {
+    // Boosts and insurance each cover exactly one roll
+    let boost_bps = std::mem::take(&mut session.next_dive_boost_bps);
+    let outcome = boosted_outcome(config, session.dive_number, roll, boost_bps);
+    let survival_prob = outcome.survival_threshold;
+    let should_survive = outcome.survived;
+    let insured = std::mem::take(&mut session.insured);
+
+    if config.verbose_logging {
+        msg!(
+            "SURVIVAL_CHECK dive={} roll={} threshold={} survived={} insured={}",
+            session.dive_number,
+            roll,
+            survival_prob,
+            should_survive,
+            insured
+        );
+    }
+
+    emit!(RoundRolledEvent {
+        session: session.key(),
+        user: session.user,
+        dive_number: session.dive_number,
+        roll,
+        survival_threshold: survival_prob,
+        survived: should_survive,
+        timestamp: clock.unix_timestamp,
+    });
 
     if should_survive {
         // --- PLAYER SURVIVED ---
-        session.dive_number += 1;
-        session.current_treasure =
-            game_math::treasure_for_dive(config, session.bet_amount, session.dive_number);
+        advance_session(config, session)?;
 
         // Update activity tracking
-        session.last_active_slot = clock.slot;
+        session.record_activity(clock.slot, config.timeout_slots);
 
         emit!(RoundPlayedEvent {
             session: session.key(),
             user: session.user,
             dive_number: session.dive_number,
             current_treasure: session.current_treasure,
+            sequence: house_vault.next_sequence()?,
             timestamp: clock.unix_timestamp,
         });
     } else {
         // --- PLAYER LOST (ATOMIC CLEANUP) ---
-        // Release the reservation
-        house_vault.release(session.max_payout)?;
-
-        emit!(SessionLostEvent {
-            session: session.key(),
-            user: session.user,
-            house_vault: session.house_vault,
-            bet_amount: session.bet_amount,
-            final_dive_number: session.dive_number,
-            timestamp: clock.unix_timestamp,
-        });
-
-        // ATOMIC CLOSE: Refund rent to user immediately
-        let user_lamports = ctx.accounts.user.lamports();
-        let session_lamports = session.to_account_info().lamports();
+        let insurance_refund = if insured {
+            game_math::insurance_refund(config, session.current_treasure)
+        } else {
+            0
+        };
+        settle_loss(
+            config,
+            session,
+            house_vault,
+            user_stats,
+            house_stats,
+            user,
+            insurance_refund,
+            clock,
+        )?;
+    }
 
-        // Transfer all lamports from session to user
-        **ctx.accounts.user.try_borrow_mut_lamports()? = user_lamports
-            .checked_add(session_lamports)
-            .ok_or(GameError::Overflow)?;
+    Ok(outcome)
+}
+/// Releases the reservation, records the loss and closes the session to the user
+/// Shared by play_round's loss branch and lose_session so both end a session the same way
+/// `insurance_refund` and the config's loss rebate are paid to the user out of the released
+/// reservation
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn settle_loss<'info>(
+    config: &GameConfig,
+    session: &mut Account<'info, GameSession>,
+    house_vault: &mut Account<'info, HouseVault>,
+    user_stats: &mut UserStats,
+    house_stats: &mut HouseStats,
+    user: &AccountInfo<'info>,
+    insurance_refund: u64,
+    clock: &Clock,
+) -> Result<()> {
+    session.mark_lost()?;
+    house_vault.release(session.reserved_amount())?;
+    let rebate = game_math::loss_rebate(config, session.bet_amount);
+    let refund = insurance_refund
+        .checked_add(rebate)
+        .ok_or(GameError::Overflow)?;
+    if refund > 0 {
+        emit!(pay_from_vault(
+            &house_vault.to_account_info(),
+            user,
+            refund,
+            FlowReason::LossRefund
+        )?);
+        house_stats.record_payout(refund)?;
+    }
+    user_stats.record_loss(session.bet_amount)?;
+    user_stats.close_session();
+    house_vault.close_session();
+    house_vault.reset_payout_streak();
+    house_stats.record_loss()?;
 
-        **session.to_account_info().try_borrow_mut_lamports()? = 0;
+    emit!(SessionLostEvent {
+        session: session.key(),
+        user: session.user,
+        house_vault: session.house_vault,
+        bet_amount: session.bet_amount,
+        final_dive_number: session.dive_number,
+        insurance_refund,
+        rebate,
+        sequence: house_vault.next_sequence()?,
+        timestamp: clock.unix_timestamp,
+    });
 
-        // Account is now closed and will be garbage collected by runtime
+    if let Some(event) = revealed_seed_event(session, clock) {
+        emit!(event);
     }
 
+    // ATOMIC CLOSE: Refund rent to user immediately
+    // Account is now closed and will be garbage collected by runtime
+    close_session_to(user, &session.to_account_info())
+}
+/// Discloses the session's server seed at close; None until the keeper has revealed one
+pub(crate) fn revealed_seed_event(
+    session: &Account<GameSession>,
+    clock: &Clock,
+) -> Option<RevealedSeedEvent> {
+    session.server_seed.map(|server_seed| RevealedSeedEvent {
+        session: session.key(),
+        user: session.user,
+        server_seed,
+        timestamp: clock.unix_timestamp,
+    })
+}
+/// Moves a session that survived its current dive on to the next one
+/// The only place treasure grows from a roll, shared by settle_round and simulate_session
+pub(crate) fn advance_session(config: &GameConfig, session: &mut GameSession) -> Result<()> {
+    let gross = next_gross_treasure(config, session);
+    let new_treasure = treasure_from_gross(config, session, gross)?;
+    session.milestone_claimed |= config.milestone_reached(session.dive_number);
+    session.dive_number += 1;
+    session.current_treasure = new_treasure;
+    session.gross_treasure = gross;
     Ok(())
 }
+/// Treasure after surviving the current dive, net of partial withdrawals
+/// The curve must strictly grow until it reaches the payout cap, which catches degenerate
+/// configs (e.g. a 1/1 multiplier) that would otherwise keep treasure flat
+pub(crate) fn next_treasure(config: &GameConfig, session: &GameSession) -> Result<u64> {
+    treasure_from_gross(config, session, next_gross_treasure(config, session))
+}
+/// Gross curve value for the next dive, a single step from the session's cache when it is set
+fn next_gross_treasure(config: &GameConfig, session: &GameSession) -> u64 {
+    let previous = (session.gross_treasure > 0).then_some(session.gross_treasure);
+    game_math::gross_treasure_from(
+        config,
+        session.bet_amount,
+        session.dive_number + 1,
+        previous,
+    )
+}
+/// Once the milestone is reached its bonus scales the curve for the rest of the session, so
+/// it is applied a single time rather than compounding dive over dive
+fn treasure_from_gross(config: &GameConfig, session: &GameSession, gross: u64) -> Result<u64> {
+    // The cap is max_payout after the house edge haircut
+    let cap = game_math::apply_house_edge(config, session.max_payout);
+    let mut curve = game_math::apply_house_edge(config, gross);
+    if session.milestone_claimed || config.milestone_reached(session.dive_number) {
+        curve = game_math::with_milestone_bonus(config, curve, cap);
+    }
+    let new_treasure = curve.saturating_sub(session.total_withdrawn);
+    let at_cap = curve >= cap;
+    require!(
+        new_treasure > session.current_treasure || at_cap,
+        GameError::TreasureInvalid
+    );
+    Ok(new_treasure)
+}
 #[derive(Accounts)]
 pub struct PlayRound<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
 
+    /// House hot wallet supplying the server seed
+    pub game_keeper: Signer<'info>,
+
     #[account(
-        seeds = [GAME_CONFIG_SEED.as_bytes()],
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
         bump = config.bump,
     )]
     pub config: Account<'info, GameConfig>,
@@ -162,59 +365,504 @@ pub struct PlayRound<'info> {
         mut,
         has_one = user,
         has_one = house_vault,
+        constraint = session.keeper == game_keeper.key() @ GameError::UnauthorizedKeeper,
     )]
     pub session: Account<'info, GameSession>,
 
-    #[account(mut)]
+    #[account(
+        mut,
+        has_one = game_keeper @ GameError::UnauthorizedKeeper,
+    )]
     pub house_vault: Account<'info, HouseVault>,
+
+    /// CHECK: SlotHashes sysvar, validated by address constraint and parsed in rng::latest_slot_hash
+    #[account(address = slot_hashes::ID)]
+    pub slot_hashes: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+    #[account(
+        mut,
+        seeds = [LEADERBOARD_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = leaderboard.bump,
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use solana_program::hash::hash;
+    use crate::test_utils::{self, TestAccount};
+
+    /// The accounts settle_round works on, with the stats accounts it updates
+    struct Round<'a> {
+        session: Account<'a, GameSession>,
+        vault: Account<'a, HouseVault>,
+        user: AccountInfo<'a>,
+        house_stats: HouseStats,
+    }
+
+    impl Round<'_> {
+        /// Settles the current dive with `roll` at slot 10
+        fn settle(&mut self, config: &GameConfig, roll: u32) -> Result<RoundOutcome> {
+            settle_round(
+                config,
+                &mut self.session,
+                &mut self.vault,
+                &mut test_utils::test_user_stats(),
+                &mut self.house_stats,
+                &self.user,
+                roll,
+                &test_utils::test_clock(10),
+            )
+        }
+
+        fn session_lamports(&self) -> u64 {
+            self.session.to_account_info().lamports()
+        }
+    }
+
+    /// Runs `f` on `session` holding 0.002 SOL of rent at a fixed address, a 5 SOL `vault` and
+    /// an empty user wallet
+    fn with_round<R>(
+        session: GameSession,
+        vault: HouseVault,
+        f: impl for<'a> FnOnce(&mut Round<'a>) -> R,
+    ) -> R {
+        let mut session_acc = TestAccount::program(&session, 2_000_000);
+        session_acc.key = Pubkey::new_from_array([9u8; 32]);
+        let mut vault_acc = TestAccount::program(&vault, 5_000_000_000);
+        let mut user_acc = TestAccount::wallet(0);
+        let session_info = session_acc.info();
+        let vault_info = vault_acc.info();
+        f(&mut Round {
+            session: Account::try_from(&session_info).unwrap(),
+            vault: Account::try_from(&vault_info).unwrap(),
+            user: user_acc.info(),
+            house_stats: test_utils::test_house_stats(),
+        })
+    }
 
     #[test]
     fn test_rng_distribution() {
-        println!("\nTesting RNG hash distribution:");
-        
-        let mut rolls = Vec::new();
+        let config = test_utils::test_config(5);
+        let threshold = game_math::survival_probability_bps(&config, 1);
+        let session_pda = Pubkey::new_unique();
         let mut survivors = 0;
-        let mut deaths = 0;
-        
-        for i in 0..1000 {
-            // Simulate the RNG logic
-            let entropy = format!("slot_{}_ts_{}_session_{}", 
-                100000 + i, 
-                1700000000 + i,
-                i
-            );
-            
-            let hash_result = hash(entropy.as_bytes()).to_bytes();
-            
-            let mut random_bytes = [0u8; 8];
-            random_bytes.copy_from_slice(&hash_result[0..8]);
-            let raw_u64 = u64::from_le_bytes(random_bytes);
-            let roll = (raw_u64 % 1_000_000) as u32;
-            
-            rolls.push(roll);
-            
-            if roll < 700000 {
+
+        for i in 0..1000u32 {
+            let mut slot_hash = [0u8; 32];
+            slot_hash[..4].copy_from_slice(&i.to_le_bytes());
+            let seed = rng::generate_seed(&slot_hash, &session_pda, 0);
+            if rng::random_roll_bps(&seed, 1) < threshold {
                 survivors += 1;
-            } else {
-                deaths += 1;
             }
         }
-        
-        println!("  Total rolls: 1000");
-        println!("  Survivors (roll < 700k): {}", survivors);
-        println!("  Deaths (roll >= 700k): {}", deaths);
-        println!("  Survival rate: {:.1}%", survivors as f64 / 10.0);
-        println!("  Min roll: {}", rolls.iter().min().unwrap());
-        println!("  Max roll: {}", rolls.iter().max().unwrap());
-        
+
         // Should be around 70% survival
         assert!(survivors > 600, "Too few survivors: {}", survivors);
         assert!(survivors < 800, "Too many survivors: {}", survivors);
     }
+
+    #[test]
+    fn test_roll_is_deterministic_for_slot_session_and_dive() {
+        let slot_hash = [42u8; 32];
+        let session_pda = Pubkey::new_from_array([9u8; 32]);
+        let seed = rng::generate_seed(&slot_hash, &session_pda, 0);
+        let rolls: Vec<u32> = (1..=3).map(|d| rng::random_roll_bps(&seed, d)).collect();
+        assert_eq!(rolls, vec![696_484, 110_423, 984_073]);
+    }
+
+    #[test]
+    fn test_outcome_reports_rng_roll_and_threshold() {
+        let config = test_utils::test_config(5);
+        with_round(
+            test_utils::test_session(),
+            test_utils::test_vault(),
+            |round| {
+                let seed = rng::generate_seed(&[42u8; 32], &round.session.key(), 7);
+                let expected_roll = rng::random_roll_bps(&seed, round.session.dive_number);
+                let expected_threshold = game_math::survival_probability_bps(&config, 1);
+
+                let outcome = round.settle(&config, expected_roll).unwrap();
+
+                assert_eq!(outcome.roll, expected_roll);
+                assert_eq!(outcome.survival_threshold, expected_threshold);
+                assert_eq!(outcome.survived, expected_roll < expected_threshold);
+            },
+        );
+    }
+
+    #[test]
+    fn test_flat_multiplier_config_is_rejected() {
+        let config = GameConfig {
+            treasure_multiplier_num: 1,
+            treasure_multiplier_den: 1,
+            ..test_utils::test_config(5)
+        };
+        let session = test_utils::test_session();
+        let err = next_treasure(&config, &session).unwrap_err();
+        assert_eq!(err, GameError::TreasureInvalid.into());
+    }
+
+    #[test]
+    fn test_treasure_may_stay_flat_at_cap() {
+        let mut config = test_utils::test_config(50);
+        config.house_edge_bps = 50_000;
+        let mut session = test_utils::test_session();
+        session.max_payout = game_math::max_payout_for_bet(&config, session.bet_amount);
+        session.dive_number = 20;
+        session.current_treasure =
+            game_math::treasure_for_dive(&config, session.bet_amount, session.dive_number);
+
+        let next = next_treasure(&config, &session).unwrap();
+        assert_eq!(next, session.current_treasure);
+    }
+
+    #[test]
+    fn test_treasure_grows_net_of_withdrawals() {
+        let config = test_utils::test_config(5);
+        let mut session = test_utils::test_session();
+        session.current_treasure = 36_100_000;
+        session.dive_number = 2;
+        session.apply_partial_cash_out(5_000_000).unwrap();
+
+        let next = next_treasure(&config, &session).unwrap();
+        assert_eq!(next, 68_590_000 - 5_000_000);
+    }
+
+    #[test]
+    fn test_cached_gross_treasure_matches_from_scratch() {
+        let mut config = test_utils::test_config(100);
+        config.house_edge_bps = 20_000;
+        let session = GameSession {
+            max_payout: game_math::max_payout_for_bet(&config, 10_000_000),
+            ..test_utils::test_session()
+        };
+        with_round(session, test_utils::test_vault(), |round| {
+            for _ in 1..100 {
+                let outcome = round.settle(&config, 0).unwrap();
+                assert!(outcome.survived);
+                let session = &round.session;
+                assert_eq!(
+                    session.current_treasure,
+                    game_math::treasure_for_dive(&config, session.bet_amount, session.dive_number),
+                    "dive {}",
+                    session.dive_number
+                );
+            }
+            assert_eq!(round.session.dive_number, 100);
+        });
+    }
+
+    #[test]
+    fn test_milestone_bonus_applies_once() {
+        let config = GameConfig {
+            milestone_dive: 3,
+            milestone_bonus_bps: 500_000,
+            ..test_utils::fixed_odds_config(1_000_000, 10)
+        };
+        with_round(
+            test_utils::test_session(),
+            test_utils::test_vault(),
+            |round| {
+                let cap = game_math::apply_house_edge(&config, round.session.max_payout);
+                for _ in 1..6 {
+                    let survived_dive = round.session.dive_number;
+                    round.settle(&config, 0).unwrap();
+                    let session = &round.session;
+                    let curve = game_math::treasure_for_dive(
+                        &config,
+                        session.bet_amount,
+                        session.dive_number,
+                    );
+                    // One 1.5x bonus from surviving dive 3 on, never stacked per dive
+                    let expected = if survived_dive >= 3 {
+                        (curve * 3 / 2).min(cap)
+                    } else {
+                        curve
+                    };
+                    assert_eq!(session.current_treasure, expected, "dive {}", survived_dive);
+                    assert_eq!(session.milestone_claimed, survived_dive >= 3);
+                }
+            },
+        );
+    }
+
+    #[test]
+    fn test_milestone_bonus_respects_max_payout() {
+        let config = GameConfig {
+            milestone_dive: 1,
+            milestone_bonus_bps: 1_000_000,
+            ..test_utils::test_config(5)
+        };
+        let mut session = test_utils::test_session();
+        session.max_payout = 15_000_000;
+        assert_eq!(next_treasure(&config, &session).unwrap(), 15_000_000);
+    }
+
+    #[test]
+    fn test_insured_loss_refunds_share_of_treasure() {
+        let config = GameConfig {
+            insurance_payout_bps: 500_000,
+            ..test_utils::test_config(5)
+        };
+        let insured = GameSession {
+            insured: true,
+            ..test_utils::test_session()
+        };
+        let vault = HouseVault {
+            active_sessions: 1,
+            ..test_utils::test_vault()
+        };
+        with_round(insured, vault, |round| {
+            let outcome = round.settle(&config, 1_000_000).unwrap();
+
+            assert!(!outcome.survived);
+            assert!(!round.session.insured);
+            // Rent from the closed session plus half of the 0.01 SOL treasure
+            assert_eq!(round.user.lamports(), 2_000_000 + 5_000_000);
+            assert_eq!(round.vault.get_lamports(), 5_000_000_000 - 5_000_000);
+            assert_eq!(round.vault.total_reserved, 0);
+            assert_eq!(round.vault.active_sessions, 0);
+            assert_eq!(round.house_stats.total_paid_out, 5_000_000);
+        });
+    }
+
+    #[test]
+    fn test_loss_conserves_lamports() {
+        let config = GameConfig {
+            insurance_payout_bps: 500_000,
+            loss_rebate_bps: 100_000,
+            ..test_utils::test_config(5)
+        };
+        let insured = GameSession {
+            insured: true,
+            ..test_utils::test_session()
+        };
+        with_round(insured, test_utils::test_vault(), |round| {
+            round.user.add_lamports(7).unwrap();
+            let total_before =
+                round.session_lamports() + round.vault.get_lamports() + round.user.lamports();
+
+            round.settle(&config, 1_000_000).unwrap();
+
+            // The session ends empty and the user gets exactly rent + insurance + rebate
+            assert_eq!(round.session_lamports(), 0);
+            assert_eq!(round.user.lamports(), 7 + 2_000_000 + 5_000_000 + 1_000_000);
+            assert_eq!(
+                round.vault.get_lamports() + round.user.lamports(),
+                total_before
+            );
+        });
+    }
+
+    #[test]
+    fn test_insurance_is_consumed_on_survival() {
+        let config = GameConfig {
+            insurance_payout_bps: 500_000,
+            ..test_utils::test_config(5)
+        };
+        let insured = GameSession {
+            insured: true,
+            ..test_utils::test_session()
+        };
+        with_round(insured, test_utils::test_vault(), |round| {
+            let outcome = round.settle(&config, 0).unwrap();
+
+            assert!(outcome.survived);
+            assert!(!round.session.insured);
+            assert_eq!(round.user.lamports(), 0);
+        });
+    }
+
+    #[test]
+    fn test_boost_raises_threshold_for_one_dive() {
+        let config = test_utils::test_config(5);
+        let base = game_math::survival_probability_bps(&config, 1);
+        let boosted = GameSession {
+            next_dive_boost_bps: 50_000,
+            ..test_utils::test_session()
+        };
+        with_round(boosted, test_utils::test_vault(), |round| {
+            // A roll right at the unboosted threshold would lose dive 1
+            let outcome = round.settle(&config, base).unwrap();
+            assert!(outcome.survived);
+            assert_eq!(outcome.survival_threshold, base + 50_000);
+            assert_eq!(round.session.next_dive_boost_bps, 0);
+
+            // Dive 2 rolls against its plain threshold
+            let threshold = game_math::survival_probability_bps(&config, 2);
+            let outcome = round.settle(&config, threshold).unwrap();
+            assert_eq!(outcome.survival_threshold, threshold);
+            assert!(!outcome.survived);
+        });
+    }
+
+    #[test]
+    fn test_verbose_logging_does_not_change_outcomes() {
+        let run = |verbose_logging: bool, roll: u32| {
+            let config = GameConfig {
+                verbose_logging,
+                ..test_utils::test_config(5)
+            };
+            with_round(
+                test_utils::test_session(),
+                test_utils::test_vault(),
+                |round| {
+                    let outcome = round.settle(&config, roll).unwrap();
+                    (
+                        outcome,
+                        round.session.dive_number,
+                        round.session.current_treasure,
+                        round.user.lamports(),
+                    )
+                },
+            )
+        };
+
+        for roll in [0, 1_000_000] {
+            assert_eq!(run(false, roll), run(true, roll));
+        }
+    }
+
+    #[test]
+    fn test_first_dive_guaranteed_only_covers_dive_one() {
+        let config = GameConfig {
+            first_dive_guaranteed: true,
+            min_dives_to_cash_out: 3,
+            ..test_utils::test_config(5)
+        };
+        with_round(
+            test_utils::test_session(),
+            test_utils::test_vault(),
+            |round| {
+                // The highest possible roll, played on dive 1 and then dive 2
+                let first = round.settle(&config, 999_999).unwrap();
+                let second = round.settle(&config, 999_999).unwrap();
+
+                assert!(first.survived);
+                assert_eq!(first.survival_threshold, 1_000_000);
+                assert!(!second.survived);
+                assert_eq!(
+                    second.survival_threshold,
+                    game_math::survival_probability_bps(&test_utils::test_config(5), 2)
+                );
+                assert_eq!(round.session.dive_number, 2);
+            },
+        );
+    }
+
+    #[test]
+    fn test_event_sequence_strictly_increases() {
+        let config = test_utils::test_config(5);
+        let vault = HouseVault {
+            sequence: 41,
+            ..test_utils::test_vault()
+        };
+        with_round(test_utils::test_session(), vault, |round| {
+            // Two survived rounds, then a loss
+            let mut sequences = Vec::new();
+            for roll in [0, 0, 999_999] {
+                round.settle(&config, roll).unwrap();
+                sequences.push(round.vault.sequence);
+            }
+            assert_eq!(sequences, vec![42, 43, 44]);
+        });
+    }
+
+    #[test]
+    fn test_replay_known_seed_sequence() {
+        let config = test_utils::test_config(10);
+        let slot_hash = [42u8; 32];
+        let server_seed = 14;
+        with_round(
+            test_utils::test_session(),
+            test_utils::test_vault(),
+            |round| {
+                // Play the session the way play_round would until it is lost
+                let mut outcomes = Vec::new();
+                while round.session.status == SessionStatus::Active {
+                    let resolved = resolve_round(
+                        &config,
+                        &round.session.key(),
+                        round.session.dive_number,
+                        &slot_hash,
+                        server_seed,
+                    );
+                    let settled = round.settle(&config, resolved.roll).unwrap();
+                    assert_eq!(settled, resolved);
+                    outcomes.push((settled.roll, settled.survival_threshold, settled.survived));
+                }
+
+                assert_eq!(
+                    outcomes,
+                    vec![
+                        (283_504, 700_000, true),
+                        (483_858, 692_000, true),
+                        (502_503, 684_000, true),
+                        (756_172, 676_000, false),
+                    ]
+                );
+                assert_eq!(round.session.dive_number, 4);
+                assert_eq!(round.session_lamports(), 0);
+            },
+        );
+    }
+
+    #[test]
+    fn test_revealed_seeds_reproduce_rolls() {
+        let mut session_acc = TestAccount::program(&test_utils::test_session(), 2_000_000);
+        let session_info = session_acc.info();
+        let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+        assert!(revealed_seed_event(&session, &test_utils::test_clock(10)).is_none());
+
+        // Rolls as play_round derives them, with the keeper committing a new seed every round
+        let mut rolls = Vec::new();
+        let mut reveals = Vec::new();
+        for dive in 1..=3u16 {
+            let server_seed = 0xDEAD_BEEF + dive as u64;
+            let slot_hash = [dive as u8; 32];
+            session.dive_number = dive;
+            session.set_commitment(rng::commitment_for(server_seed), dive as u64);
+            session
+                .consume_commitment(server_seed, dive as u64 + 1)
+                .unwrap();
+            reveals.push(round_seed_event(
+                &session,
+                server_seed,
+                &slot_hash,
+                &test_utils::test_clock(dive as u64 + 1),
+            ));
+            let seed = rng::generate_seed(&slot_hash, &session.key(), server_seed);
+            rolls.push(rng::random_roll_bps(&seed, dive));
+        }
+
+        let replayed: Vec<u32> = reveals
+            .iter()
+            .map(|event| {
+                assert_eq!(event.session, session.key());
+                rng::random_roll_from_slots(
+                    &event.slot_hash,
+                    &event.session,
+                    event.server_seed,
+                    event.dive_number,
+                )
+            })
+            .collect();
+        assert_eq!(replayed, rolls);
+
+        let closing = revealed_seed_event(&session, &test_utils::test_clock(10)).unwrap();
+        assert_eq!(closing.server_seed, 0xDEAD_BEEF + 3);
+    }
 }
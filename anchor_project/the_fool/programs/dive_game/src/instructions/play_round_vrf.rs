@@ -0,0 +1,88 @@
+use crate::errors::GameError;
+use crate::states::*;
+use crate::vrf;
+use anchor_lang::prelude::*;
+
+use super::play_round::settle_round;
+
+/// Resolves a round from a Switchboard VRF result instead of slot-hash RNG
+/// Only available when `config.vrf_enabled` is set. The VRF account must be owned by the
+/// Switchboard program, have the session PDA as its authority, and carry the round that
+/// request_vrf_round recorded on the session before its result existed, so a result is never
+/// reused across rounds or picked after it is known.
+pub fn play_round_vrf(ctx: Context<PlayRoundVrf>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    let session = &mut ctx.accounts.session;
+    let house_vault = &mut ctx.accounts.house_vault;
+    let clock = Clock::get()?;
+
+    config.ensure_not_paused()?;
+    require!(!house_vault.locked, GameError::HouseLocked);
+    require!(config.vrf_enabled, GameError::InvalidConfig);
+    session.ensure_active()?;
+    session.ensure_play_delay(clock.slot, config.min_slots_before_play)?;
+    require!(
+        session.dive_number < session.max_dives(config),
+        GameError::MaxDivesReached
+    );
+
+    let vrf_round = vrf::read_vrf_result(&ctx.accounts.vrf.try_borrow_data()?)?;
+    require_keys_eq!(vrf_round.authority, session.key());
+    session.take_vrf_request(&ctx.accounts.vrf.key(), vrf_round.request_slot)?;
+
+    let roll = vrf::roll_from_vrf_result(&vrf_round.result, session.dive_number);
+
+    settle_round(
+        config,
+        session,
+        house_vault,
+        &mut ctx.accounts.user_stats,
+        &mut ctx.accounts.house_stats,
+        &ctx.accounts.user.to_account_info(),
+        roll,
+        &clock,
+    )?;
+    ctx.accounts.config.release_ended(&ctx.accounts.session);
+
+    Ok(())
+}
+
+#[derive(Accounts)]
+pub struct PlayRoundVrf<'info> {
+    #[account(mut)]
+    pub user: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(
+        mut,
+        has_one = user,
+        has_one = house_vault,
+    )]
+    pub session: Account<'info, GameSession>,
+
+    #[account(mut)]
+    pub house_vault: Account<'info, HouseVault>,
+
+    /// CHECK: Switchboard VRF account, owner checked here and layout parsed in vrf::read_vrf_result
+    #[account(owner = vrf::SWITCHBOARD_PROGRAM_ID)]
+    pub vrf: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump = user_stats.bump,
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(
+        mut,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump = house_stats.bump,
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+}
@@ -0,0 +1,29 @@
+use crate::events::AdminProposedEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Starts a two-step config admin transfer
+/// The proposed key takes over only once it signs accept_admin
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let clock = Clock::get()?;
+    config.propose_admin(new_admin)?;
+    emit!(AdminProposedEvent {
+        config: config.key(),
+        admin: config.admin,
+        pending_admin: new_admin,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&config.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+}
@@ -0,0 +1,30 @@
+use crate::events::HouseAuthorityProposedEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Starts a two-step house authority transfer
+/// The proposed key takes over only once it signs accept_house_authority
+pub fn propose_house_authority(
+    ctx: Context<ProposeHouseAuthority>,
+    new_authority: Pubkey,
+) -> Result<()> {
+    let house_vault = &mut ctx.accounts.house_vault;
+    let clock = Clock::get()?;
+    house_vault.propose_authority(new_authority)?;
+    emit!(HouseAuthorityProposedEvent {
+        house_vault: house_vault.key(),
+        house_authority: house_vault.house_authority,
+        pending_authority: new_authority,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+#[derive(Accounts)]
+pub struct ProposeHouseAuthority<'info> {
+    pub house_authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = house_authority,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+}
@@ -0,0 +1,102 @@
+use crate::events::SessionKeeperReboundEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Binds an in-flight session to the vault's current game keeper
+/// Sessions keep the keeper they started with, so after set_game_keeper the house authority
+/// rebinds each active session before the new keeper can commit or play its rounds
+pub fn rebind_session_keeper(ctx: Context<RebindSessionKeeper>) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    let clock = Clock::get()?;
+    session.ensure_active()?;
+
+    let old_keeper = session.keeper;
+    session.keeper = ctx.accounts.house_vault.game_keeper;
+    emit!(SessionKeeperReboundEvent {
+        session: session.key(),
+        house_vault: session.house_vault,
+        old_keeper,
+        new_keeper: session.keeper,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+#[derive(Accounts)]
+pub struct RebindSessionKeeper<'info> {
+    pub house_authority: Signer<'info>,
+    #[account(has_one = house_authority)]
+    pub house_vault: Account<'info, HouseVault>,
+    #[account(
+        mut,
+        has_one = house_vault,
+    )]
+    pub session: Account<'info, GameSession>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::GameError;
+    use crate::instructions::CommitRound;
+    use crate::test_utils::*;
+    use anchor_lang::Bumps;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn test_rotated_keeper_needs_rebind() {
+        install_syscall_stubs();
+        let old_keeper = Pubkey::new_unique();
+        let mut authority_acc = TestAccount::wallet(0);
+        let mut keeper_acc = TestAccount::wallet(0);
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                house_authority: authority_acc.key,
+                game_keeper: keeper_acc.key,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                house_vault: vault_acc.key,
+                keeper: old_keeper,
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let authority_info = authority_acc.info();
+        let vault_info = vault_acc.info();
+        let session_info = session_acc.info();
+        let commit_infos = [keeper_acc.info(), session_info.clone(), vault_info.clone()];
+        let validate_commit = || {
+            let mut accounts: &[AccountInfo] = &commit_infos;
+            CommitRound::try_accounts(
+                &crate::ID,
+                &mut accounts,
+                &[],
+                &mut <CommitRound as Bumps>::Bumps::default(),
+                &mut BTreeSet::new(),
+            )
+            .map(|_| ())
+        };
+
+        let err = validate_commit().unwrap_err();
+        assert_eq!(err, GameError::UnauthorizedKeeper.into());
+
+        let mut accounts = RebindSessionKeeper {
+            house_authority: Signer::try_from(&authority_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            session: Account::try_from(&session_info).unwrap(),
+        };
+        rebind_session_keeper(Context::new(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            Default::default(),
+        ))
+        .unwrap();
+        accounts.session.exit(&crate::ID).unwrap();
+
+        assert!(validate_commit().is_ok());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::errors::GameError;
+use crate::events::ReservedReconciledEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Recomputes total_reserved from the vault's live sessions
+///
+/// remaining_accounts holds every Active GameSession of `house_vault`; `session_count` is the
+/// authority's confirmation that this is the complete set, and must match the vault's own
+/// active_sessions count. The program can't enumerate sessions itself, so a mismatch fails
+/// with InvalidConfig rather than guessing.
+/// Unlike reset_vault_reserved this fixes drift while sessions are still open.
+pub fn reconcile_reserved<'info>(
+    ctx: Context<'_, '_, 'info, 'info, ReconcileReserved<'info>>,
+    session_count: u32,
+) -> Result<()> {
+    let clock = Clock::get()?;
+    let house_vault = &mut ctx.accounts.house_vault;
+    let new_reserved = sum_reserved(
+        house_vault.key(),
+        house_vault.active_sessions,
+        ctx.remaining_accounts,
+        session_count,
+    )?;
+    let previous_reserved = house_vault.total_reserved;
+    house_vault.total_reserved = new_reserved;
+
+    emit!(ReservedReconciledEvent {
+        house_vault: house_vault.key(),
+        house_authority: house_vault.house_authority,
+        previous_reserved,
+        new_reserved,
+        session_count,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Sums the outstanding reservation of each distinct Active session of the vault
+/// `active_sessions` is the vault's count, so a subset of its sessions can't lower the total
+pub(crate) fn sum_reserved<'info>(
+    house_vault: Pubkey,
+    active_sessions: u32,
+    accounts: &'info [AccountInfo<'info>],
+    session_count: u32,
+) -> Result<u64> {
+    require!(session_count == active_sessions, GameError::InvalidConfig);
+    require!(
+        accounts.len() == session_count as usize,
+        GameError::InvalidConfig
+    );
+    let mut seen: Vec<&Pubkey> = Vec::with_capacity(accounts.len());
+    let mut total = 0u64;
+    for info in accounts {
+        // A duplicated session would be counted twice
+        require!(!seen.contains(&info.key), GameError::InvalidConfig);
+        seen.push(info.key);
+
+        let session = Account::<GameSession>::try_from(info)?;
+        require_keys_eq!(session.house_vault, house_vault);
+        session.ensure_active()?;
+        total = total
+            .checked_add(session.reserved_amount())
+            .ok_or(GameError::Overflow)?;
+    }
+    Ok(total)
+}
+
+#[derive(Accounts)]
+pub struct ReconcileReserved<'info> {
+    pub house_authority: Signer<'info>,
+
+    #[account(
+        mut,
+        has_one = house_authority,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn session_for(vault: Pubkey, total_withdrawn: u64) -> TestAccount {
+        let session = GameSession {
+            house_vault: vault,
+            total_withdrawn,
+            ..test_session()
+        };
+        TestAccount::program(&session, 2_000_000)
+    }
+
+    #[test]
+    fn test_reconcile_corrects_inflated_reserve() {
+        let vault = Pubkey::new_unique();
+        let mut a = session_for(vault, 0);
+        let mut b = session_for(vault, 300_000_000);
+        let accounts = [a.info(), b.info()];
+        // 1_000_000_000 + 700_000_000 still reserved, whatever the vault believed
+        assert_eq!(sum_reserved(vault, 2, &accounts, 2).unwrap(), 1_700_000_000);
+        assert_eq!(sum_reserved(vault, 0, &[], 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_handler_overwrites_inflated_total_reserved() {
+        install_syscall_stubs();
+        let mut authority = TestAccount::wallet(0);
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                house_authority: authority.key,
+                total_reserved: 5_000_000_000,
+                active_sessions: 1,
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let mut a = session_for(vault_acc.key, 0);
+        let sessions = [a.info()];
+        let authority_info = authority.info();
+        let vault_info = vault_acc.info();
+        let mut accounts = ReconcileReserved {
+            house_authority: Signer::try_from(&authority_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+        };
+        reconcile_reserved(
+            Context::new(&crate::ID, &mut accounts, &sessions, Default::default()),
+            1,
+        )
+        .unwrap();
+        assert_eq!(accounts.house_vault.total_reserved, 1_000_000_000);
+    }
+
+    #[test]
+    fn test_reconcile_rejects_count_mismatch() {
+        let vault = Pubkey::new_unique();
+        let mut a = session_for(vault, 0);
+        let accounts = [a.info()];
+        for count in [0, 2] {
+            let err = sum_reserved(vault, count, &accounts, count).unwrap_err();
+            assert_eq!(err, GameError::InvalidConfig.into());
+        }
+    }
+
+    #[test]
+    fn test_reconcile_rejects_a_subset_of_live_sessions() {
+        let vault = Pubkey::new_unique();
+        let mut a = session_for(vault, 0);
+        let accounts = [a.info()];
+        // The vault still has a second open session the authority left out
+        let err = sum_reserved(vault, 2, &accounts, 1).unwrap_err();
+        assert_eq!(err, GameError::InvalidConfig.into());
+    }
+
+    #[test]
+    fn test_reconcile_rejects_duplicates_and_foreign_sessions() {
+        let vault = Pubkey::new_unique();
+        let mut a = session_for(vault, 0);
+        let info = a.info();
+        let duplicated = [info.clone(), info];
+        let err = sum_reserved(vault, 2, &duplicated, 2).unwrap_err();
+        assert_eq!(err, GameError::InvalidConfig.into());
+
+        let mut foreign = session_for(Pubkey::new_unique(), 0);
+        let accounts = [foreign.info()];
+        assert!(sum_reserved(vault, 1, &accounts, 1).is_err());
+    }
+
+    #[test]
+    fn test_reconcile_rejects_inactive_sessions() {
+        let vault = Pubkey::new_unique();
+        let mut lost = TestAccount::program(
+            &GameSession {
+                house_vault: vault,
+                status: SessionStatus::Lost,
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let accounts = [lost.info()];
+        let err = sum_reserved(vault, 1, &accounts, 1).unwrap_err();
+        assert_eq!(err, GameError::InvalidSessionStatus.into());
+    }
+}
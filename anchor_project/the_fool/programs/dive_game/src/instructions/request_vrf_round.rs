@@ -0,0 +1,208 @@
+use crate::errors::GameError;
+use crate::events::VrfRequestedEvent;
+use crate::states::*;
+use crate::vrf::{self, VrfRoundRequest};
+use anchor_lang::prelude::*;
+
+/// Records on the session the VRF round that resolves its next dive
+///
+/// Sent after requesting randomness from Switchboard and before the oracle answers: the VRF
+/// account must still be awaiting its result, have the session PDA as its authority and
+/// have been requested after the session's last activity. Until play_round_vrf plays that
+/// round the session can't cash out, withdraw or buy boosts and insurance.
+pub fn request_vrf_round(ctx: Context<RequestVrfRound>) -> Result<()> {
+    let session = &mut ctx.accounts.session;
+    require!(!ctx.accounts.house_vault.locked, GameError::HouseLocked);
+
+    let request = vrf::read_vrf_request(&ctx.accounts.vrf.try_borrow_data()?)?;
+    let session_key = session.key();
+    record_request(
+        &ctx.accounts.config,
+        session,
+        &session_key,
+        ctx.accounts.vrf.key(),
+        &request,
+    )?;
+
+    emit!(VrfRequestedEvent {
+        session: session_key,
+        vrf: ctx.accounts.vrf.key(),
+        dive_number: session.dive_number,
+        request_slot: request.request_slot,
+    });
+    Ok(())
+}
+
+/// Checks a pending VRF round against the session and records it
+pub(crate) fn record_request(
+    config: &GameConfig,
+    session: &mut GameSession,
+    session_key: &Pubkey,
+    vrf: Pubkey,
+    request: &VrfRoundRequest,
+) -> Result<()> {
+    config.ensure_not_paused()?;
+    require!(config.vrf_enabled, GameError::InvalidConfig);
+    session.ensure_active()?;
+    require!(
+        session.dive_number < session.max_dives(config),
+        GameError::MaxDivesReached
+    );
+    require_keys_eq!(request.authority, *session_key);
+    session.record_vrf_request(vrf, request.request_slot)
+}
+
+#[derive(Accounts)]
+pub struct RequestVrfRound<'info> {
+    pub user: Signer<'info>,
+
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&session.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    #[account(
+        mut,
+        has_one = user,
+        has_one = house_vault,
+    )]
+    pub session: Account<'info, GameSession>,
+
+    pub house_vault: Account<'info, HouseVault>,
+
+    /// CHECK: Switchboard VRF account, owner checked here and layout parsed in vrf::read_vrf_request
+    #[account(owner = vrf::SWITCHBOARD_PROGRAM_ID)]
+    pub vrf: UncheckedAccount<'info>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::apply_boost::apply_boost_to;
+    use crate::instructions::buy_insurance::apply_insurance;
+    use crate::test_utils::*;
+
+    fn vrf_config() -> GameConfig {
+        GameConfig {
+            vrf_enabled: true,
+            ..test_config(5)
+        }
+    }
+
+    fn request(authority: Pubkey, request_slot: u64) -> VrfRoundRequest {
+        VrfRoundRequest {
+            authority,
+            request_slot,
+        }
+    }
+
+    #[test]
+    fn test_request_is_recorded_once() {
+        let config = vrf_config();
+        let session_key = Pubkey::new_unique();
+        let vrf = Pubkey::new_unique();
+        let mut session = GameSession {
+            last_active_slot: 100,
+            ..test_session()
+        };
+
+        record_request(
+            &config,
+            &mut session,
+            &session_key,
+            vrf,
+            &request(session_key, 101),
+        )
+        .unwrap();
+        assert_eq!(session.pending_vrf, Some(vrf));
+        assert_eq!(session.vrf_request_slot, 101);
+
+        let err = record_request(
+            &config,
+            &mut session,
+            &session_key,
+            Pubkey::new_unique(),
+            &request(session_key, 102),
+        )
+        .unwrap_err();
+        assert_eq!(err, GameError::VrfRequestPending.into());
+    }
+
+    #[test]
+    fn test_request_from_the_last_active_slot_is_stale() {
+        let config = vrf_config();
+        let session_key = Pubkey::new_unique();
+        let mut session = GameSession {
+            last_active_slot: 100,
+            ..test_session()
+        };
+        let err = record_request(
+            &config,
+            &mut session,
+            &session_key,
+            Pubkey::new_unique(),
+            &request(session_key, 100),
+        )
+        .unwrap_err();
+        assert_eq!(err, GameError::StaleVrfRequest.into());
+        assert_eq!(session.pending_vrf, None);
+    }
+
+    #[test]
+    fn test_request_must_belong_to_session() {
+        let config = vrf_config();
+        let mut session = test_session();
+        assert!(record_request(
+            &config,
+            &mut session,
+            &Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            &request(Pubkey::new_unique(), 101),
+        )
+        .is_err());
+        assert!(record_request(
+            &test_config(5),
+            &mut session,
+            &Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            &request(Pubkey::new_unique(), 101),
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_only_the_recorded_round_resolves_the_request() {
+        let vrf = Pubkey::new_unique();
+        let mut session = test_session();
+        session.record_vrf_request(vrf, 101).unwrap();
+
+        let err = session
+            .take_vrf_request(&Pubkey::new_unique(), 101)
+            .unwrap_err();
+        assert_eq!(err, GameError::StaleVrfRequest.into());
+        let err = session.take_vrf_request(&vrf, 102).unwrap_err();
+        assert_eq!(err, GameError::StaleVrfRequest.into());
+
+        session.take_vrf_request(&vrf, 101).unwrap();
+        assert_eq!(session.pending_vrf, None);
+        assert!(session.ensure_no_pending_vrf().is_ok());
+    }
+
+    #[test]
+    fn test_pending_request_blocks_boosts_and_insurance() {
+        let config = GameConfig {
+            max_boost_bps: 100_000,
+            ..vrf_config()
+        };
+        let mut session = test_session();
+        session
+            .record_vrf_request(Pubkey::new_unique(), 101)
+            .unwrap();
+
+        let err = apply_boost_to(&config, &mut session, 50_000, u64::MAX).unwrap_err();
+        assert_eq!(err, GameError::VrfRequestPending.into());
+        let err = apply_insurance(&config, &mut session, u64::MAX).unwrap_err();
+        assert_eq!(err, GameError::VrfRequestPending.into());
+    }
+}
@@ -0,0 +1,23 @@
+use crate::events::WithdrawRequestedEvent;
+use anchor_lang::prelude::*;
+
+use super::withdraw_house::WithdrawHouse;
+
+/// First step of a timelocked house withdrawal
+/// Records `amount` to be paid by execute_withdraw once withdraw_delay_slots have passed, so
+/// players see a withdrawal coming before funds can leave the vault
+pub fn request_withdraw(ctx: Context<WithdrawHouse>, amount: u64) -> Result<()> {
+    let clock = Clock::get()?;
+    let unlock_slot = clock
+        .slot
+        .saturating_add(ctx.accounts.config.withdraw_delay_at(clock.slot));
+    let house_vault = &mut ctx.accounts.house_vault;
+    house_vault.request_withdraw(amount, unlock_slot)?;
+    emit!(WithdrawRequestedEvent {
+        house_vault: house_vault.key(),
+        amount,
+        unlock_slot,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 
 /// Emergency admin function to reset total_reserved when accounting gets out of sync
 /// This should only be used when there are NO active sessions
-/// 
+///
 /// SAFETY: This function now validates that total_reserved is 0 before allowing a reset.
 /// If total_reserved > 0, it means there may be active sessions, and resetting would
 /// break the accounting system. Admin must ensure all sessions are properly closed first.
@@ -0,0 +1,29 @@
+use crate::events::AuthorizedCrankUpdatedEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Restricts clean_expired_session and its batch form to one crank key, or clears the
+/// restriction so anyone may crank again
+/// Only the house authority can call this
+pub fn set_authorized_crank(ctx: Context<SetAuthorizedCrank>, crank: Option<Pubkey>) -> Result<()> {
+    let house_vault = &mut ctx.accounts.house_vault;
+    let clock = Clock::get()?;
+    let old_crank = house_vault.authorized_crank;
+    house_vault.set_authorized_crank(crank)?;
+    emit!(AuthorizedCrankUpdatedEvent {
+        house_vault: house_vault.key(),
+        old_crank,
+        new_crank: house_vault.authorized_crank,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+#[derive(Accounts)]
+pub struct SetAuthorizedCrank<'info> {
+    pub house_authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = house_authority,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+}
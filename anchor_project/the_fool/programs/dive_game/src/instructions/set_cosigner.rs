@@ -0,0 +1,28 @@
+use crate::events::CosignerUpdatedEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Sets or clears the second signer required on house withdrawals
+/// Only the house authority can call this
+pub fn set_cosigner(ctx: Context<SetCosigner>, cosigner: Option<Pubkey>) -> Result<()> {
+    let house_vault = &mut ctx.accounts.house_vault;
+    let clock = Clock::get()?;
+    let old_cosigner = house_vault.cosigner;
+    house_vault.set_cosigner(cosigner)?;
+    emit!(CosignerUpdatedEvent {
+        house_vault: house_vault.key(),
+        old_cosigner,
+        new_cosigner: house_vault.cosigner,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+#[derive(Accounts)]
+pub struct SetCosigner<'info> {
+    pub house_authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = house_authority,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+}
@@ -0,0 +1,29 @@
+use crate::events::GameKeeperUpdatedEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Rotates the game keeper (hot wallet) that signs play_round
+/// Only the house authority (cold wallet) can call this
+pub fn set_game_keeper(ctx: Context<SetGameKeeper>, game_keeper: Pubkey) -> Result<()> {
+    let house_vault = &mut ctx.accounts.house_vault;
+    let clock = Clock::get()?;
+    let old_game_keeper = house_vault.game_keeper;
+    house_vault.set_game_keeper(game_keeper)?;
+    emit!(GameKeeperUpdatedEvent {
+        house_vault: house_vault.key(),
+        house_authority: house_vault.house_authority,
+        old_game_keeper,
+        new_game_keeper: house_vault.game_keeper,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+#[derive(Accounts)]
+pub struct SetGameKeeper<'info> {
+    pub house_authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = house_authority,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+}
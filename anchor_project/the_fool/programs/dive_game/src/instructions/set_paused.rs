@@ -0,0 +1,30 @@
+use crate::events::GamePausedEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Global kill switch across all vaults
+/// While paused, start_session and rounds are rejected; cash_out, partial_cash_out,
+/// lose_session and cleanup stay open so players can exit
+pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let clock = Clock::get()?;
+    config.paused = paused;
+    emit!(GamePausedEvent {
+        config: config.key(),
+        admin: config.admin,
+        paused,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+#[derive(Accounts)]
+pub struct SetPaused<'info> {
+    pub admin: Signer<'info>,
+    #[account(
+        mut,
+        has_one = admin,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&config.difficulty)],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+}
@@ -0,0 +1,170 @@
+use crate::events::SessionSimulatedEvent;
+use crate::game_math;
+use crate::rng;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+use super::get_max_dives::ConfigView;
+use super::play_round::{advance_session, round_outcome};
+
+/// Read-only dry run: how a session opened with `bet_amount` would go if every round were
+/// rolled from the seed auto_play derives for `session`, `slot_hash` and `server_seed`
+/// Nothing is created or moved; the treasures are the ones play_round would hold
+/// The result travels in return data, so configs past ~120 max_dives exceed its 1024 bytes
+pub fn simulate_session(
+    ctx: Context<ConfigView>,
+    bet_amount: u64,
+    server_seed: u64,
+    session: Pubkey,
+    slot_hash: [u8; 32],
+) -> Result<SimResult> {
+    let seed = rng::generate_seed(&slot_hash, &session, server_seed);
+    let result = simulate(&ctx.accounts.config, bet_amount, &seed)?;
+    emit!(SessionSimulatedEvent {
+        bet_amount,
+        server_seed,
+        lost: result.lost,
+        final_dive: result.final_dive,
+    });
+    Ok(result)
+}
+
+/// Plays a fresh session against `seed` until a roll fails or max_dives is reached
+pub(crate) fn simulate(config: &GameConfig, bet_amount: u64, seed: &[u8; 32]) -> Result<SimResult> {
+    config.validate_bet(bet_amount)?;
+    // Same opening state as start_session
+    let effective_bet = game_math::effective_bet(config, bet_amount);
+    // Only the treasure curve fields matter to the simulation
+    let mut session = GameSession {
+        bet_amount: effective_bet,
+        current_treasure: effective_bet,
+        max_payout: game_math::max_payout_for_bet(config, effective_bet),
+        dive_number: 1,
+        ..GameSession::blank()
+    };
+    let mut treasures = Vec::new();
+    while session.dive_number < config.max_dives {
+        let roll = rng::random_roll_bps(seed, session.dive_number);
+        if !round_outcome(config, session.dive_number, roll).survived {
+            return Ok(SimResult {
+                lost: true,
+                final_dive: session.dive_number,
+                treasures,
+            });
+        }
+        advance_session(config, &mut session)?;
+        treasures.push(session.current_treasure);
+    }
+    Ok(SimResult {
+        lost: false,
+        final_dive: session.dive_number,
+        treasures,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::instructions::auto_play::run_auto_play;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_simulation_matches_played_session() {
+        let config = test_config(10);
+        let slot_hash = [42u8; 32];
+        let mut lost_seen = false;
+        let mut survived_seen = false;
+        for server_seed in 0..40u64 {
+            let mut session_acc = TestAccount {
+                // Fixed so the seeds below cover both outcomes whatever order tests run in
+                key: Pubkey::new_from_array([9u8; 32]),
+                ..TestAccount::program(&test_session(), 2_000_000)
+            };
+            let mut vault_acc = TestAccount::program(&test_vault(), 5_000_000_000);
+            let mut user_acc = TestAccount::wallet(0);
+            let session_info = session_acc.info();
+            let vault_info = vault_acc.info();
+            let user_info = user_acc.info();
+            let mut session = Account::<GameSession>::try_from(&session_info).unwrap();
+            let mut vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+            let seed = rng::generate_seed(&slot_hash, &session.key(), server_seed);
+
+            let simulated = simulate(&config, session.bet_amount, &seed).unwrap();
+            let active = run_auto_play(
+                &config,
+                &mut session,
+                &mut vault,
+                &mut test_user_stats(),
+                &mut test_house_stats(),
+                &user_info,
+                &seed,
+                config.max_dives,
+                &test_clock(10),
+            )
+            .unwrap();
+
+            assert_eq!(simulated.lost, !active, "seed {}", server_seed);
+            assert_eq!(simulated.final_dive, session.dive_number);
+            assert_eq!(simulated.treasures.len(), session.dive_number as usize - 1);
+            if active {
+                assert_eq!(simulated.treasures.last(), Some(&session.current_treasure));
+            }
+            lost_seen |= simulated.lost;
+            survived_seen |= !simulated.lost;
+        }
+        assert!(lost_seen && survived_seen);
+    }
+
+    #[test]
+    fn test_simulated_treasures_follow_curve() {
+        let config = fixed_odds_config(1_000_000, 6);
+        let bet = test_session().bet_amount;
+        let result = simulate(&config, bet, &[0u8; 32]).unwrap();
+        assert!(!result.lost);
+        assert_eq!(result.final_dive, 6);
+        let curve: Vec<u64> = (2..=6)
+            .map(|dive| game_math::treasure_for_dive(&config, bet, dive))
+            .collect();
+        assert_eq!(result.treasures, curve);
+    }
+
+    #[test]
+    fn test_expected_reservations_cover_simulated_payouts() {
+        let config = GameConfig {
+            reservation_mode: ReservationMode::Expected,
+            ..test_config(10)
+        };
+        let bet = test_session().bet_amount;
+        let effective_bet = game_math::effective_bet(&config, bet);
+        let reserved = game_math::reservation_for_bet(&config, effective_bet, config.max_dives);
+        let sessions = 10_000u64;
+        let runs: Vec<SimResult> = (0..sessions)
+            .map(|server_seed| {
+                let seed = rng::generate_seed(&[42u8; 32], &Pubkey::default(), server_seed);
+                simulate(&config, bet, &seed).unwrap()
+            })
+            .collect();
+        // Whichever dive the players all cash out at, the pooled reservations pay for it
+        for cash_out_dive in 1..=config.max_dives {
+            let paid: u64 = runs
+                .iter()
+                .map(|run| match cash_out_dive {
+                    1 => effective_bet,
+                    dive => run.treasures.get(dive as usize - 2).copied().unwrap_or(0),
+                })
+                .sum();
+            // The best dive pays the reservation on average, so allow for sampling noise
+            assert!(
+                paid <= sessions * reserved / 20 * 21,
+                "dive {cash_out_dive}: paid {paid} of {}",
+                sessions * reserved
+            );
+        }
+    }
+
+    #[test]
+    fn test_simulation_rejects_invalid_bet() {
+        let config = test_config(10);
+        assert!(simulate(&config, 0, &[0u8; 32]).is_err());
+    }
+}
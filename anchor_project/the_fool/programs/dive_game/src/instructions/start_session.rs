@@ -1,30 +1,212 @@
 use crate::errors::GameError;
-use crate::events::SessionStartedEvent;
+use crate::events::{FlowDirection, FlowReason, SessionStartedEvent};
 use crate::game_math;
+use crate::price_feed;
 use crate::states::*;
+use crate::util::vault_flow;
 use anchor_lang::prelude::*;
 use anchor_lang::system_program;
-pub fn start_session(ctx: Context<StartSession>, _session_index: u64) -> Result<()> {
+pub fn start_session(
+    ctx: Context<StartSession>,
+    session_index: u64,
+    bet_amount: u64,
+    referrer: Option<Pubkey>,
+    difficulty: u8,
+    max_dives_override: Option<u16>,
+    client_tag: Option<[u8; 16]>,
+) -> Result<()> {
     let config = &ctx.accounts.config;
     let house_vault = &mut ctx.accounts.house_vault;
     let session = &mut ctx.accounts.session;
     let clock = Clock::get()?;
+    config.ensure_not_paused()?;
     require!(!house_vault.locked, GameError::HouseLocked);
+    validate_referrer(
+        referrer,
+        &ctx.accounts.user.key(),
+        &house_vault.house_authority,
+    )?;
 
-    // Use fixed bet from config
-    let bet_amount = config.fixed_bet;
-    let max_payout = game_math::max_payout_for_bet(config, bet_amount);
+    // Event sessions: the house co-signs to let this session dive deeper than max_dives
+    if let Some(max_dives) = max_dives_override {
+        require!(
+            ctx.accounts.house_authority.is_signer,
+            GameError::InvalidMaxDivesOverride
+        );
+        config.validate_max_dives_override(max_dives)?;
+    }
+
+    // USD-denominated configs take bet_amount in cents and price it at the feed's current rate,
+    // which the session keeps for converting its payout back
+    let locked_price_micro_usd = if config.usd_denominated {
+        let feed = ctx
+            .accounts
+            .price_feed
+            .as_ref()
+            .ok_or(GameError::InvalidPriceFeed)?;
+        price_feed::read_sol_price(
+            feed,
+            &config.price_feed,
+            config.max_price_age_secs,
+            clock.unix_timestamp,
+        )?
+        .micro_usd()?
+    } else {
+        0
+    };
+    let bet_amount = if config.usd_denominated {
+        game_math::usd_cents_to_lamports(bet_amount, locked_price_micro_usd)
+            .ok_or(GameError::Overflow)?
+    } else {
+        bet_amount
+    };
+
+    config.validate_bet(bet_amount)?;
+    // Measured before the bet lands so the cap reflects the house's own liquidity
+    config.validate_bet_vault_cap(bet_amount, house_vault.to_account_info().lamports())?;
+    // Coarse circuit breaker on open sessions across the vault, next to the per-user limit
+    house_vault.open_session(config.max_total_sessions)?;
+    // The rake stays in the vault unreserved as house profit and the jackpot share is set
+    // aside in jackpot_pool; the rest is the bet in play
+    let rake_amount = game_math::rake_for_bet(config, bet_amount);
+    let jackpot_amount = game_math::jackpot_for_bet(config, bet_amount);
+    let effective_bet = game_math::effective_bet(config, bet_amount);
+    let max_payout = game_math::max_payout_for_bet(config, effective_bet);
+    let reserved_payout = game_math::reservation_for_bet(
+        config,
+        effective_bet,
+        max_dives_override.unwrap_or(config.max_dives),
+    );
     let transfer_ix = system_program::Transfer {
         from: ctx.accounts.user.to_account_info(),
         to: house_vault.to_account_info(),
     };
     let cpi_ctx = CpiContext::new(ctx.accounts.system_program.to_account_info(), transfer_ix);
     system_program::transfer(cpi_ctx, bet_amount)?;
+    emit!(vault_flow(
+        &house_vault.to_account_info(),
+        FlowDirection::In,
+        bet_amount,
+        FlowReason::Bet
+    ));
     let vault_balance = house_vault.to_account_info().lamports();
+    let vault_key = house_vault.key();
+    reserve_session_funds(
+        config,
+        house_vault,
+        vault_key,
+        vault_balance,
+        jackpot_amount,
+        reserved_payout,
+    )?;
+
+    // Phase 1 RNG Security: No longer generate or store RNG seed
+    // Each round will use fresh entropy from SlotHashes sysvar
+
+    session.user = ctx.accounts.user.key();
+    session.house_vault = house_vault.key();
+    session.status = SessionStatus::Active;
+    session.bet_amount = effective_bet;
+    session.current_treasure = effective_bet;
+    session.max_payout = max_payout;
+    session.reserved_payout = reserved_payout;
+    session.dive_number = 1;
+    session.bump = ctx.bumps.session;
+
+    // Phase 2: Initialize activity tracking for timeout-based cleanup
+    session.record_activity(clock.slot, config.timeout_slots);
+    session.set_commitment([0u8; 32], 0);
+    session.total_withdrawn = 0;
+    session.gross_treasure = 0;
+    session.server_seed = None;
+    session.insured = false;
+    session.keeper = house_vault.game_keeper;
+    session.max_dives_override = max_dives_override;
+    session.milestone_claimed = false;
+    session.locked_price_micro_usd = locked_price_micro_usd;
+    session.next_dive_boost_bps = 0;
+    session.client_tag = client_tag.unwrap_or_default();
+    session.pending_vrf = None;
+    session.vrf_request_slot = 0;
+    session.referrer = referrer;
+    session.version = SESSION_VERSION;
+    // play_round and cash_out derive the config PDA from this
+    session.difficulty = difficulty;
+
+    let user_stats = &mut ctx.accounts.user_stats;
+    user_stats.user = ctx.accounts.user.key();
+    user_stats.bump = ctx.bumps.user_stats;
+    user_stats.claim_session_index(session_index)?;
+    user_stats.open_session(config.max_active_sessions_per_user)?;
+    user_stats.record_bet(bet_amount)?;
+
+    let house_stats = &mut ctx.accounts.house_stats;
+    house_stats.house_vault = house_vault.key();
+    house_stats.bump = ctx.bumps.house_stats;
+    house_stats.record_session_start(bet_amount)?;
 
-    // Handle the case where total_reserved exceeds actual balance (accounting error)
+    let leaderboard = &mut ctx.accounts.leaderboard;
+    leaderboard.house_vault = house_vault.key();
+    leaderboard.bump = ctx.bumps.leaderboard;
+    emit!(session_started_event(
+        session,
+        rake_amount,
+        house_vault.next_sequence()?,
+        clock.unix_timestamp,
+    ));
+    ctx.accounts.config.open_sessions(1)?;
+    Ok(())
+}
+
+/// SessionStartedEvent for a freshly opened `session`, shared with batch_start_sessions
+pub(crate) fn session_started_event(
+    session: &Account<GameSession>,
+    rake_amount: u64,
+    sequence: u64,
+    timestamp: i64,
+) -> SessionStartedEvent {
+    SessionStartedEvent {
+        session: session.key(),
+        user: session.user,
+        house_vault: session.house_vault,
+        bet_amount: session.bet_amount,
+        rake_amount,
+        max_payout: session.max_payout,
+        sequence,
+        timestamp,
+        client_tag: session.client_tag,
+    }
+}
+/// Rejects a referrer that would hand the referral fee back to the player or the house
+pub(crate) fn validate_referrer(
+    referrer: Option<Pubkey>,
+    user: &Pubkey,
+    house_authority: &Pubkey,
+) -> Result<()> {
+    if let Some(referrer) = referrer {
+        require!(
+            referrer != *user && referrer != *house_authority,
+            GameError::SelfReferral
+        );
+    }
+    Ok(())
+}
+/// Books a session's jackpot share and reserves `reserved_payout` once the bet is in the vault,
+/// rejecting the start if the vault can't cover it
+/// Shared with batch_start_sessions, which runs it for every entry before anything is created
+pub(crate) fn reserve_session_funds(
+    config: &GameConfig,
+    house_vault: &mut HouseVault,
+    vault_key: Pubkey,
+    vault_balance: u64,
+    jackpot_amount: u64,
+    reserved_payout: u64,
+) -> Result<()> {
+    house_vault.add_to_jackpot(jackpot_amount)?;
+
+    // Handle the case where committed funds exceed actual balance (accounting error)
     // This can happen if sessions weren't properly cleaned up
-    let available = match vault_balance.checked_sub(house_vault.total_reserved) {
+    let available = match vault_balance.checked_sub(house_vault.committed()) {
         Some(avail) => avail,
         None => {
             // total_reserved > vault_balance - log detailed error
@@ -32,84 +214,57 @@ pub fn start_session(ctx: Context<StartSession>, _session_index: u64) -> Result<
                 "VAULT_ACCOUNTING_ERROR vault_balance={} total_reserved={} vault={}",
                 vault_balance / 1_000_000_000,
                 house_vault.total_reserved / 1_000_000_000,
-                house_vault.key()
+                vault_key
             );
             msg!("HINT: Reserved funds exceed actual balance. Admin should reset total_reserved.");
             return Err(GameError::InsufficientVaultBalance.into());
         }
     };
 
-    // Relaxed vault requirement: only require 20% of max_payout to be available
+    // Relaxed vault requirement (demo mode): only require 20% of max_payout to be available
     // This allows the game to run with lower vault balances for testing/demo
-    // Still reserve the full max_payout for accounting purposes
-    let required_balance = max_payout / 5; // 20% of max_payout
+    // With require_full_coverage the entire max_payout must be available
+    // Either way the whole reservation is booked for accounting purposes
+    let required_balance = config.required_coverage(reserved_payout);
 
     if available < required_balance {
         msg!(
             "INSUFFICIENT_VAULT need={} have={} vault={}",
             required_balance / 1_000_000_000,
             available / 1_000_000_000,
-            house_vault.key()
+            vault_key
         );
         return Err(GameError::InsufficientVaultBalance.into());
     }
 
     // CIRCUIT BREAKER: Prevent vault insolvency from 20% rule
     // The 20% rule allows up to 5x leverage (vault can reserve 5x its balance)
-    // This circuit breaker caps total reservations at 100% of vault balance to prevent insolvency
-    // 
-    // Without this: 5 concurrent sessions could reserve 500% of vault balance
-    // With this: Total reserved can never exceed actual vault balance
-    let new_total_reserved = house_vault
-        .total_reserved
-        .checked_add(max_payout)
-        .ok_or(GameError::Overflow)?;
-
-    if new_total_reserved > vault_balance {
+    // This caps total reservations at max_exposure_bps of the vault balance (100% by default)
+    if let Err(err) =
+        house_vault.check_exposure(vault_balance, reserved_payout, config.max_exposure_bps)
+    {
         msg!(
-            "VAULT_CAPACITY_EXCEEDED vault_balance={} SOL, current_reserved={} SOL, requested={} SOL, would_be={} SOL",
+            "VAULT_CAPACITY_EXCEEDED vault_balance={} SOL, current_reserved={} SOL, requested={} SOL, max_exposure={}ppm",
             vault_balance / 1_000_000_000,
             house_vault.total_reserved / 1_000_000_000,
-            max_payout / 1_000_000_000,
-            new_total_reserved / 1_000_000_000
+            reserved_payout / 1_000_000_000,
+            config.max_exposure_bps
         );
-        return Err(GameError::VaultCapacityExceeded.into());
+        return Err(err);
     }
 
-    // Reserve full max_payout for proper accounting
-    house_vault.reserve(max_payout)?;
-
-    // Phase 1 RNG Security: No longer generate or store RNG seed
-    // Each round will use fresh entropy from SlotHashes sysvar
-
-    session.user = ctx.accounts.user.key();
-    session.house_vault = house_vault.key();
-    session.status = SessionStatus::Active;
-    session.bet_amount = bet_amount;
-    session.current_treasure = bet_amount;
-    session.max_payout = max_payout;
-    session.dive_number = 1;
-    session.bump = ctx.bumps.session;
-
-    // Phase 2: Initialize activity tracking for timeout-based cleanup
-    session.last_active_slot = clock.slot;
-    emit!(SessionStartedEvent {
-        session: session.key(),
-        user: session.user,
-        house_vault: session.house_vault,
-        bet_amount: session.bet_amount,
-        max_payout: session.max_payout,
-        timestamp: clock.unix_timestamp,
-    });
+    // max_payout, or its expected value under ReservationMode::Expected
+    house_vault.reserve(reserved_payout)?;
     Ok(())
 }
 #[derive(Accounts)]
-#[instruction(session_index: u64)]
+#[instruction(session_index: u64, bet_amount: u64, referrer: Option<Pubkey>, difficulty: u8)]
 pub struct StartSession<'info> {
     #[account(mut)]
     pub user: Signer<'info>,
     #[account(
-        seeds = [GAME_CONFIG_SEED.as_bytes()],
+        mut,
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&difficulty)],
         bump = config.bump,
     )]
     pub config: Account<'info, GameConfig>,
@@ -132,5 +287,85 @@ pub struct StartSession<'info> {
         bump
     )]
     pub session: Account<'info, GameSession>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + UserStats::INIT_SPACE,
+        seeds = [USER_STATS_SEED.as_bytes(), user.key().as_ref()],
+        bump
+    )]
+    pub user_stats: Account<'info, UserStats>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + HouseStats::INIT_SPACE,
+        seeds = [HOUSE_STATS_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump
+    )]
+    pub house_stats: Account<'info, HouseStats>,
+    #[account(
+        init_if_needed,
+        payer = user,
+        space = 8 + Leaderboard::INIT_SPACE,
+        seeds = [LEADERBOARD_SEED.as_bytes(), house_vault.key().as_ref()],
+        bump
+    )]
+    pub leaderboard: Account<'info, Leaderboard>,
     pub system_program: Program<'info, System>,
+    /// CHECK: must be config.price_feed, owned by the Pyth receiver; only read when
+    /// config.usd_denominated
+    pub price_feed: Option<UncheckedAccount<'info>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_client_tag_round_trips_through_session_and_event() {
+        let tag = *b"spring-campaign\0";
+        let mut session_acc = TestAccount::program(
+            &GameSession {
+                client_tag: tag,
+                ..test_session()
+            },
+            2_000_000,
+        );
+        let session_info = session_acc.info();
+        let session = Account::<GameSession>::try_from(&session_info).unwrap();
+        assert_eq!(session.client_tag, tag);
+
+        let event = session_started_event(&session, 0, 7, 1_700);
+        let data = anchor_lang::Event::data(&event);
+        let decoded = SessionStartedEvent::try_from_slice(&data[8..]).unwrap();
+        assert_eq!(decoded.client_tag, tag);
+        assert_eq!(decoded.session, session.key());
+        assert_eq!(decoded.sequence, 7);
+    }
+
+    #[test]
+    fn test_self_and_house_referrals_are_rejected() {
+        let user = Pubkey::new_unique();
+        let house_authority = Pubkey::new_unique();
+
+        let err = validate_referrer(Some(user), &user, &house_authority).unwrap_err();
+        assert_eq!(err, GameError::SelfReferral.into());
+        let err = validate_referrer(Some(house_authority), &user, &house_authority).unwrap_err();
+        assert_eq!(err, GameError::SelfReferral.into());
+
+        assert!(validate_referrer(Some(Pubkey::new_unique()), &user, &house_authority).is_ok());
+        assert!(validate_referrer(None, &user, &house_authority).is_ok());
+    }
+
+    #[test]
+    fn test_untagged_session_reports_zeros() {
+        let mut session_acc = TestAccount::program(&test_session(), 2_000_000);
+        let session_info = session_acc.info();
+        let session = Account::<GameSession>::try_from(&session_info).unwrap();
+        assert_eq!(
+            session_started_event(&session, 0, 0, 0).client_tag,
+            [0u8; 16]
+        );
+    }
 }
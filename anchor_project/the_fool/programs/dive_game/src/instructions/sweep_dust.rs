@@ -0,0 +1,110 @@
+use crate::errors::GameError;
+use crate::events::{DustSweptEvent, FlowReason};
+use crate::states::*;
+use crate::util::pay_from_vault;
+use anchor_lang::prelude::*;
+
+/// Moves rounding dust left in the vault to the house authority
+/// Only sweeps when the free balance is at most MAX_DUST_LAMPORTS; real profit is withdrawn
+/// through withdraw_house and its timelock and cosigner. Committed funds and the rent-exempt
+/// minimum are never touched.
+pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+    let clock = Clock::get()?;
+    let vault_info = ctx.accounts.house_vault.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+    let amount = sweep_free_dust(
+        &ctx.accounts.house_vault,
+        &ctx.accounts.house_authority.to_account_info(),
+        rent_exempt,
+    )?;
+    emit!(DustSweptEvent {
+        house_vault: ctx.accounts.house_vault.key(),
+        house_authority: ctx.accounts.house_authority.key(),
+        amount,
+        timestamp: clock.unix_timestamp,
+    });
+    Ok(())
+}
+
+/// Pays the vault's free balance to `authority` if it is dust, returning the amount swept
+pub(crate) fn sweep_free_dust<'info>(
+    house_vault: &Account<'info, HouseVault>,
+    authority: &AccountInfo<'info>,
+    rent_exempt: u64,
+) -> Result<u64> {
+    let vault_info = house_vault.to_account_info();
+    let amount = house_vault.withdrawable(vault_info.lamports(), rent_exempt);
+    require!(amount <= MAX_DUST_LAMPORTS, GameError::NotDust);
+    if amount > 0 {
+        emit!(pay_from_vault(
+            &vault_info,
+            authority,
+            amount,
+            FlowReason::DustSweep
+        )?);
+    }
+    Ok(amount)
+}
+
+#[derive(Accounts)]
+pub struct SweepDust<'info> {
+    #[account(mut)]
+    pub house_authority: Signer<'info>,
+    #[account(
+        mut,
+        has_one = house_authority,
+    )]
+    pub house_vault: Account<'info, HouseVault>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    const RENT: u64 = 1_000_000;
+
+    fn run_sweep(balance: u64) -> (Result<u64>, u64, u64) {
+        let mut vault_acc = TestAccount::program(&test_vault(), balance);
+        let mut authority_acc = TestAccount::wallet(0);
+        let vault_info = vault_acc.info();
+        let authority_info = authority_acc.info();
+        let vault = Account::<HouseVault>::try_from(&vault_info).unwrap();
+        let result = sweep_free_dust(&vault, &authority_info, RENT);
+        (result, vault_info.lamports(), authority_info.lamports())
+    }
+
+    #[test]
+    fn test_sweeps_dust_down_to_committed_and_rent() {
+        let floor = test_vault().total_reserved + RENT;
+        let (result, vault, authority) = run_sweep(floor + 1_234);
+        assert_eq!(result.unwrap(), 1_234);
+        assert_eq!(vault, floor);
+        assert_eq!(authority, 1_234);
+    }
+
+    #[test]
+    fn test_dust_threshold_is_inclusive() {
+        let floor = test_vault().total_reserved + RENT;
+        let (result, vault, _) = run_sweep(floor + MAX_DUST_LAMPORTS);
+        assert_eq!(result.unwrap(), MAX_DUST_LAMPORTS);
+        assert_eq!(vault, floor);
+    }
+
+    #[test]
+    fn test_balance_above_dust_is_refused() {
+        let floor = test_vault().total_reserved + RENT;
+        let (result, vault, authority) = run_sweep(floor + MAX_DUST_LAMPORTS + 1);
+        assert_eq!(result.unwrap_err(), GameError::NotDust.into());
+        assert_eq!(vault, floor + MAX_DUST_LAMPORTS + 1);
+        assert_eq!(authority, 0);
+    }
+
+    #[test]
+    fn test_nothing_to_sweep_below_floor() {
+        let floor = test_vault().total_reserved + RENT;
+        let (result, vault, _) = run_sweep(floor - 10);
+        assert_eq!(result.unwrap(), 0);
+        assert_eq!(vault, floor - 10);
+    }
+}
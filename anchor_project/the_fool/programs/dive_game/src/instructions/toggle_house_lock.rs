@@ -1,9 +1,24 @@
+use crate::errors::GameError;
 use crate::events::ToggleHouseLockEvent;
 use crate::states::*;
 use anchor_lang::prelude::*;
+/// Locking is always allowed; unlocking requires the vault to cover its reservations, jackpot
+/// and rent, so an insolvent vault can't be reopened for play
+/// Unlocking also clears the circuit breaker's run of big payouts
 pub fn toggle_house_lock(ctx: Context<ToggleHouseLock>) -> Result<()> {
     let house_vault = &mut ctx.accounts.house_vault;
     let clock = Clock::get()?;
+    if house_vault.locked {
+        let vault_info = house_vault.to_account_info();
+        let rent_exempt = Rent::get()?.minimum_balance(vault_info.data_len());
+        require!(
+            house_vault
+                .health(vault_info.lamports(), rent_exempt)
+                .solvent,
+            GameError::InsufficientVaultBalance
+        );
+        house_vault.reset_payout_streak();
+    }
     house_vault.locked = !house_vault.locked;
     emit!(ToggleHouseLockEvent {
         house_vault: house_vault.key(),
@@ -23,3 +38,61 @@ pub struct ToggleHouseLock<'info> {
     )]
     pub house_vault: Account<'info, HouseVault>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    /// Toggles a vault holding `lamports` with 1 SOL reserved, returning the result and lock
+    fn toggle(locked: bool, lamports: u64) -> (Result<()>, bool) {
+        install_syscall_stubs();
+        let mut authority_acc = TestAccount::wallet(0);
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                house_authority: authority_acc.key,
+                locked,
+                total_reserved: 1_000_000_000,
+                ..test_vault()
+            },
+            lamports,
+        );
+        let authority_info = authority_acc.info();
+        let vault_info = vault_acc.info();
+        let mut accounts = ToggleHouseLock {
+            house_authority: Signer::try_from(&authority_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+        };
+        let result = toggle_house_lock(Context::new(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            Default::default(),
+        ));
+        (result, accounts.house_vault.locked)
+    }
+
+    #[test]
+    fn test_unlocking_underfunded_vault_fails() {
+        let (result, locked) = toggle(true, 1_000_000_000);
+        assert_eq!(
+            result.unwrap_err(),
+            GameError::InsufficientVaultBalance.into()
+        );
+        assert!(locked);
+    }
+
+    #[test]
+    fn test_unlocking_solvent_vault_succeeds() {
+        let (result, locked) = toggle(true, 2_000_000_000);
+        result.unwrap();
+        assert!(!locked);
+    }
+
+    #[test]
+    fn test_locking_underfunded_vault_is_allowed() {
+        let (result, locked) = toggle(false, 1_000_000_000);
+        result.unwrap();
+        assert!(locked);
+    }
+}
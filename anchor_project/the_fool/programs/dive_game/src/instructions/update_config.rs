@@ -1,7 +1,16 @@
+use crate::errors::GameError;
+use crate::events::ConfigUpdatedEvent;
 use crate::states::*;
 use anchor_lang::prelude::*;
 
+#[cfg(feature = "tsify")]
+use serde::{Deserialize, Serialize};
+#[cfg(feature = "tsify")]
+use tsify::Tsify;
+
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Default)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
 pub struct UpdateConfigParams {
     pub base_survival_ppm: Option<u32>,
     pub decay_per_dive_ppm: Option<u32>,
@@ -10,54 +19,275 @@ pub struct UpdateConfigParams {
     pub treasure_multiplier_den: Option<u16>,
     pub max_payout_multiplier: Option<u16>,
     pub max_dives: Option<u16>,
-    pub fixed_bet: Option<u64>,
+    pub min_bet: Option<u64>,
+    pub max_bet: Option<u64>,
+    pub vrf_enabled: Option<bool>,
+    pub house_edge_bps: Option<u32>,
+    pub curve_mode: Option<CurveMode>,
+    pub linear_step_bps: Option<u32>,
+    pub referral_fee_bps: Option<u32>,
+    pub max_exposure_bps: Option<u32>,
+    pub timeout_slots: Option<u64>,
+    pub require_full_coverage: Option<bool>,
+    pub min_dives_to_cash_out: Option<u16>,
+    pub rake_bps: Option<u32>,
+    pub final_gamble_bps: Option<u32>,
+    pub jackpot_bps: Option<u32>,
+    pub max_payout_lamports: Option<u64>,
+    pub allowed_bets: Option<[u64; 8]>,
+    pub use_bet_whitelist: Option<bool>,
+    pub crank_reward_lamports: Option<u64>,
+    pub max_active_sessions_per_user: Option<u16>,
+    pub decay_mode: Option<DecayMode>,
+    pub insurance_payout_bps: Option<u32>,
+    pub verbose_logging: Option<bool>,
+    pub first_dive_guaranteed: Option<bool>,
+    pub allow_breakeven_exit: Option<bool>,
+    pub absolute_max_dives: Option<u16>,
+    pub min_slots_before_play: Option<u64>,
+    pub loss_rebate_bps: Option<u32>,
+    pub bet_step: Option<u64>,
+    pub withdraw_delay_slots: Option<u64>,
+    pub max_bet_vault_bps: Option<u32>,
+    pub milestone_dive: Option<u16>,
+    pub milestone_bonus_bps: Option<u32>,
+    pub usd_denominated: Option<bool>,
+    pub price_feed: Option<Pubkey>,
+    pub max_price_age_secs: Option<u64>,
+    pub max_total_sessions: Option<u32>,
+    pub rounding_mode: Option<RoundingMode>,
+    pub bonus_wagering_multiple: Option<u16>,
+    pub max_boost_bps: Option<u32>,
+    pub expiry_mode: Option<ExpiryMode>,
+    pub big_payout_lamports: Option<u64>,
+    pub max_consecutive_big_payouts: Option<u16>,
+    pub reservation_mode: Option<ReservationMode>,
+}
+
+impl UpdateConfigParams {
+    /// Applies only the fields that are provided
+    pub fn apply(&self, config: &mut GameConfig) {
+        if let Some(val) = self.base_survival_ppm {
+            config.base_survival_ppm = val;
+        }
+        if let Some(val) = self.decay_per_dive_ppm {
+            config.decay_per_dive_ppm = val;
+        }
+        if let Some(val) = self.min_survival_ppm {
+            config.min_survival_ppm = val;
+        }
+        if let Some(val) = self.treasure_multiplier_num {
+            config.treasure_multiplier_num = val;
+        }
+        if let Some(val) = self.treasure_multiplier_den {
+            config.treasure_multiplier_den = val;
+        }
+        if let Some(val) = self.max_payout_multiplier {
+            config.max_payout_multiplier = val;
+        }
+        if let Some(val) = self.max_dives {
+            config.max_dives = val;
+        }
+        if let Some(val) = self.min_bet {
+            config.min_bet = val;
+        }
+        if let Some(val) = self.max_bet {
+            config.max_bet = val;
+        }
+        if let Some(val) = self.vrf_enabled {
+            config.vrf_enabled = val;
+        }
+        if let Some(val) = self.house_edge_bps {
+            config.house_edge_bps = val;
+        }
+        if let Some(val) = self.curve_mode {
+            config.curve_mode = val;
+        }
+        if let Some(val) = self.linear_step_bps {
+            config.linear_step_bps = val;
+        }
+        if let Some(val) = self.referral_fee_bps {
+            config.referral_fee_bps = val;
+        }
+        if let Some(val) = self.max_exposure_bps {
+            config.max_exposure_bps = val;
+        }
+        if let Some(val) = self.timeout_slots {
+            config.timeout_slots = val;
+        }
+        if let Some(val) = self.require_full_coverage {
+            config.require_full_coverage = val;
+        }
+        if let Some(val) = self.min_dives_to_cash_out {
+            config.min_dives_to_cash_out = val;
+        }
+        if let Some(val) = self.rake_bps {
+            config.rake_bps = val;
+        }
+        if let Some(val) = self.final_gamble_bps {
+            config.final_gamble_bps = val;
+        }
+        if let Some(val) = self.jackpot_bps {
+            config.jackpot_bps = val;
+        }
+        if let Some(val) = self.max_payout_lamports {
+            config.max_payout_lamports = val;
+        }
+        if let Some(val) = self.allowed_bets {
+            config.allowed_bets = val;
+        }
+        if let Some(val) = self.use_bet_whitelist {
+            config.use_bet_whitelist = val;
+        }
+        if let Some(val) = self.crank_reward_lamports {
+            config.crank_reward_lamports = val;
+        }
+        if let Some(val) = self.max_active_sessions_per_user {
+            config.max_active_sessions_per_user = val;
+        }
+        if let Some(val) = self.decay_mode {
+            config.decay_mode = val;
+        }
+        if let Some(val) = self.insurance_payout_bps {
+            config.insurance_payout_bps = val;
+        }
+        if let Some(val) = self.verbose_logging {
+            config.verbose_logging = val;
+        }
+        if let Some(val) = self.first_dive_guaranteed {
+            config.first_dive_guaranteed = val;
+        }
+        if let Some(val) = self.allow_breakeven_exit {
+            config.allow_breakeven_exit = val;
+        }
+        if let Some(val) = self.absolute_max_dives {
+            config.absolute_max_dives = val;
+        }
+        if let Some(val) = self.min_slots_before_play {
+            config.min_slots_before_play = val;
+        }
+        if let Some(val) = self.loss_rebate_bps {
+            config.loss_rebate_bps = val;
+        }
+        if let Some(val) = self.bet_step {
+            config.bet_step = val;
+        }
+        if let Some(val) = self.withdraw_delay_slots {
+            config.withdraw_delay_slots = val;
+        }
+        if let Some(val) = self.max_bet_vault_bps {
+            config.max_bet_vault_bps = val;
+        }
+        if let Some(val) = self.milestone_dive {
+            config.milestone_dive = val;
+        }
+        if let Some(val) = self.milestone_bonus_bps {
+            config.milestone_bonus_bps = val;
+        }
+        if let Some(val) = self.usd_denominated {
+            config.usd_denominated = val;
+        }
+        if let Some(val) = self.price_feed {
+            config.price_feed = val;
+        }
+        if let Some(val) = self.max_price_age_secs {
+            config.max_price_age_secs = val;
+        }
+        if let Some(val) = self.max_total_sessions {
+            config.max_total_sessions = val;
+        }
+        if let Some(val) = self.rounding_mode {
+            config.rounding_mode = val;
+        }
+        if let Some(val) = self.bonus_wagering_multiple {
+            config.bonus_wagering_multiple = val;
+        }
+        if let Some(val) = self.max_boost_bps {
+            config.max_boost_bps = val;
+        }
+        if let Some(val) = self.expiry_mode {
+            config.expiry_mode = val;
+        }
+        if let Some(val) = self.big_payout_lamports {
+            config.big_payout_lamports = val;
+        }
+        if let Some(val) = self.max_consecutive_big_payouts {
+            config.max_consecutive_big_payouts = val;
+        }
+        if let Some(val) = self.reservation_mode {
+            config.reservation_mode = val;
+        }
+    }
 }
 
 /// Update existing game configuration
 /// Only the admin can call this
 pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
     let config = &mut ctx.accounts.config;
+    let previous = config.clone();
 
-    // Update only the fields that are provided
-    if let Some(val) = params.base_survival_ppm {
-        config.base_survival_ppm = val;
-    }
-    if let Some(val) = params.decay_per_dive_ppm {
-        config.decay_per_dive_ppm = val;
-    }
-    if let Some(val) = params.min_survival_ppm {
-        config.min_survival_ppm = val;
-    }
-    if let Some(val) = params.treasure_multiplier_num {
-        config.treasure_multiplier_num = val;
-    }
-    if let Some(val) = params.treasure_multiplier_den {
-        config.treasure_multiplier_den = val;
-    }
-    if let Some(val) = params.max_payout_multiplier {
-        config.max_payout_multiplier = val;
-    }
-    if let Some(val) = params.max_dives {
-        config.max_dives = val;
-    }
-    if let Some(val) = params.fixed_bet {
-        config.fixed_bet = val;
-    }
+    let clock = Clock::get()?;
+
+    params.apply(config);
 
     // Validate the updated config
     config.validate()?;
+    check_live_reservations(&previous, config)?;
+    hold_withdraw_delay(&previous, config, clock.slot);
 
     msg!("Game config updated successfully");
     msg!("  Max dives: {}", config.max_dives);
     msg!(
-        "  Fixed bet: {} lamports ({} SOL)",
-        config.fixed_bet,
-        config.fixed_bet / 1_000_000_000
+        "  Bet range: {}-{} lamports",
+        config.min_bet,
+        config.max_bet
     );
 
+    emit!(config_updated_event(
+        config.key(),
+        config,
+        clock.unix_timestamp
+    ));
+
+    Ok(())
+}
+
+/// Open sessions reserved against the old max_payout_multiplier, so it can only go up once
+/// no session under this config is live on any vault
+pub(crate) fn check_live_reservations(previous: &GameConfig, updated: &GameConfig) -> Result<()> {
+    if updated.max_payout_multiplier > previous.max_payout_multiplier {
+        require!(
+            previous.active_sessions == 0,
+            GameError::VaultHasReservedFunds
+        );
+    }
     Ok(())
 }
 
+/// Keeps the withdraw delay in force at `slot` running to its end when the update lowers it,
+/// so a shorter delay can't be set and used in the same transaction
+pub(crate) fn hold_withdraw_delay(previous: &GameConfig, updated: &mut GameConfig, slot: u64) {
+    let in_force = previous.withdraw_delay_at(slot);
+    if updated.withdraw_delay_slots < in_force {
+        updated.withdraw_delay_floor_slots = in_force;
+        updated.withdraw_delay_floor_until = slot.saturating_add(in_force);
+    }
+}
+
+/// Audit event carrying the full resulting config
+pub(crate) fn config_updated_event(
+    key: Pubkey,
+    config: &GameConfig,
+    timestamp: i64,
+) -> ConfigUpdatedEvent {
+    ConfigUpdatedEvent {
+        config: key,
+        admin: config.admin,
+        settings: config.clone(),
+        timestamp,
+    }
+}
+
 #[derive(Accounts)]
 pub struct UpdateConfig<'info> {
     #[account(mut)]
@@ -66,8 +296,213 @@ pub struct UpdateConfig<'info> {
     #[account(
         mut,
         has_one = admin,
-        seeds = [GAME_CONFIG_SEED.as_bytes()],
+        seeds = [GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&config.difficulty)],
         bump = config.bump,
     )]
     pub config: Account<'info, GameConfig>,
 }
+
+#[cfg(all(test, feature = "tsify"))]
+mod tsify_tests {
+    use super::*;
+    use crate::instructions::GameConfigParams;
+
+    /// Generated TS declarations must track every on-chain param field
+    #[test]
+    fn test_param_declarations_cover_all_fields() {
+        for decl in [GameConfigParams::DECL, UpdateConfigParams::DECL] {
+            for field in [
+                "base_survival_ppm",
+                "curve_mode",
+                "timeout_slots",
+                "require_full_coverage",
+                "min_dives_to_cash_out",
+            ] {
+                assert!(decl.contains(field), "{field} missing from {decl}");
+            }
+        }
+        assert!(GameConfigParams::DECL.contains("GameConfigParams"));
+        assert!(UpdateConfigParams::DECL.contains("UpdateConfigParams"));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{test_config, test_session};
+
+    fn initialized_config() -> GameConfig {
+        test_config(5)
+    }
+
+    #[test]
+    fn test_update_single_field_reads_back() {
+        let mut config = initialized_config();
+        UpdateConfigParams {
+            max_dives: Some(10),
+            ..Default::default()
+        }
+        .apply(&mut config);
+        assert!(config.validate().is_ok());
+        assert_eq!(config.max_dives, 10);
+        assert_eq!(
+            config.base_survival_ppm,
+            initialized_config().base_survival_ppm
+        );
+        assert_eq!(config.min_bet, initialized_config().min_bet);
+    }
+
+    #[test]
+    fn test_config_updated_event_matches_stored_config() {
+        let mut config = initialized_config();
+        config.admin = Pubkey::new_unique();
+        UpdateConfigParams {
+            house_edge_bps: Some(20_000),
+            max_payout_multiplier: Some(50),
+            ..Default::default()
+        }
+        .apply(&mut config);
+
+        let key = Pubkey::new_unique();
+        let event = config_updated_event(key, &config, 1_700_000_000);
+
+        assert_eq!(event.config, key);
+        assert_eq!(event.admin, config.admin);
+        assert_eq!(event.settings.house_edge_bps, 20_000);
+        assert_eq!(event.settings.max_payout_multiplier, 50);
+        assert_eq!(
+            event.settings.try_to_vec().unwrap(),
+            config.try_to_vec().unwrap()
+        );
+        assert_eq!(event.timestamp, 1_700_000_000);
+    }
+
+    #[test]
+    fn test_update_bet_bounds() {
+        let mut config = initialized_config();
+        UpdateConfigParams {
+            min_bet: Some(50_000_000),
+            max_bet: Some(500_000_000),
+            ..Default::default()
+        }
+        .apply(&mut config);
+        assert!(config.validate().is_ok());
+        assert_eq!(config.min_bet, 50_000_000);
+        assert_eq!(config.max_bet, 500_000_000);
+    }
+
+    #[test]
+    fn test_update_min_bet_above_max_bet_fails_validation() {
+        let mut config = initialized_config();
+        UpdateConfigParams {
+            min_bet: Some(config.max_bet + 1),
+            ..Default::default()
+        }
+        .apply(&mut config);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_multiplier_increase_blocked_by_live_sessions() {
+        // Sessions open on some other vault; whichever vault the admin looks at is irrelevant
+        let previous = GameConfig {
+            active_sessions: 1,
+            ..initialized_config()
+        };
+        let mut config = previous.clone();
+        UpdateConfigParams {
+            max_payout_multiplier: Some(previous.max_payout_multiplier + 50),
+            ..Default::default()
+        }
+        .apply(&mut config);
+        assert!(config.validate().is_ok());
+
+        let err = check_live_reservations(&previous, &config).unwrap_err();
+        assert_eq!(err, GameError::VaultHasReservedFunds.into());
+
+        let idle = initialized_config();
+        assert!(check_live_reservations(&idle, &config).is_ok());
+    }
+
+    #[test]
+    fn test_multiplier_decrease_allowed_with_live_sessions() {
+        let previous = GameConfig {
+            active_sessions: 1,
+            ..initialized_config()
+        };
+        let mut config = previous.clone();
+        UpdateConfigParams {
+            max_payout_multiplier: Some(previous.max_payout_multiplier - 50),
+            ..Default::default()
+        }
+        .apply(&mut config);
+        assert!(check_live_reservations(&previous, &config).is_ok());
+    }
+
+    #[test]
+    fn test_session_count_follows_open_and_close() {
+        let mut config = initialized_config();
+        config.open_sessions(2).unwrap();
+        let mut session = test_session();
+        config.release_ended(&session);
+        assert_eq!(config.active_sessions, 2);
+
+        session.mark_lost().unwrap();
+        config.release_ended(&session);
+        config.close_sessions(5);
+        assert_eq!(config.active_sessions, 0);
+    }
+
+    #[test]
+    fn test_lowered_withdraw_delay_waits_out_the_old_one() {
+        let previous = GameConfig {
+            withdraw_delay_slots: 100,
+            ..initialized_config()
+        };
+        let mut config = previous.clone();
+        UpdateConfigParams {
+            withdraw_delay_slots: Some(0),
+            ..Default::default()
+        }
+        .apply(&mut config);
+        hold_withdraw_delay(&previous, &mut config, 1_000);
+
+        assert_eq!(config.withdraw_delay_slots, 0);
+        assert_eq!(config.withdraw_delay_at(1_000), 100);
+        assert_eq!(config.withdraw_delay_at(1_099), 100);
+        assert_eq!(config.withdraw_delay_at(1_100), 0);
+
+        // Lowering again mid-hold can't shorten it either
+        let held = config.clone();
+        hold_withdraw_delay(&held, &mut config, 1_050);
+        assert_eq!(config.withdraw_delay_at(1_149), 100);
+    }
+
+    #[test]
+    fn test_raised_withdraw_delay_applies_at_once() {
+        let previous = GameConfig {
+            withdraw_delay_slots: 100,
+            ..initialized_config()
+        };
+        let mut config = previous.clone();
+        UpdateConfigParams {
+            withdraw_delay_slots: Some(500),
+            ..Default::default()
+        }
+        .apply(&mut config);
+        hold_withdraw_delay(&previous, &mut config, 1_000);
+
+        assert_eq!(config.withdraw_delay_floor_until, 0);
+        assert_eq!(config.withdraw_delay_at(1_000), 500);
+    }
+
+    #[test]
+    fn test_empty_update_is_noop() {
+        let mut config = initialized_config();
+        UpdateConfigParams::default().apply(&mut config);
+        assert_eq!(
+            config.try_to_vec().unwrap(),
+            initialized_config().try_to_vec().unwrap()
+        );
+    }
+}
@@ -0,0 +1,76 @@
+use crate::events::VaultHealthEvent;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+/// Read-only solvency report for a house vault
+/// Uses the same dynamic rent-exempt minimum as withdraw_house, so available_to_withdraw is
+/// exactly what withdraw_house_all would move; mirrored in VaultHealthEvent
+pub fn vault_health(ctx: Context<VaultView>) -> Result<VaultHealth> {
+    let house_vault = &ctx.accounts.house_vault;
+    let vault_account = house_vault.to_account_info();
+    let rent_exempt = Rent::get()?.minimum_balance(vault_account.data_len());
+    let health = house_vault.health(vault_account.lamports(), rent_exempt);
+    emit!(VaultHealthEvent {
+        house_vault: house_vault.key(),
+        health: health.clone(),
+    });
+    Ok(health)
+}
+
+/// Accounts for read-only views over a house vault
+#[derive(Accounts)]
+pub struct VaultView<'info> {
+    pub house_vault: Account<'info, HouseVault>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+
+    fn health_of(vault: &HouseVault, balance: u64) -> VaultHealth {
+        install_syscall_stubs();
+        let mut vault_acc = TestAccount::program(vault, balance);
+        let vault_info = vault_acc.info();
+        let mut accounts = VaultView {
+            house_vault: Account::try_from(&vault_info).unwrap(),
+        };
+        vault_health(Context::new(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            Default::default(),
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_reserved_heavy_vault_is_not_solvent() {
+        let vault = HouseVault {
+            total_reserved: 5_000_000_000,
+            ..test_vault()
+        };
+        let health = health_of(&vault, 3_000_000_000);
+        assert_eq!(health.balance, 3_000_000_000);
+        assert_eq!(health.total_reserved, 5_000_000_000);
+        assert!(health.rent_exempt_minimum > 0);
+        assert_eq!(health.available_to_withdraw, 0);
+        assert!(!health.solvent);
+    }
+
+    #[test]
+    fn test_covered_vault_reports_withdrawable_surplus() {
+        let vault = test_vault();
+        let health = health_of(&vault, 5_000_000_000);
+        assert!(health.solvent);
+        assert_eq!(
+            health.available_to_withdraw,
+            5_000_000_000 - 1_000_000_000 - health.rent_exempt_minimum
+        );
+
+        // Exactly covering reservations plus rent is still solvent, with nothing to withdraw
+        let health = health_of(&vault, 1_000_000_000 + health.rent_exempt_minimum);
+        assert!(health.solvent);
+        assert_eq!(health.available_to_withdraw, 0);
+    }
+}
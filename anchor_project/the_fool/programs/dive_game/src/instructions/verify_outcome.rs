@@ -0,0 +1,70 @@
+use crate::events::OutcomeVerifiedEvent;
+use crate::rng;
+use anchor_lang::prelude::*;
+
+/// Read-only check that `claimed_roll` is the roll `dive` got from `server_seed`, once the
+/// keeper has revealed it in RoundSeedRevealedEvent
+/// `session` is part of the seed, so the closed session's key is passed in rather than loaded
+pub fn verify_outcome(
+    _ctx: Context<VerifyOutcome>,
+    server_seed: u64,
+    session: Pubkey,
+    slot_hash: [u8; 32],
+    dive: u16,
+    claimed_roll: u32,
+) -> Result<bool> {
+    let valid = roll_matches(server_seed, &session, &slot_hash, dive, claimed_roll);
+    emit!(OutcomeVerifiedEvent {
+        session,
+        dive_number: dive,
+        claimed_roll,
+        valid,
+    });
+    Ok(valid)
+}
+
+/// Recomputes the roll the same way play_round derives it
+pub(crate) fn roll_matches(
+    server_seed: u64,
+    session: &Pubkey,
+    slot_hash: &[u8; 32],
+    dive: u16,
+    claimed_roll: u32,
+) -> bool {
+    let seed = rng::generate_seed(slot_hash, session, server_seed);
+    rng::random_roll_bps(&seed, dive) == claimed_roll
+}
+
+/// Needs no accounts: everything verified comes in as arguments
+#[derive(Accounts)]
+pub struct VerifyOutcome {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_revealed_roll_verifies() {
+        let session = Pubkey::new_unique();
+        let slot_hash = [42u8; 32];
+        let roll = rng::random_roll_bps(&rng::generate_seed(&slot_hash, &session, 7), 3);
+        assert!(roll_matches(7, &session, &slot_hash, 3, roll));
+    }
+
+    #[test]
+    fn test_tampered_tuple_fails() {
+        let session = Pubkey::new_unique();
+        let slot_hash = [42u8; 32];
+        let roll = rng::random_roll_bps(&rng::generate_seed(&slot_hash, &session, 7), 3);
+        assert!(!roll_matches(
+            7,
+            &session,
+            &slot_hash,
+            3,
+            (roll + 1) % 1_000_000
+        ));
+        assert!(!roll_matches(8, &session, &slot_hash, 3, roll));
+        assert!(!roll_matches(7, &Pubkey::new_unique(), &slot_hash, 3, roll));
+        assert!(!roll_matches(7, &session, &[43u8; 32], 3, roll));
+    }
+}
@@ -1,39 +1,16 @@
 use crate::errors::GameError;
+use crate::events::FlowReason;
 use crate::states::*;
+use crate::util::pay_from_vault;
 use anchor_lang::prelude::*;
 
 /// Allows the house authority to withdraw profits from the house vault
 /// Ensures solvency by only allowing withdrawal of unreserved funds
+/// Funds go to `destination` when one is passed, otherwise to the authority
+/// Unavailable while a withdraw delay is in force; request_withdraw/execute_withdraw apply then
 pub fn withdraw_house(ctx: Context<WithdrawHouse>, amount: u64) -> Result<()> {
-    let house_vault = &mut ctx.accounts.house_vault;
-    let vault_account = house_vault.to_account_info();
-
-    let current_balance = vault_account.lamports();
-
-    // Calculate solvency
-    // available = balance - reserved - rent_exempt_minimum
-    // SECURITY FIX: Use dynamic rent calculation instead of hardcoded value
-    // This ensures rent exemption stays accurate as Solana rent costs change
-    let rent = Rent::get()?;
-    let rent_exempt = rent.minimum_balance(vault_account.data_len());
-    let required = house_vault.total_reserved.saturating_add(rent_exempt);
-
-    let available = current_balance.saturating_sub(required);
-
-    require!(amount <= available, GameError::InsufficientVaultBalance);
-
-    // Transfer lamports from vault to house authority
-    **vault_account.try_borrow_mut_lamports()? -= amount;
-    **ctx.accounts.house_authority.try_borrow_mut_lamports()? += amount;
-
-    msg!("House withdrawal: {} lamports", amount);
-    msg!(
-        "Vault balance after withdrawal: {}",
-        vault_account.lamports()
-    );
-    msg!("Reserved funds: {}", house_vault.total_reserved);
-
-    Ok(())
+    ctx.accounts.ensure_not_timelocked(Clock::get()?.slot)?;
+    ctx.accounts.pay_withdrawal(amount)
 }
 
 #[derive(Accounts)]
@@ -41,11 +18,294 @@ pub struct WithdrawHouse<'info> {
     #[account(mut)]
     pub house_authority: Signer<'info>,
 
+    /// Default difficulty config, read for withdraw_delay_slots
+    #[account(
+        seeds = [GAME_CONFIG_SEED.as_bytes()],
+        bump = config.bump,
+    )]
+    pub config: Account<'info, GameConfig>,
+
+    // No seeds check: the vault PDA stays derived from the original authority after
+    // accept_house_authority, so has_one is what binds the signer to the vault
     #[account(
         mut,
         has_one = house_authority,
-        seeds = [HOUSE_VAULT_SEED.as_bytes(), house_authority.key().as_ref()],
-        bump = house_vault.bump
     )]
     pub house_vault: Account<'info, HouseVault>,
+
+    /// CHECK: receives lamports only, e.g. a cold-storage wallet; the authority still signs
+    #[account(mut)]
+    pub destination: Option<UncheckedAccount<'info>>,
+
+    /// Required when house_vault.cosigner is set, checked in pay_withdrawal
+    pub cosigner: Option<Signer<'info>>,
+}
+
+impl<'info> WithdrawHouse<'info> {
+    /// Account credited by the withdrawal
+    pub fn destination(&self) -> AccountInfo<'info> {
+        match &self.destination {
+            Some(destination) => destination.to_account_info(),
+            None => self.house_authority.to_account_info(),
+        }
+    }
+
+    /// Immediate withdrawals are only allowed without a timelock, including one just lowered
+    pub fn ensure_not_timelocked(&self, slot: u64) -> Result<()> {
+        require!(
+            self.config.withdraw_delay_at(slot) == 0,
+            GameError::WithdrawalTimelocked
+        );
+        Ok(())
+    }
+
+    /// When the vault has a cosigner, it must have signed alongside the authority
+    pub fn ensure_cosigned(&self) -> Result<()> {
+        if let Some(expected) = self.house_vault.cosigner {
+            let signed = self
+                .cosigner
+                .as_ref()
+                .is_some_and(|cosigner| cosigner.key() == expected);
+            require!(signed, GameError::MissingCosigner);
+        }
+        Ok(())
+    }
+
+    /// Moves `amount` from the vault to the destination if it is unreserved
+    pub(crate) fn pay_withdrawal(&self, amount: u64) -> Result<()> {
+        self.ensure_cosigned()?;
+        let house_vault = &self.house_vault;
+        let vault_account = house_vault.to_account_info();
+
+        let current_balance = vault_account.lamports();
+
+        // Calculate solvency
+        // available = balance - reserved - rent_exempt_minimum
+        // SECURITY FIX: Use dynamic rent calculation instead of hardcoded value
+        // This ensures rent exemption stays accurate as Solana rent costs change
+        let rent = Rent::get()?;
+        let rent_exempt = rent.minimum_balance(vault_account.data_len());
+        let available = house_vault.withdrawable(current_balance, rent_exempt);
+
+        require!(amount <= available, GameError::InsufficientVaultBalance);
+
+        // Transfer lamports from vault to the destination
+        emit!(pay_from_vault(
+            &vault_account,
+            &self.destination(),
+            amount,
+            FlowReason::Withdrawal
+        )?);
+
+        msg!("House withdrawal: {} lamports", amount);
+        msg!(
+            "Vault balance after withdrawal: {}",
+            vault_account.lamports()
+        );
+        msg!("Reserved funds: {}", house_vault.total_reserved);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::*;
+    use anchor_lang::Bumps;
+    use std::collections::BTreeSet;
+
+    fn vault_for(authority: &TestAccount) -> TestAccount {
+        TestAccount::program(
+            &HouseVault {
+                house_authority: authority.key,
+                ..test_vault()
+            },
+            5_000_000_000,
+        )
+    }
+
+    /// `config` at the tier 0 config PDA
+    fn config_at_pda(config: GameConfig) -> TestAccount {
+        let (key, bump) = Pubkey::find_program_address(&[GAME_CONFIG_SEED.as_bytes()], &crate::ID);
+        let mut acc = TestAccount::program(&GameConfig { bump, ..config }, 1_000_000);
+        acc.key = key;
+        acc
+    }
+
+    /// Default config at its PDA, with `withdraw_delay_slots` set
+    fn config_with_delay(delay: u64) -> TestAccount {
+        config_at_pda(GameConfig {
+            withdraw_delay_slots: delay,
+            ..test_config(5)
+        })
+    }
+
+    /// Withdraws 1 SOL to the authority under `config`
+    /// Returns the result with the vault's lamports afterwards
+    fn direct_withdraw(config: GameConfig) -> (Result<()>, u64) {
+        install_syscall_stubs();
+        let mut authority_acc = TestAccount::wallet(0);
+        let mut config_acc = config_at_pda(config);
+        let mut vault_acc = vault_for(&authority_acc);
+        let authority_info = authority_acc.info();
+        let config_info = config_acc.info();
+        let vault_info = vault_acc.info();
+        let mut accounts = WithdrawHouse {
+            house_authority: Signer::try_from(&authority_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            destination: None,
+            cosigner: None,
+        };
+        let result = withdraw_house(
+            Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+            1_000_000_000,
+        );
+        (result, vault_info.lamports())
+    }
+
+    #[test]
+    fn test_withdraw_credits_distinct_destination() {
+        install_syscall_stubs();
+        let mut authority_acc = TestAccount::wallet(0);
+        let mut config_acc = config_with_delay(0);
+        let mut vault_acc = vault_for(&authority_acc);
+        let mut destination_acc = TestAccount::wallet(0);
+        let authority_info = authority_acc.info();
+        let config_info = config_acc.info();
+        let vault_info = vault_acc.info();
+        let destination_info = destination_acc.info();
+        let mut accounts = WithdrawHouse {
+            house_authority: Signer::try_from(&authority_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            destination: Some(UncheckedAccount::try_from(&destination_info)),
+            cosigner: None,
+        };
+
+        withdraw_house(
+            Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+            1_000_000_000,
+        )
+        .unwrap();
+
+        assert_eq!(destination_info.lamports(), 1_000_000_000);
+        assert_eq!(authority_info.lamports(), 0);
+        assert_eq!(vault_info.lamports(), 4_000_000_000);
+    }
+
+    #[test]
+    fn test_read_only_destination_is_rejected() {
+        let mut authority_acc = TestAccount::wallet(0);
+        let mut config_acc = config_with_delay(0);
+        let mut vault_acc = vault_for(&authority_acc);
+        let mut destination_acc = TestAccount::wallet(0);
+        let mut destination_info = destination_acc.info();
+        destination_info.is_writable = false;
+        // Absent optional accounts are passed as the program id
+        let mut no_cosigner = TestAccount::wallet(0);
+        no_cosigner.key = crate::ID;
+        let infos = [
+            authority_acc.info(),
+            config_acc.info(),
+            vault_acc.info(),
+            destination_info,
+            no_cosigner.info(),
+        ];
+        let mut accounts: &[AccountInfo] = &infos;
+        let err = WithdrawHouse::try_accounts(
+            &crate::ID,
+            &mut accounts,
+            &[],
+            &mut <WithdrawHouse as Bumps>::Bumps::default(),
+            &mut BTreeSet::new(),
+        )
+        .map(|_| ())
+        .unwrap_err();
+        assert_eq!(err, anchor_lang::error::ErrorCode::ConstraintMut.into());
+    }
+
+    #[test]
+    fn test_direct_withdraw_rejected_under_timelock() {
+        let (result, vault_lamports) = direct_withdraw(GameConfig {
+            withdraw_delay_slots: 100,
+            ..test_config(5)
+        });
+        assert_eq!(result.unwrap_err(), GameError::WithdrawalTimelocked.into());
+        assert_eq!(vault_lamports, 5_000_000_000);
+    }
+
+    #[test]
+    fn test_direct_withdraw_rejected_while_lowered_delay_runs_out() {
+        // Delay just set to 0, with the old 100 slots still running past STUB_SLOT
+        let (result, vault_lamports) = direct_withdraw(GameConfig {
+            withdraw_delay_slots: 0,
+            withdraw_delay_floor_slots: 100,
+            withdraw_delay_floor_until: STUB_SLOT + 1,
+            ..test_config(5)
+        });
+        assert_eq!(result.unwrap_err(), GameError::WithdrawalTimelocked.into());
+        assert_eq!(vault_lamports, 5_000_000_000);
+    }
+
+    /// Withdraws 1 SOL from a vault with a cosigner, signed by `cosigner_acc` if any
+    /// Returns the result with the authority's lamports afterwards
+    fn cosigned_withdraw(
+        cosigner_acc: Option<&mut TestAccount>,
+        vault_cosigner: Pubkey,
+    ) -> (Result<()>, u64) {
+        install_syscall_stubs();
+        let mut authority_acc = TestAccount::wallet(0);
+        let mut config_acc = config_with_delay(0);
+        let mut vault_acc = TestAccount::program(
+            &HouseVault {
+                house_authority: authority_acc.key,
+                cosigner: Some(vault_cosigner),
+                ..test_vault()
+            },
+            5_000_000_000,
+        );
+        let authority_info = authority_acc.info();
+        let config_info = config_acc.info();
+        let vault_info = vault_acc.info();
+        let cosigner_info = cosigner_acc.map(|acc| acc.info());
+        let mut accounts = WithdrawHouse {
+            house_authority: Signer::try_from(&authority_info).unwrap(),
+            config: Account::try_from(&config_info).unwrap(),
+            house_vault: Account::try_from(&vault_info).unwrap(),
+            destination: None,
+            cosigner: cosigner_info
+                .as_ref()
+                .map(|info| Signer::try_from(info).unwrap()),
+        };
+        let result = withdraw_house(
+            Context::new(&crate::ID, &mut accounts, &[], Default::default()),
+            1_000_000_000,
+        );
+        (result, authority_info.lamports())
+    }
+
+    #[test]
+    fn test_withdraw_without_required_cosigner_is_rejected() {
+        let (result, authority_lamports) = cosigned_withdraw(None, Pubkey::new_unique());
+        assert_eq!(result.unwrap_err(), GameError::MissingCosigner.into());
+        assert_eq!(authority_lamports, 0);
+    }
+
+    #[test]
+    fn test_withdraw_with_wrong_cosigner_is_rejected() {
+        let mut stranger_acc = TestAccount::wallet(0);
+        let (result, _) = cosigned_withdraw(Some(&mut stranger_acc), Pubkey::new_unique());
+        assert_eq!(result.unwrap_err(), GameError::MissingCosigner.into());
+    }
+
+    #[test]
+    fn test_withdraw_with_cosigner_pays() {
+        let mut cosigner_acc = TestAccount::wallet(0);
+        let cosigner = cosigner_acc.key;
+        let (result, authority_lamports) = cosigned_withdraw(Some(&mut cosigner_acc), cosigner);
+        result.unwrap();
+        assert_eq!(authority_lamports, 1_000_000_000);
+    }
 }
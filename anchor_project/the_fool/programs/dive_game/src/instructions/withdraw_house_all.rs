@@ -0,0 +1,54 @@
+use crate::events::FlowReason;
+use crate::util::pay_from_vault;
+use anchor_lang::prelude::*;
+
+use super::withdraw_house::WithdrawHouse;
+
+/// Withdraws every unreserved lamport above the rent-exempt minimum to the house authority
+/// Uses the same solvency rule as withdraw_house; a no-op when nothing is available
+pub fn withdraw_house_all(ctx: Context<WithdrawHouse>) -> Result<()> {
+    ctx.accounts.ensure_not_timelocked(Clock::get()?.slot)?;
+    let house_vault = &ctx.accounts.house_vault;
+    let vault_account = house_vault.to_account_info();
+
+    let rent_exempt = Rent::get()?.minimum_balance(vault_account.data_len());
+    let amount = house_vault.withdrawable(vault_account.lamports(), rent_exempt);
+
+    if amount == 0 {
+        msg!("House withdrawal: nothing available");
+        return Ok(());
+    }
+
+    emit!(pay_from_vault(
+        &vault_account,
+        &ctx.accounts.destination(),
+        amount,
+        FlowReason::Withdrawal
+    )?);
+
+    msg!("House withdrawal: {} lamports", amount);
+    msg!("Reserved funds: {}", house_vault.total_reserved);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::*;
+    use crate::test_utils::*;
+
+    #[test]
+    fn test_withdraw_all_never_touches_reserved_or_rent() {
+        let vault = test_vault();
+        let rent_exempt = Rent::default().minimum_balance(8 + HouseVault::INIT_SPACE);
+        let balance = 5_000_000_000;
+
+        let amount = vault.withdrawable(balance, rent_exempt);
+        let remaining = balance - amount;
+
+        assert_eq!(remaining, vault.total_reserved + rent_exempt);
+        // A second call finds nothing left
+        assert_eq!(vault.withdrawable(remaining, rent_exempt), 0);
+    }
+}
@@ -0,0 +1,330 @@
+//! Earlier account layouts and the steps that bring them up to the current one
+//! Every layout change bumps the account's *_VERSION and adds its previous layout here, which
+//! migrate_config, migrate_vault and migrate_session then decode from the raw account
+
+use crate::errors::GameError;
+use crate::instructions::init_config::{new_config, GameConfigParams};
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_lang::{system_program, Discriminator};
+
+/// Offset of `version` in current layouts: the first byte after the discriminator
+pub const VERSION_OFFSET: usize = 8;
+
+/// Baseline GameConfig, from before accounts carried a version
+/// A single fixed_bet stood in for the bet range
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct GameConfigV0 {
+    pub admin: Pubkey,
+    pub base_survival_ppm: u32,
+    pub decay_per_dive_ppm: u32,
+    pub min_survival_ppm: u32,
+    pub treasure_multiplier_num: u16,
+    pub treasure_multiplier_den: u16,
+    pub max_payout_multiplier: u16,
+    pub max_dives: u16,
+    pub fixed_bet: u64,
+    pub bump: u8,
+}
+
+impl GameConfigV0 {
+    /// Current layout with every later setting at its init_config default, which is how the
+    /// baseline program behaved, and the fixed bet as the only allowed bet. The baseline had a
+    /// single config, the tier 0 address.
+    pub fn upgrade(self) -> GameConfig {
+        new_config(
+            self.admin,
+            0,
+            self.bump,
+            GameConfigParams {
+                base_survival_ppm: Some(self.base_survival_ppm),
+                decay_per_dive_ppm: Some(self.decay_per_dive_ppm),
+                min_survival_ppm: Some(self.min_survival_ppm),
+                treasure_multiplier_num: Some(self.treasure_multiplier_num),
+                treasure_multiplier_den: Some(self.treasure_multiplier_den),
+                max_payout_multiplier: Some(self.max_payout_multiplier),
+                max_dives: Some(self.max_dives),
+                min_bet: Some(self.fixed_bet),
+                max_bet: Some(self.fixed_bet),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+/// Version 2 of GameConfig, before the withdraw delay floor
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct GameConfigV2 {
+    pub version: u8,
+    pub admin: Pubkey,
+    pub base_survival_ppm: u32,
+    pub decay_per_dive_ppm: u32,
+    pub min_survival_ppm: u32,
+    pub treasure_multiplier_num: u16,
+    pub treasure_multiplier_den: u16,
+    pub max_payout_multiplier: u16,
+    pub max_dives: u16,
+    pub min_bet: u64,
+    pub max_bet: u64,
+    pub vrf_enabled: bool,
+    pub house_edge_bps: u32,
+    pub curve_mode: CurveMode,
+    pub linear_step_bps: u32,
+    pub referral_fee_bps: u32,
+    pub max_exposure_bps: u32,
+    pub timeout_slots: u64,
+    pub paused: bool,
+    pub require_full_coverage: bool,
+    pub pending_admin: Option<Pubkey>,
+    pub min_dives_to_cash_out: u16,
+    pub rake_bps: u32,
+    pub final_gamble_bps: u32,
+    pub jackpot_bps: u32,
+    pub difficulty: u8,
+    pub max_payout_lamports: u64,
+    pub allowed_bets: [u64; 8],
+    pub use_bet_whitelist: bool,
+    pub crank_reward_lamports: u64,
+    pub max_active_sessions_per_user: u16,
+    pub decay_mode: DecayMode,
+    pub insurance_payout_bps: u32,
+    pub verbose_logging: bool,
+    pub first_dive_guaranteed: bool,
+    pub allow_breakeven_exit: bool,
+    pub absolute_max_dives: u16,
+    pub min_slots_before_play: u64,
+    pub loss_rebate_bps: u32,
+    pub bet_step: u64,
+    pub withdraw_delay_slots: u64,
+    pub max_bet_vault_bps: u32,
+    pub milestone_dive: u16,
+    pub milestone_bonus_bps: u32,
+    pub usd_denominated: bool,
+    pub price_feed: Pubkey,
+    pub max_price_age_secs: u64,
+    pub max_total_sessions: u32,
+    pub rounding_mode: RoundingMode,
+    pub bonus_wagering_multiple: u16,
+    pub max_boost_bps: u32,
+    pub expiry_mode: ExpiryMode,
+    pub big_payout_lamports: u64,
+    pub max_consecutive_big_payouts: u16,
+    pub reservation_mode: ReservationMode,
+    pub active_sessions: u64,
+    pub bump: u8,
+}
+
+impl GameConfigV2 {
+    /// Current layout with no lowered withdraw delay still in force
+    pub fn upgrade(self) -> GameConfig {
+        GameConfig {
+            version: CONFIG_VERSION,
+            admin: self.admin,
+            base_survival_ppm: self.base_survival_ppm,
+            decay_per_dive_ppm: self.decay_per_dive_ppm,
+            min_survival_ppm: self.min_survival_ppm,
+            treasure_multiplier_num: self.treasure_multiplier_num,
+            treasure_multiplier_den: self.treasure_multiplier_den,
+            max_payout_multiplier: self.max_payout_multiplier,
+            max_dives: self.max_dives,
+            min_bet: self.min_bet,
+            max_bet: self.max_bet,
+            vrf_enabled: self.vrf_enabled,
+            house_edge_bps: self.house_edge_bps,
+            curve_mode: self.curve_mode,
+            linear_step_bps: self.linear_step_bps,
+            referral_fee_bps: self.referral_fee_bps,
+            max_exposure_bps: self.max_exposure_bps,
+            timeout_slots: self.timeout_slots,
+            paused: self.paused,
+            require_full_coverage: self.require_full_coverage,
+            pending_admin: self.pending_admin,
+            min_dives_to_cash_out: self.min_dives_to_cash_out,
+            rake_bps: self.rake_bps,
+            final_gamble_bps: self.final_gamble_bps,
+            jackpot_bps: self.jackpot_bps,
+            difficulty: self.difficulty,
+            max_payout_lamports: self.max_payout_lamports,
+            allowed_bets: self.allowed_bets,
+            use_bet_whitelist: self.use_bet_whitelist,
+            crank_reward_lamports: self.crank_reward_lamports,
+            max_active_sessions_per_user: self.max_active_sessions_per_user,
+            decay_mode: self.decay_mode,
+            insurance_payout_bps: self.insurance_payout_bps,
+            verbose_logging: self.verbose_logging,
+            first_dive_guaranteed: self.first_dive_guaranteed,
+            allow_breakeven_exit: self.allow_breakeven_exit,
+            absolute_max_dives: self.absolute_max_dives,
+            min_slots_before_play: self.min_slots_before_play,
+            loss_rebate_bps: self.loss_rebate_bps,
+            bet_step: self.bet_step,
+            withdraw_delay_slots: self.withdraw_delay_slots,
+            max_bet_vault_bps: self.max_bet_vault_bps,
+            milestone_dive: self.milestone_dive,
+            milestone_bonus_bps: self.milestone_bonus_bps,
+            usd_denominated: self.usd_denominated,
+            price_feed: self.price_feed,
+            max_price_age_secs: self.max_price_age_secs,
+            max_total_sessions: self.max_total_sessions,
+            rounding_mode: self.rounding_mode,
+            bonus_wagering_multiple: self.bonus_wagering_multiple,
+            max_boost_bps: self.max_boost_bps,
+            expiry_mode: self.expiry_mode,
+            big_payout_lamports: self.big_payout_lamports,
+            max_consecutive_big_payouts: self.max_consecutive_big_payouts,
+            reservation_mode: self.reservation_mode,
+            active_sessions: self.active_sessions,
+            withdraw_delay_floor_slots: 0,
+            withdraw_delay_floor_until: 0,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Baseline HouseVault, from before accounts carried a version
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct HouseVaultV0 {
+    pub house_authority: Pubkey,
+    pub game_keeper: Pubkey,
+    pub locked: bool,
+    pub total_reserved: u64,
+    pub bump: u8,
+}
+
+impl HouseVaultV0 {
+    /// Current layout with empty pools and no pending authority, withdrawal, cosigner or crank
+    /// Open sessions are counted back in as migrate_session upgrades them
+    pub fn upgrade(self) -> HouseVault {
+        HouseVault {
+            version: VAULT_VERSION,
+            house_authority: self.house_authority,
+            game_keeper: self.game_keeper,
+            locked: self.locked,
+            total_reserved: self.total_reserved,
+            pending_authority: None,
+            strict_accounting: false,
+            jackpot_pool: 0,
+            sequence: 0,
+            pending_withdraw_amount: 0,
+            withdraw_unlock_slot: 0,
+            cosigner: None,
+            active_sessions: 0,
+            bonus_pool: 0,
+            authorized_crank: None,
+            consecutive_big_payouts: 0,
+            bump: self.bump,
+        }
+    }
+}
+
+/// Baseline GameSession, from before accounts carried a version
+#[derive(AnchorSerialize, AnchorDeserialize, InitSpace)]
+pub struct GameSessionV0 {
+    pub user: Pubkey,
+    pub house_vault: Pubkey,
+    pub status: SessionStatus,
+    pub bet_amount: u64,
+    pub current_treasure: u64,
+    pub max_payout: u64,
+    pub dive_number: u16,
+    pub bump: u8,
+    pub last_active_slot: u64,
+}
+
+impl GameSessionV0 {
+    /// Current layout for a session of the tier 0 config, played by the vault's keeper
+    /// The baseline reserved the whole max_payout, timed out from the last activity and had no
+    /// commitments or partial withdrawals
+    pub fn upgrade(self, keeper: Pubkey, timeout_slots: u64) -> GameSession {
+        let mut session = GameSession {
+            user: self.user,
+            house_vault: self.house_vault,
+            status: self.status,
+            bet_amount: self.bet_amount,
+            current_treasure: self.current_treasure,
+            max_payout: self.max_payout,
+            dive_number: self.dive_number,
+            bump: self.bump,
+            last_active_slot: self.last_active_slot,
+            keeper,
+            reserved_payout: self.max_payout,
+            ..GameSession::blank()
+        };
+        session.record_activity(self.last_active_slot, timeout_slots);
+        session
+    }
+}
+
+/// Version of a versioned account's raw data
+/// Baseline accounts have no version byte, so they are told apart by their allocation size and
+/// read as version 0
+pub fn stored_version(data: &[u8], v0_len: usize) -> Result<u8> {
+    if data.len() == v0_len {
+        return Ok(0);
+    }
+    data.get(VERSION_OFFSET)
+        .copied()
+        .ok_or_else(|| ErrorCode::AccountDidNotDeserialize.into())
+}
+
+/// Decodes `info` as the current layout of T, converting older versions with `upgrade`, which
+/// gets the stored version and the data after the discriminator
+/// Returns the version it was stored at; nothing is written until store_upgraded
+pub fn load_versioned<T>(
+    info: &AccountInfo,
+    v0_len: usize,
+    current: u8,
+    upgrade: impl FnOnce(u8, &mut &[u8]) -> Result<T>,
+) -> Result<(u8, T)>
+where
+    T: AccountDeserialize + Discriminator,
+{
+    require_keys_eq!(
+        *info.owner,
+        crate::ID,
+        ErrorCode::AccountOwnedByWrongProgram
+    );
+    let data = info.try_borrow_data()?;
+    require!(
+        data.starts_with(T::DISCRIMINATOR),
+        ErrorCode::AccountDiscriminatorMismatch
+    );
+    let version = stored_version(&data, v0_len)?;
+    require!(version <= current, GameError::UnsupportedAccountVersion);
+    let account = if version == current {
+        T::try_deserialize(&mut &data[..])?
+    } else {
+        upgrade(version, &mut &data[T::DISCRIMINATOR.len()..])?
+    };
+    Ok((version, account))
+}
+
+/// Resizes `info` to the current layout of T, tops its rent up from `payer`, and writes `account`
+pub fn store_upgraded<'info, T>(
+    info: &AccountInfo<'info>,
+    account: &T,
+    payer: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+) -> Result<()>
+where
+    T: AccountSerialize + Space,
+{
+    let len = 8 + T::INIT_SPACE;
+    let shortfall = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(info.lamports());
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: info.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    info.resize(len)?;
+    account.try_serialize(&mut &mut info.try_borrow_mut_data()?[..])
+}
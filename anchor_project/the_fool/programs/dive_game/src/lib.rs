@@ -3,32 +3,132 @@ pub mod errors;
 pub mod events;
 pub mod game_math;
 pub mod instructions;
+pub mod legacy;
+pub mod price_feed;
 pub mod rng;
 pub mod states;
+#[cfg(test)]
+pub(crate) mod test_utils;
+pub mod util;
+pub mod vrf;
 use instructions::init_config::GameConfigParams;
 use instructions::update_config::UpdateConfigParams;
 use instructions::*;
+use states::{SessionSnapshot, SimResult, VaultHealth};
 declare_id!("2hMffkY1dCRo548Kj152LNyPomQAiFhw7dVAsgNbZ7F2");
 #[program]
 pub mod dive_game {
     use super::*;
-    pub fn init_config(ctx: Context<InitializeConfig>, params: GameConfigParams) -> Result<()> {
-        instructions::init_config(ctx, params)
+    pub fn init_config(
+        ctx: Context<InitializeConfig>,
+        difficulty: u8,
+        params: GameConfigParams,
+    ) -> Result<()> {
+        instructions::init_config(ctx, difficulty, params)
     }
-    pub fn init_house_vault(ctx: Context<InitializeHouseVault>, locked: bool) -> Result<()> {
-        instructions::init_house_vault(ctx, locked)
+    pub fn init_house_vault(
+        ctx: Context<InitializeHouseVault>,
+        locked: bool,
+        game_keeper: Pubkey,
+        strict_accounting: bool,
+        seed_amount: u64,
+    ) -> Result<()> {
+        instructions::init_house_vault(ctx, locked, game_keeper, strict_accounting, seed_amount)
     }
-    pub fn start_session(ctx: Context<StartSession>, session_index: u64) -> Result<()> {
-        instructions::start_session(ctx, session_index)
+    pub fn set_game_keeper(ctx: Context<SetGameKeeper>, game_keeper: Pubkey) -> Result<()> {
+        instructions::set_game_keeper(ctx, game_keeper)
     }
-    pub fn play_round(ctx: Context<PlayRound>) -> Result<()> {
-        instructions::play_round(ctx)
+    pub fn set_cosigner(ctx: Context<SetCosigner>, cosigner: Option<Pubkey>) -> Result<()> {
+        instructions::set_cosigner(ctx, cosigner)
+    }
+    pub fn set_authorized_crank(
+        ctx: Context<SetAuthorizedCrank>,
+        crank: Option<Pubkey>,
+    ) -> Result<()> {
+        instructions::set_authorized_crank(ctx, crank)
+    }
+    pub fn rebind_session_keeper(ctx: Context<RebindSessionKeeper>) -> Result<()> {
+        instructions::rebind_session_keeper(ctx)
+    }
+    pub fn propose_house_authority(
+        ctx: Context<ProposeHouseAuthority>,
+        new_authority: Pubkey,
+    ) -> Result<()> {
+        instructions::propose_house_authority(ctx, new_authority)
+    }
+    pub fn accept_house_authority(ctx: Context<AcceptHouseAuthority>) -> Result<()> {
+        instructions::accept_house_authority(ctx)
+    }
+    pub fn start_session(
+        ctx: Context<StartSession>,
+        session_index: u64,
+        bet_amount: u64,
+        referrer: Option<Pubkey>,
+        difficulty: u8,
+        max_dives_override: Option<u16>,
+        client_tag: Option<[u8; 16]>,
+    ) -> Result<()> {
+        instructions::start_session(
+            ctx,
+            session_index,
+            bet_amount,
+            referrer,
+            difficulty,
+            max_dives_override,
+            client_tag,
+        )
+    }
+    pub fn batch_start_sessions<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BatchStartSessions<'info>>,
+        entries: Vec<BatchEntry>,
+    ) -> Result<()> {
+        instructions::batch_start_sessions(ctx, entries)
+    }
+    pub fn commit_round(ctx: Context<CommitRound>, commitment: [u8; 32]) -> Result<()> {
+        instructions::commit_round(ctx, commitment)
+    }
+    pub fn play_round(ctx: Context<PlayRound>, server_seed: u64, expected_dive: u16) -> Result<()> {
+        instructions::play_round(ctx, server_seed, expected_dive)
+    }
+    pub fn auto_play(ctx: Context<PlayRound>, server_seed: u64, target_dive: u16) -> Result<()> {
+        instructions::auto_play(ctx, server_seed, target_dive)
+    }
+    pub fn deep_dive<'info>(
+        ctx: Context<'_, '_, 'info, 'info, PlayRound<'info>>,
+        server_seed: u64,
+    ) -> Result<()> {
+        instructions::deep_dive(ctx, server_seed)
+    }
+    pub fn gamble_treasure(ctx: Context<PlayRound>, server_seed: u64) -> Result<()> {
+        instructions::gamble_treasure(ctx, server_seed)
+    }
+    pub fn request_vrf_round(ctx: Context<RequestVrfRound>) -> Result<()> {
+        instructions::request_vrf_round(ctx)
+    }
+    pub fn play_round_vrf(ctx: Context<PlayRoundVrf>) -> Result<()> {
+        instructions::play_round_vrf(ctx)
+    }
+    pub fn increase_bet(ctx: Context<IncreaseBet>, additional: u64) -> Result<()> {
+        instructions::increase_bet(ctx, additional)
+    }
+
+    pub fn buy_insurance(ctx: Context<BuyInsurance>, cost: u64) -> Result<()> {
+        instructions::buy_insurance(ctx, cost)
+    }
+    pub fn apply_boost(ctx: Context<ApplyBoost>, boost_bps: u32, cost: u64) -> Result<()> {
+        instructions::apply_boost(ctx, boost_bps, cost)
     }
     pub fn lose_session(ctx: Context<LoseSession>) -> Result<()> {
         instructions::lose_session(ctx)
     }
-    pub fn cash_out(ctx: Context<CashOut>) -> Result<()> {
-        instructions::cash_out(ctx)
+    pub fn cash_out<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CashOut<'info>>,
+        min_payout: u64,
+    ) -> Result<u64> {
+        instructions::cash_out(ctx, min_payout)
+    }
+    pub fn partial_cash_out(ctx: Context<PartialCashOut>, amount: u64) -> Result<u64> {
+        instructions::partial_cash_out(ctx, amount)
     }
     pub fn toggle_house_lock(ctx: Context<ToggleHouseLock>) -> Result<()> {
         instructions::toggle_house_lock::toggle_house_lock(ctx)
@@ -36,13 +136,103 @@ pub mod dive_game {
     pub fn clean_expired_session(ctx: Context<CleanExpired>) -> Result<()> {
         instructions::clean_expired_session::clean_expired_session(ctx)
     }
+    pub fn clean_expired_sessions_batch<'info>(
+        ctx: Context<'_, '_, 'info, 'info, CleanExpiredBatch<'info>>,
+    ) -> Result<()> {
+        instructions::clean_expired_sessions_batch(ctx)
+    }
+    pub fn admin_close_session(ctx: Context<AdminCloseSession>) -> Result<()> {
+        instructions::admin_close_session(ctx)
+    }
     pub fn withdraw_house(ctx: Context<WithdrawHouse>, amount: u64) -> Result<()> {
         instructions::withdraw_house(ctx, amount)
     }
+    pub fn withdraw_house_all(ctx: Context<WithdrawHouse>) -> Result<()> {
+        instructions::withdraw_house_all(ctx)
+    }
+    pub fn sweep_dust(ctx: Context<SweepDust>) -> Result<()> {
+        instructions::sweep_dust(ctx)
+    }
+    pub fn request_withdraw(ctx: Context<WithdrawHouse>, amount: u64) -> Result<()> {
+        instructions::request_withdraw(ctx, amount)
+    }
+    pub fn execute_withdraw(ctx: Context<WithdrawHouse>) -> Result<()> {
+        instructions::execute_withdraw(ctx)
+    }
+    pub fn cancel_withdraw(ctx: Context<WithdrawHouse>) -> Result<()> {
+        instructions::cancel_withdraw(ctx)
+    }
+    pub fn deposit_house(ctx: Context<DepositHouse>, amount: u64) -> Result<()> {
+        instructions::deposit_house(ctx, amount)
+    }
+    pub fn grant_bonus(ctx: Context<GrantBonus>, amount: u64) -> Result<()> {
+        instructions::grant_bonus(ctx, amount)
+    }
+    pub fn claim_bonus(ctx: Context<ClaimBonus>) -> Result<()> {
+        instructions::claim_bonus(ctx)
+    }
     pub fn reset_vault_reserved(ctx: Context<ResetVaultReserved>) -> Result<()> {
         instructions::reset_vault_reserved(ctx)
     }
+    pub fn reconcile_reserved<'info>(
+        ctx: Context<'_, '_, 'info, 'info, ReconcileReserved<'info>>,
+        session_count: u32,
+    ) -> Result<()> {
+        instructions::reconcile_reserved(ctx, session_count)
+    }
     pub fn update_config(ctx: Context<UpdateConfig>, params: UpdateConfigParams) -> Result<()> {
         instructions::update_config(ctx, params)
     }
+    pub fn set_paused(ctx: Context<SetPaused>, paused: bool) -> Result<()> {
+        instructions::set_paused(ctx, paused)
+    }
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        instructions::propose_admin(ctx, new_admin)
+    }
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        instructions::accept_admin(ctx)
+    }
+    pub fn migrate_config(ctx: Context<MigrateConfig>) -> Result<()> {
+        instructions::migrate_config(ctx)
+    }
+    pub fn migrate_session(ctx: Context<MigrateSession>) -> Result<()> {
+        instructions::migrate_session(ctx)
+    }
+    pub fn migrate_vault(ctx: Context<MigrateVault>) -> Result<()> {
+        instructions::migrate_vault(ctx)
+    }
+    pub fn get_max_dives(ctx: Context<ConfigView>, bet_amount: u64) -> Result<u16> {
+        instructions::get_max_dives(ctx, bet_amount)
+    }
+    pub fn get_survival_probability(ctx: Context<ConfigView>, dive_number: u16) -> Result<u32> {
+        instructions::get_survival_probability(ctx, dive_number)
+    }
+    pub fn get_next_treasure(ctx: Context<SessionView>) -> Result<u64> {
+        instructions::get_next_treasure(ctx)
+    }
+    pub fn get_session_view(ctx: Context<SessionView>) -> Result<SessionSnapshot> {
+        instructions::get_session_view(ctx)
+    }
+    pub fn simulate_session(
+        ctx: Context<ConfigView>,
+        bet_amount: u64,
+        server_seed: u64,
+        session: Pubkey,
+        slot_hash: [u8; 32],
+    ) -> Result<SimResult> {
+        instructions::simulate_session(ctx, bet_amount, server_seed, session, slot_hash)
+    }
+    pub fn verify_outcome(
+        ctx: Context<VerifyOutcome>,
+        server_seed: u64,
+        session: Pubkey,
+        slot_hash: [u8; 32],
+        dive: u16,
+        claimed_roll: u32,
+    ) -> Result<bool> {
+        instructions::verify_outcome(ctx, server_seed, session, slot_hash, dive, claimed_roll)
+    }
+    pub fn vault_health(ctx: Context<VaultView>) -> Result<VaultHealth> {
+        instructions::vault_health(ctx)
+    }
 }
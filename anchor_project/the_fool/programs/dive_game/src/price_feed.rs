@@ -0,0 +1,166 @@
+use crate::errors::GameError;
+use anchor_lang::prelude::*;
+use solana_program::hash::hash;
+
+/// Pyth Solana receiver, owner of PriceUpdateV2 accounts
+pub const PYTH_RECEIVER_ID: Pubkey = pubkey!("rec5EKMGg6MxZYaMdyBfgwp4d5rB9T1VQH5pJv5LtFJ");
+
+/// Price fields of a Pyth PriceUpdateV2 account; the USD value is price * 10^exponent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeedPrice {
+    pub price: i64,
+    pub exponent: i32,
+    pub publish_time: i64,
+}
+
+impl FeedPrice {
+    /// Price of one SOL in micro-USD, the unit locked on USD-denominated sessions
+    pub fn micro_usd(&self) -> Result<u64> {
+        require!(self.price > 0, GameError::InvalidPriceFeed);
+        let shift = self.exponent + 6;
+        let price = self.price as u128;
+        let scaled = if shift >= 0 {
+            10u128
+                .checked_pow(shift as u32)
+                .and_then(|scale| price.checked_mul(scale))
+        } else {
+            10u128
+                .checked_pow(shift.unsigned_abs())
+                .map(|scale| price / scale)
+        };
+        let scaled = scaled.ok_or(GameError::InvalidPriceFeed)?;
+        require!(
+            scaled > 0 && scaled <= u64::MAX as u128,
+            GameError::InvalidPriceFeed
+        );
+        Ok(scaled as u64)
+    }
+}
+
+/// Decodes a PriceUpdateV2 account, accepting only fully verified updates
+/// Layout: discriminator, write_authority, verification_level (Partial carries a u8), then
+/// the price message: feed_id, price, conf, exponent, publish_time, ...
+pub fn parse_price_update(data: &[u8]) -> Result<FeedPrice> {
+    const VERIFICATION_START: usize = 8 + 32;
+    const FULL: u8 = 1;
+    require!(
+        data.len() >= VERIFICATION_START + 1 + 32 + 8 + 8 + 4 + 8,
+        GameError::InvalidPriceFeed
+    );
+    require!(
+        data[..8] == hash(b"account:PriceUpdateV2").to_bytes()[..8],
+        GameError::InvalidPriceFeed
+    );
+    require!(
+        data[VERIFICATION_START] == FULL,
+        GameError::InvalidPriceFeed
+    );
+    let price_start = VERIFICATION_START + 1 + 32;
+    let read = |offset: usize, len: usize| &data[price_start + offset..price_start + offset + len];
+    Ok(FeedPrice {
+        price: i64::from_le_bytes(read(0, 8).try_into().unwrap()),
+        exponent: i32::from_le_bytes(read(16, 4).try_into().unwrap()),
+        publish_time: i64::from_le_bytes(read(20, 8).try_into().unwrap()),
+    })
+}
+
+/// Reads the SOL/USD price from `feed`, which must be the configured Pyth account and no
+/// older than `max_age_secs` at `now`
+pub fn read_sol_price(
+    feed: &AccountInfo,
+    expected_feed: &Pubkey,
+    max_age_secs: u64,
+    now: i64,
+) -> Result<FeedPrice> {
+    require_keys_eq!(feed.key(), *expected_feed, GameError::InvalidPriceFeed);
+    require_keys_eq!(*feed.owner, PYTH_RECEIVER_ID, GameError::InvalidPriceFeed);
+    let price = parse_price_update(&feed.try_borrow_data()?)?;
+    let age = now.saturating_sub(price.publish_time);
+    require!(
+        age <= max_age_secs.min(i64::MAX as u64) as i64,
+        GameError::StalePriceFeed
+    );
+    Ok(price)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+
+    /// A Full-verified PriceUpdateV2 account body
+    fn price_update(price: i64, exponent: i32, publish_time: i64) -> Vec<u8> {
+        let mut data = hash(b"account:PriceUpdateV2").to_bytes()[..8].to_vec();
+        data.extend_from_slice(&[0u8; 32]); // write_authority
+        data.push(1); // VerificationLevel::Full
+        data.extend_from_slice(&[7u8; 32]); // feed_id
+        data.extend_from_slice(&price.to_le_bytes());
+        data.extend_from_slice(&1_000u64.to_le_bytes()); // conf
+        data.extend_from_slice(&exponent.to_le_bytes());
+        data.extend_from_slice(&publish_time.to_le_bytes());
+        data.extend_from_slice(&[0u8; 8 + 8 + 8 + 8]); // prev_publish_time, ema, slot
+        data
+    }
+
+    fn feed_account(data: Vec<u8>) -> TestAccount {
+        TestAccount {
+            owner: PYTH_RECEIVER_ID,
+            data,
+            ..TestAccount::wallet(1_000_000)
+        }
+    }
+
+    #[test]
+    fn test_parse_price_update() {
+        let price = parse_price_update(&price_update(15_012_345_678, -8, 1_700)).unwrap();
+        assert_eq!(
+            price,
+            FeedPrice {
+                price: 15_012_345_678,
+                exponent: -8,
+                publish_time: 1_700,
+            }
+        );
+        // $150.12345678 per SOL
+        assert_eq!(price.micro_usd().unwrap(), 150_123_456);
+    }
+
+    #[test]
+    fn test_partially_verified_update_is_rejected() {
+        let mut data = price_update(15_000_000_000, -8, 1_700);
+        data[40] = 0;
+        let err = parse_price_update(&data).unwrap_err();
+        assert_eq!(err, GameError::InvalidPriceFeed.into());
+    }
+
+    #[test]
+    fn test_non_positive_price_is_rejected() {
+        let price = parse_price_update(&price_update(0, -8, 1_700)).unwrap();
+        assert!(price.micro_usd().is_err());
+    }
+
+    #[test]
+    fn test_stale_price_is_rejected() {
+        let mut acc = feed_account(price_update(15_000_000_000, -8, 1_000));
+        let key = acc.key;
+        let info = acc.info();
+        assert!(read_sol_price(&info, &key, 60, 1_060).is_ok());
+        let err = read_sol_price(&info, &key, 60, 1_061).unwrap_err();
+        assert_eq!(err, GameError::StalePriceFeed.into());
+    }
+
+    #[test]
+    fn test_unexpected_feed_account_is_rejected() {
+        let mut acc = feed_account(price_update(15_000_000_000, -8, 1_000));
+        let info = acc.info();
+        assert!(read_sol_price(&info, &Pubkey::new_unique(), 60, 1_000).is_err());
+
+        let mut spoofed = TestAccount {
+            owner: Pubkey::new_unique(),
+            ..feed_account(price_update(15_000_000_000, -8, 1_000))
+        };
+        let key = spoofed.key;
+        let info = spoofed.info();
+        assert!(read_sol_price(&info, &key, 60, 1_000).is_err());
+    }
+}
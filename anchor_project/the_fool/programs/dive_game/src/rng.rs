@@ -1,12 +1,48 @@
 use anchor_lang::prelude::*;
 use solana_program::hash::hashv;
-pub fn generate_seed(recent_slot_hash: &[u8; 32], session_pda: &Pubkey) -> [u8; 32] {
-    let mut seed_material = Vec::with_capacity(64);
+/// Extracts the most recent slot hash from raw SlotHashes sysvar data
+/// Layout: u64 entry count, followed by (u64 slot, [u8; 32] hash) entries, newest first
+/// The entry must be for a slot before `current_slot`, i.e. one whose block is settled
+pub fn latest_slot_hash(sysvar_data: &[u8], current_slot: u64) -> Result<[u8; 32]> {
+    const LEN_PREFIX: usize = 8;
+    const SLOT_LEN: usize = 8;
+    const HASH_START: usize = LEN_PREFIX + SLOT_LEN;
+    require!(
+        sysvar_data.len() >= HASH_START + 32,
+        crate::errors::GameError::InvalidSlotHash
+    );
+    let mut count_bytes = [0u8; 8];
+    count_bytes.copy_from_slice(&sysvar_data[..LEN_PREFIX]);
+    require!(
+        u64::from_le_bytes(count_bytes) > 0,
+        crate::errors::GameError::InvalidSlotHash
+    );
+    let mut slot_bytes = [0u8; 8];
+    slot_bytes.copy_from_slice(&sysvar_data[LEN_PREFIX..HASH_START]);
+    require!(
+        u64::from_le_bytes(slot_bytes) < current_slot,
+        crate::errors::GameError::InvalidSlotHash
+    );
+    let mut hash = [0u8; 32];
+    hash.copy_from_slice(&sysvar_data[HASH_START..HASH_START + 32]);
+    Ok(hash)
+}
+pub fn generate_seed(
+    recent_slot_hash: &[u8; 32],
+    session_pda: &Pubkey,
+    server_seed: u64,
+) -> [u8; 32] {
+    let mut seed_material = Vec::with_capacity(72);
     seed_material.extend_from_slice(recent_slot_hash);
     seed_material.extend_from_slice(session_pda.as_ref());
+    seed_material.extend_from_slice(&server_seed.to_le_bytes());
     let hash = hashv(&[&seed_material]);
     hash.to_bytes()
 }
+/// Commitment published by the game keeper before revealing `server_seed` in play_round
+pub fn commitment_for(server_seed: u64) -> [u8; 32] {
+    hashv(&[&server_seed.to_le_bytes()]).to_bytes()
+}
 pub fn random_roll_bps(seed: &[u8; 32], dive_number: u16) -> u32 {
     let mut seed_material = Vec::with_capacity(34);
     seed_material.extend_from_slice(seed);
@@ -17,8 +53,13 @@ pub fn random_roll_bps(seed: &[u8; 32], dive_number: u16) -> u32 {
     let rand_u64 = u64::from_le_bytes(buf);
     (rand_u64 % 1_000_000) as u32
 }
-pub fn random_roll_from_slots(slot_hash: &[u8; 32], session_pda: &Pubkey, dive_number: u16) -> u32 {
-    let seed = generate_seed(slot_hash, session_pda);
+pub fn random_roll_from_slots(
+    slot_hash: &[u8; 32],
+    session_pda: &Pubkey,
+    server_seed: u64,
+    dive_number: u16,
+) -> u32 {
+    let seed = generate_seed(slot_hash, session_pda, server_seed);
     random_roll_bps(&seed, dive_number)
 }
 
@@ -38,12 +79,13 @@ pub fn random_roll_from_entropy(entropy: &[u8], dive_number: u16) -> u32 {
 mod tests {
     use super::*;
     use rstest::rstest;
+    const SERVER_SEED: u64 = 0xDEAD_BEEF;
     #[test]
     fn test_seed_generation_deterministic() {
         let slot_hash = [1u8; 32];
         let pda = Pubkey::new_unique();
-        let seed1 = generate_seed(&slot_hash, &pda);
-        let seed2 = generate_seed(&slot_hash, &pda);
+        let seed1 = generate_seed(&slot_hash, &pda, SERVER_SEED);
+        let seed2 = generate_seed(&slot_hash, &pda, SERVER_SEED);
         assert_eq!(seed1, seed2, "Same inputs must produce same seed");
     }
     #[rstest]
@@ -53,8 +95,8 @@ mod tests {
     #[case([42u8; 32], [43u8; 32])]
     fn test_different_slots_different_seeds(#[case] hash1: [u8; 32], #[case] hash2: [u8; 32]) {
         let pda = Pubkey::new_unique();
-        let seed1 = generate_seed(&hash1, &pda);
-        let seed2 = generate_seed(&hash2, &pda);
+        let seed1 = generate_seed(&hash1, &pda, SERVER_SEED);
+        let seed2 = generate_seed(&hash2, &pda, SERVER_SEED);
         assert_ne!(
             seed1, seed2,
             "Different slot hashes must produce different seeds"
@@ -66,7 +108,7 @@ mod tests {
         let pdas: Vec<Pubkey> = (0..10).map(|_| Pubkey::new_unique()).collect();
         let mut seeds = Vec::new();
         for pda in &pdas {
-            seeds.push(generate_seed(&slot_hash, pda));
+            seeds.push(generate_seed(&slot_hash, pda, SERVER_SEED));
         }
         for i in 0..seeds.len() {
             for j in (i + 1)..seeds.len() {
@@ -84,9 +126,9 @@ mod tests {
     #[case([1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32])]
     fn test_seed_generation_various_inputs(#[case] slot_hash: [u8; 32]) {
         let pda = Pubkey::new_unique();
-        let seed = generate_seed(&slot_hash, &pda);
+        let seed = generate_seed(&slot_hash, &pda, SERVER_SEED);
         assert_eq!(seed.len(), 32);
-        assert_eq!(seed, generate_seed(&slot_hash, &pda));
+        assert_eq!(seed, generate_seed(&slot_hash, &pda, SERVER_SEED));
     }
     #[rstest]
     #[case([42u8; 32], 1)]
@@ -203,9 +245,9 @@ mod tests {
         #[case] pda: Pubkey,
         #[case] dive: u16,
     ) {
-        let seed = generate_seed(&slot_hash, &pda);
+        let seed = generate_seed(&slot_hash, &pda, SERVER_SEED);
         let _ = random_roll_bps(&seed, dive);
-        let _ = random_roll_from_slots(&slot_hash, &pda, dive);
+        let _ = random_roll_from_slots(&slot_hash, &pda, SERVER_SEED, dive);
     }
     #[test]
     fn test_extreme_values() {
@@ -221,16 +263,16 @@ mod tests {
     fn test_seed_collision_resistance() {
         let base_hash = [100u8; 32];
         let base_pda = Pubkey::new_unique();
-        let base_seed = generate_seed(&base_hash, &base_pda);
+        let base_seed = generate_seed(&base_hash, &base_pda, SERVER_SEED);
         let mut modified_hash = base_hash;
         modified_hash[0] = 101;
-        let modified_seed = generate_seed(&modified_hash, &base_pda);
+        let modified_seed = generate_seed(&modified_hash, &base_pda, SERVER_SEED);
         assert_ne!(
             base_seed, modified_seed,
             "One byte change should produce different seed"
         );
         let different_pda = Pubkey::new_unique();
-        let different_seed = generate_seed(&base_hash, &different_pda);
+        let different_seed = generate_seed(&base_hash, &different_pda, SERVER_SEED);
         assert_ne!(
             base_seed, different_seed,
             "Different PDA should produce different seed"
@@ -257,7 +299,7 @@ mod tests {
     fn test_full_session_simulation() {
         let slot_hash = [77u8; 32];
         let session_pda = Pubkey::new_unique();
-        let seed = generate_seed(&slot_hash, &session_pda);
+        let seed = generate_seed(&slot_hash, &session_pda, SERVER_SEED);
         let mut rolls = Vec::new();
         for dive in 1..=50 {
             let roll = random_roll_bps(&seed, dive);
@@ -277,7 +319,7 @@ mod tests {
         let mut first_rolls = Vec::new();
         for _ in 0..num_sessions {
             let session_pda = Pubkey::new_unique();
-            let seed = generate_seed(&slot_hash, &session_pda);
+            let seed = generate_seed(&slot_hash, &session_pda, SERVER_SEED);
             let roll = random_roll_bps(&seed, 1);
             first_rolls.push(roll);
         }
@@ -294,11 +336,71 @@ mod tests {
         );
     }
     #[test]
+    fn test_same_server_seed_and_slot_produce_same_roll() {
+        let slot_hash = [5u8; 32];
+        let pda = Pubkey::new_unique();
+        let roll1 = random_roll_from_slots(&slot_hash, &pda, 1234, 1);
+        let roll2 = random_roll_from_slots(&slot_hash, &pda, 1234, 1);
+        assert_eq!(roll1, roll2);
+    }
+    #[test]
+    fn test_different_server_seed_changes_roll() {
+        let slot_hash = [5u8; 32];
+        let pda = Pubkey::new_unique();
+        assert_ne!(
+            generate_seed(&slot_hash, &pda, 1),
+            generate_seed(&slot_hash, &pda, 2)
+        );
+        let rolls_a: Vec<u32> = (1..=10)
+            .map(|d| random_roll_from_slots(&slot_hash, &pda, 1, d))
+            .collect();
+        let rolls_b: Vec<u32> = (1..=10)
+            .map(|d| random_roll_from_slots(&slot_hash, &pda, 2, d))
+            .collect();
+        assert_ne!(rolls_a, rolls_b);
+    }
+    #[test]
+    fn test_commitment_binds_server_seed() {
+        assert_eq!(commitment_for(7), commitment_for(7));
+        assert_ne!(commitment_for(7), commitment_for(8));
+        assert_ne!(commitment_for(0), [0u8; 32]);
+    }
+    fn slot_hashes_data(entries: &[(u64, [u8; 32])]) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&(entries.len() as u64).to_le_bytes());
+        for (slot, hash) in entries {
+            data.extend_from_slice(&slot.to_le_bytes());
+            data.extend_from_slice(hash);
+        }
+        data
+    }
+    #[test]
+    fn test_latest_slot_hash_returns_newest_entry() {
+        let data = slot_hashes_data(&[(101, [7u8; 32]), (100, [3u8; 32])]);
+        assert_eq!(latest_slot_hash(&data, 102).unwrap(), [7u8; 32]);
+    }
+    #[rstest]
+    #[case(vec![])]
+    #[case(vec![0u8; 8])]
+    #[case(vec![0u8; 48])]
+    fn test_latest_slot_hash_rejects_empty_sysvar(#[case] data: Vec<u8>) {
+        let err = latest_slot_hash(&data, 102).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::InvalidSlotHash.into());
+    }
+    #[rstest]
+    #[case(101)]
+    #[case(100)]
+    fn test_latest_slot_hash_rejects_unsettled_slot(#[case] current_slot: u64) {
+        let data = slot_hashes_data(&[(101, [7u8; 32]), (100, [3u8; 32])]);
+        let err = latest_slot_hash(&data, current_slot).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::InvalidSlotHash.into());
+    }
+    #[test]
     fn test_determinism_across_all_functions() {
         let slot_hash = [42u8; 32];
         let pda = Pubkey::new_unique();
-        let seed1 = generate_seed(&slot_hash, &pda);
-        let seed2 = generate_seed(&slot_hash, &pda);
+        let seed1 = generate_seed(&slot_hash, &pda, SERVER_SEED);
+        let seed2 = generate_seed(&slot_hash, &pda, SERVER_SEED);
         assert_eq!(seed1, seed2);
         for dive in 0..=100 {
             let roll1 = random_roll_bps(&seed1, dive);
@@ -306,8 +408,8 @@ mod tests {
             assert_eq!(roll1, roll2);
         }
         for dive in 0..=10 {
-            let roll1 = random_roll_from_slots(&slot_hash, &pda, dive);
-            let roll2 = random_roll_from_slots(&slot_hash, &pda, dive);
+            let roll1 = random_roll_from_slots(&slot_hash, &pda, SERVER_SEED, dive);
+            let roll2 = random_roll_from_slots(&slot_hash, &pda, SERVER_SEED, dive);
             assert_eq!(roll1, roll2);
         }
     }
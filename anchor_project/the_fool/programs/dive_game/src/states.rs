@@ -8,24 +8,116 @@ use tsify::Tsify;
 pub const HOUSE_VAULT_SEED: &str = "house_vault";
 pub const SESSION_SEED: &str = "session";
 pub const GAME_CONFIG_SEED: &str = "game_config";
+pub const USER_STATS_SEED: &str = "user_stats";
+pub const HOUSE_STATS_SEED: &str = "house_stats";
+pub const LEADERBOARD_SEED: &str = "leaderboard";
 
-#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+/// Number of payouts a Leaderboard keeps
+pub const LEADERBOARD_SIZE: usize = 10;
+
+/// Largest free vault balance sweep_dust will move; anything more goes through withdraw_house
+pub const MAX_DUST_LAMPORTS: u64 = 100_000;
+
+/// Seed component appended to GAME_CONFIG_SEED for a difficulty tier
+/// Tier 0 adds an empty seed, so it derives the same address as the original single config
+pub fn difficulty_seed(difficulty: &u8) -> &[u8] {
+    if *difficulty == 0 {
+        &[]
+    } else {
+        std::slice::from_ref(difficulty)
+    }
+}
+
+/// Current account layout versions, bumped with every layout change together with the step in
+/// crate::legacy that converts the previous layout
+/// `version` is the first field after the discriminator, so it can be read before the layout
+/// is known
+pub const CONFIG_VERSION: u8 = 3;
+pub const VAULT_VERSION: u8 = 2;
+pub const SESSION_VERSION: u8 = 2;
+
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Copy, Default, PartialEq, Eq, InitSpace, Debug,
+)]
 #[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
 #[cfg_attr(feature = "tsify", tsify(namespace))]
 pub enum SessionStatus {
+    #[default]
     Active,
     Lost,
     CashedOut,
 }
+/// Shape of the treasure curve in treasure_for_dive
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(namespace))]
+pub enum CurveMode {
+    /// Treasure is multiplied by num/den every dive
+    Geometric,
+    /// Treasure grows by bet_amount * linear_step_bps / 1_000_000 every dive
+    Linear,
+}
+/// Shape of the survival decay in survival_probability_bps
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(namespace))]
+pub enum DecayMode {
+    /// Survival drops by decay_per_dive_ppm every dive
+    Linear,
+    /// Survival is multiplied by (1_000_000 - decay_per_dive_ppm) / 1_000_000 every dive
+    Exponential,
+}
+/// How treasure_for_dive rounds its multiply-divide steps
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(namespace))]
+pub enum RoundingMode {
+    /// Truncate, the remainder stays with the house
+    Floor,
+    /// Round half up to the nearest lamport
+    Nearest,
+}
+/// What cleaning an expired session does with its current treasure
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(namespace))]
+pub enum ExpiryMode {
+    /// Only release the reservation; the treasure stays in the vault
+    ReleaseOnly,
+    /// Pay the treasure to the player, who never lost a roll, if cash_out would let them exit
+    AutoCashOut,
+}
+/// How much of the vault start_session sets aside for a new session
+#[derive(AnchorDeserialize, AnchorSerialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(namespace))]
+pub enum ReservationMode {
+    /// Reserve the whole max_payout
+    Full,
+    /// Reserve the best probability-weighted payout on the survival curve
+    Expected,
+}
 #[account]
 #[derive(InitSpace)]
 #[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
 #[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
 pub struct HouseVault {
+    pub version: u8,             // Layout version, see VAULT_VERSION
     pub house_authority: Pubkey, // Cold wallet (can withdraw/change config)
     pub game_keeper: Pubkey,     // Hot wallet (signs play_round for server RNG)
     pub locked: bool,
     pub total_reserved: u64,
+    pub pending_authority: Option<Pubkey>, // Proposed house_authority awaiting accept_house_authority
+    pub strict_accounting: bool, // Fail release() on underflow instead of clamping to zero
+    pub jackpot_pool: u64,       // Lamports set aside for the next max-dive cash-out
+    pub sequence: u64,           // Game events emitted so far, stamped on each as its order
+    pub pending_withdraw_amount: u64, // Requested by request_withdraw, 0 when none is pending
+    pub withdraw_unlock_slot: u64, // Slot from which execute_withdraw may pay the request
+    pub cosigner: Option<Pubkey>, // Second signer required on withdrawals, set via set_cosigner
+    pub active_sessions: u32,    // Sessions open against this vault, bounded by max_total_sessions
+    pub bonus_pool: u64,         // Lamports granted as locked player bonuses, awaiting claim_bonus
+    pub authorized_crank: Option<Pubkey>, // Only key allowed to clean expired sessions, None = anyone
+    pub consecutive_big_payouts: u16,     // Current run of payouts above config.big_payout_lamports
     pub bump: u8,
 }
 impl HouseVault {
@@ -36,17 +128,263 @@ impl HouseVault {
             .ok_or(error!(crate::errors::GameError::Overflow))?;
         Ok(())
     }
+    /// Rejects a reservation that would push total_reserved past
+    /// vault_balance * max_exposure_bps / 1_000_000
+    pub fn check_exposure(
+        &self,
+        vault_balance: u64,
+        max_payout: u64,
+        max_exposure_bps: u32,
+    ) -> Result<()> {
+        let new_total_reserved = self
+            .total_reserved
+            .checked_add(max_payout)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        let cap = vault_balance as u128 * max_exposure_bps as u128 / 1_000_000;
+        require!(
+            new_total_reserved as u128 <= cap,
+            crate::errors::GameError::VaultCapacityExceeded
+        );
+        Ok(())
+    }
+
+    /// First step of an authority transfer; replaces any earlier proposal
+    pub fn propose_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+        require!(
+            new_authority != Pubkey::default(),
+            crate::errors::GameError::InvalidConfig
+        );
+        self.pending_authority = Some(new_authority);
+        Ok(())
+    }
+
+    /// Second step: only the pending key can take over as house_authority
+    pub fn accept_authority(&mut self, signer: Pubkey) -> Result<()> {
+        require!(
+            self.pending_authority == Some(signer),
+            crate::errors::GameError::NotPendingAuthority
+        );
+        self.house_authority = signer;
+        self.pending_authority = None;
+        Ok(())
+    }
+
+    /// Lamports the house authority may withdraw without touching reserved funds
+    /// or the vault's rent-exempt minimum
+    pub fn withdrawable(&self, balance: u64, rent_exempt: u64) -> u64 {
+        let required = self.committed().saturating_add(rent_exempt);
+        balance.saturating_sub(required)
+    }
+
+    /// Solvency snapshot for a vault holding `balance` lamports
+    pub fn health(&self, balance: u64, rent_exempt: u64) -> VaultHealth {
+        VaultHealth {
+            balance,
+            total_reserved: self.total_reserved,
+            jackpot_pool: self.jackpot_pool,
+            rent_exempt_minimum: rent_exempt,
+            available_to_withdraw: self.withdrawable(balance, rent_exempt),
+            solvent: balance as u128 >= self.committed() as u128 + rent_exempt as u128,
+        }
+    }
+
+    /// Lamports owed to players: session reservations, the jackpot pool and granted bonuses
+    pub fn committed(&self) -> u64 {
+        self.total_reserved
+            .saturating_add(self.jackpot_pool)
+            .saturating_add(self.bonus_pool)
+    }
+
+    pub fn add_to_jackpot(&mut self, amount: u64) -> Result<()> {
+        self.jackpot_pool = self
+            .jackpot_pool
+            .checked_add(amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Advances the event counter and returns the sequence number for the next game event
+    /// Slots and timestamps don't order events within a block; this gives indexers a total order
+    pub fn next_sequence(&mut self) -> Result<u64> {
+        self.sequence = self
+            .sequence
+            .checked_add(1)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(self.sequence)
+    }
+
+    /// Queues a timelocked withdrawal, replacing any pending one
+    pub fn request_withdraw(&mut self, amount: u64, unlock_slot: u64) -> Result<()> {
+        require!(amount > 0, crate::errors::GameError::InvalidWithdrawAmount);
+        self.pending_withdraw_amount = amount;
+        self.withdraw_unlock_slot = unlock_slot;
+        Ok(())
+    }
+
+    /// Clears the pending withdrawal once unlocked, returning its amount
+    pub fn take_unlocked_withdraw(&mut self, current_slot: u64) -> Result<u64> {
+        require!(
+            self.pending_withdraw_amount > 0,
+            crate::errors::GameError::NoPendingWithdrawal
+        );
+        require!(
+            current_slot >= self.withdraw_unlock_slot,
+            crate::errors::GameError::WithdrawalNotUnlocked
+        );
+        self.withdraw_unlock_slot = 0;
+        Ok(std::mem::take(&mut self.pending_withdraw_amount))
+    }
+
+    /// Drops the pending withdrawal, returning its amount
+    pub fn cancel_withdraw(&mut self) -> Result<u64> {
+        require!(
+            self.pending_withdraw_amount > 0,
+            crate::errors::GameError::NoPendingWithdrawal
+        );
+        self.withdraw_unlock_slot = 0;
+        Ok(std::mem::take(&mut self.pending_withdraw_amount))
+    }
+
+    /// Empties the jackpot pool, returning what it held
+    pub fn take_jackpot(&mut self) -> u64 {
+        std::mem::take(&mut self.jackpot_pool)
+    }
+
+    /// Rotates the hot wallet allowed to sign play_round
+    pub fn set_game_keeper(&mut self, game_keeper: Pubkey) -> Result<()> {
+        require!(
+            game_keeper != Pubkey::default(),
+            crate::errors::GameError::InvalidConfig
+        );
+        self.game_keeper = game_keeper;
+        Ok(())
+    }
+    /// Counts a newly started session against `limit` open sessions (0 = unlimited)
+    pub fn open_session(&mut self, limit: u32) -> Result<()> {
+        require!(
+            limit == 0 || self.active_sessions < limit,
+            crate::errors::GameError::VaultCapacityExceeded
+        );
+        self.active_sessions = self
+            .active_sessions
+            .checked_add(1)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Counts a session closing; sessions started before the counter existed saturate at 0
+    pub fn close_session(&mut self) {
+        self.active_sessions = self.active_sessions.saturating_sub(1);
+    }
+
+    /// A cosigner equal to the authority would add no second key
+    pub fn set_cosigner(&mut self, cosigner: Option<Pubkey>) -> Result<()> {
+        if let Some(key) = cosigner {
+            require!(
+                key != Pubkey::default() && key != self.house_authority,
+                crate::errors::GameError::InvalidConfig
+            );
+        }
+        self.cosigner = cosigner;
+        Ok(())
+    }
+
+    /// Counts `payout` toward the circuit breaker: payouts above `threshold` extend the run and
+    /// anything else ends it. Locks the vault once the run exceeds `limit` and returns whether
+    /// this payout tripped it. A zero threshold or limit disables the breaker
+    pub fn record_payout_streak(&mut self, payout: u64, threshold: u64, limit: u16) -> bool {
+        if threshold == 0 || limit == 0 {
+            return false;
+        }
+        if payout <= threshold {
+            self.consecutive_big_payouts = 0;
+            return false;
+        }
+        self.consecutive_big_payouts = self.consecutive_big_payouts.saturating_add(1);
+        if self.consecutive_big_payouts > limit && !self.locked {
+            self.locked = true;
+            return true;
+        }
+        false
+    }
+
+    /// Ends the current run of big payouts, e.g. when the house wins a session
+    pub fn reset_payout_streak(&mut self) {
+        self.consecutive_big_payouts = 0;
+    }
+
+    /// Restricts expired-session cleanup to `crank`, or reopens it to anyone with None
+    pub fn set_authorized_crank(&mut self, crank: Option<Pubkey>) -> Result<()> {
+        require!(
+            crank != Some(Pubkey::default()),
+            crate::errors::GameError::InvalidConfig
+        );
+        self.authorized_crank = crank;
+        Ok(())
+    }
+
+    /// Whether `crank` may clean this vault's expired sessions
+    pub fn crank_allowed(&self, crank: &Pubkey) -> bool {
+        self.authorized_crank
+            .is_none_or(|authorized| authorized == *crank)
+    }
     pub fn release(&mut self, amount: u64) -> Result<()> {
+        if self.strict_accounting {
+            // Correctness over liveness: surface the accounting drift
+            self.total_reserved = self
+                .total_reserved
+                .checked_sub(amount)
+                .ok_or(crate::errors::GameError::Overflow)?;
+            return Ok(());
+        }
         // Saturating sub ensures we don't brick the contract if math drifts slightly
         self.total_reserved = self.total_reserved.saturating_sub(amount);
         Ok(())
     }
 }
+/// Returned by vault_health; `solvent` means balance covers reservations, jackpot and rent
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct VaultHealth {
+    pub balance: u64,
+    pub total_reserved: u64,
+    pub jackpot_pool: u64,
+    pub rent_exempt_minimum: u64,
+    pub available_to_withdraw: u64,
+    pub solvent: bool,
+}
+/// Returned by simulate_session: how a fresh session would play out under one seed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SimResult {
+    /// Whether a roll failed before max_dives
+    pub lost: bool,
+    /// Dive the session would lose on, or max_dives when every roll survives
+    pub final_dive: u16,
+    /// Treasure held after surviving each dive, starting with dive 1
+    pub treasures: Vec<u64>,
+}
+/// Returned by get_session_view: the raw session plus values derived from its config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct SessionSnapshot {
+    pub session: GameSession,
+    /// Survival probability of the current dive's roll, out of 1_000_000
+    pub survival_ppm: u32,
+    /// Treasure held after surviving the current dive
+    pub next_treasure: u64,
+    /// Slots left before clean_expired_session may close the session, 0 once expired
+    pub slots_until_expiry: u64,
+}
 #[account]
 #[derive(InitSpace)]
 #[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
 #[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
 pub struct GameConfig {
+    pub version: u8, // Layout version, see CONFIG_VERSION
     pub admin: Pubkey,
     pub base_survival_ppm: u32,
     pub decay_per_dive_ppm: u32,
@@ -55,20 +393,70 @@ pub struct GameConfig {
     pub treasure_multiplier_den: u16,
     pub max_payout_multiplier: u16,
     pub max_dives: u16,
-    pub fixed_bet: u64, // Single fixed bet amount (e.g. 0.01 SOL)
+    pub min_bet: u64,        // Smallest accepted bet_amount in lamports
+    pub max_bet: u64,        // Largest accepted bet_amount in lamports
+    pub vrf_enabled: bool,   // Rounds resolve through play_round_vrf instead of slot-hash RNG
+    pub house_edge_bps: u32, // Payout haircut out of 1_000_000 applied in treasure_for_dive
+    pub curve_mode: CurveMode,
+    pub linear_step_bps: u32, // Per-dive increment out of 1_000_000 of the bet (Linear mode)
+    pub referral_fee_bps: u32, // Referrer cut of cash out payouts out of 1_000_000
+    pub max_exposure_bps: u32, // Cap on total_reserved as a share of vault balance, out of 1_000_000
+    pub timeout_slots: u64,    // Inactivity window before clean_expired_session may close a session
+    pub paused: bool, // Global kill switch: blocks new sessions and rounds, exits stay open
+    pub require_full_coverage: bool, // Require the full max_payout to be available at session start instead of 20%
+    pub pending_admin: Option<Pubkey>, // Proposed admin awaiting accept_admin
+    pub min_dives_to_cash_out: u16, // Dive the session must have reached before cash_out is allowed
+    pub rake_bps: u32, // Share of each bet kept by the house at session start, out of 1_000_000
+    pub final_gamble_bps: u32, // Win chance of gamble_treasure's double-or-nothing roll, out of 1_000_000
+    pub jackpot_bps: u32, // Share of each bet diverted into the vault's jackpot_pool, out of 1_000_000
+    pub difficulty: u8,   // Tier this config PDA is derived for, see difficulty_seed
+    pub max_payout_lamports: u64, // Absolute payout ceiling on top of max_payout_multiplier, 0 = unlimited
+    pub allowed_bets: [u64; 8], // Bet denominations accepted when use_bet_whitelist is set, 0 = unused slot
+    pub use_bet_whitelist: bool, // Only accept bets listed in allowed_bets instead of the min_bet..=max_bet range
+    pub crank_reward_lamports: u64, // Paid from the vault to whoever cleans an expired session, on top of its rent
+    pub max_active_sessions_per_user: u16, // Open sessions a single user may hold at once, 0 = unlimited
+    pub decay_mode: DecayMode,             // How decay_per_dive_ppm lowers survival each dive
+    pub insurance_payout_bps: u32, // Share of the treasure buy_insurance refunds on a failed dive, 0 = insurance disabled
+    pub verbose_logging: bool, // Logs a per-round trace from play_round; off by default to save compute on mainnet
+    pub first_dive_guaranteed: bool, // Promotion: the first dive of every session survives without a roll
+    pub allow_breakeven_exit: bool, // Lets cash_out refund the bet while the treasure hasn't grown past it
+    pub absolute_max_dives: u16, // Ceiling for start_session's max_dives_override, 0 = overrides disabled
+    pub min_slots_before_play: u64, // Slots a session must wait after start_session before its first roll
+    pub loss_rebate_bps: u32, // Share of the bet refunded to the player when a session is lost
+    pub bet_step: u64, // Bets must be a multiple of this outside whitelist mode, 0 = any amount
+    pub withdraw_delay_slots: u64, // Timelock for house withdrawals via request_withdraw, 0 = withdraw_house pays immediately
+    pub max_bet_vault_bps: u32, // Caps a bet at vault_balance * this / (1_000_000 * max_payout_multiplier), 0 = max_bet only
+    pub milestone_dive: u16, // Surviving this dive unlocks milestone_bonus_bps on the treasure, 0 = no milestone
+    pub milestone_bonus_bps: u32, // Treasure bonus once milestone_dive is survived, out of 1_000_000
+    pub usd_denominated: bool, // bet_amount is USD cents, converted at the price_feed price in start_session
+    pub price_feed: Pubkey,    // Pyth price update account for SOL/USD, read when usd_denominated
+    pub max_price_age_secs: u64, // Oldest price_feed publish_time accepted, in seconds
+    pub max_total_sessions: u32, // Cap on sessions open at once across the vault, 0 = unlimited
+    pub rounding_mode: RoundingMode, // Rounding of the treasure curve and house edge
+    pub bonus_wagering_multiple: u16, // claim_bonus needs total_bet >= this * bonus_locked_lamports, 0 = claimable at once
+    pub max_boost_bps: u32, // Largest survival boost apply_boost accepts for one dive, out of 1_000_000, 0 = boosts disabled
+    pub expiry_mode: ExpiryMode, // What clean_expired_session does with an abandoned session's treasure
+    pub big_payout_lamports: u64, // Payouts above this count toward the circuit breaker, 0 = breaker off
+    pub max_consecutive_big_payouts: u16, // Locks the vault once more big payouts than this land in a row, 0 = breaker off
+    pub reservation_mode: ReservationMode, // How much of the vault each new session reserves
+    pub active_sessions: u64, // Open sessions on any vault, so payout changes know what they'd affect
+    pub withdraw_delay_floor_slots: u64, // Delay still in force after withdraw_delay_slots was lowered
+    pub withdraw_delay_floor_until: u64, // Slot from which withdraw_delay_floor_slots no longer applies
     pub bump: u8,
 }
 impl GameConfig {
-    pub fn default_config() -> (u32, u32, u32, u16, u16, u16, u16, u64) {
+    pub fn default_config() -> (u32, u32, u32, u16, u16, u16, u16, u64, u64, u64) {
         (
-            700_000,    // base_survival_ppm (70%)
-            8_000,      // decay_per_dive_ppm (0.8%)
-            50_000,     // min_survival_ppm (5%)
-            19,         // treasure_multiplier_num
-            10,         // treasure_multiplier_den (1.9x per dive)
-            100,        // max_payout_multiplier (100x max)
-            5,          // max_dives
-            10_000_000, // fixed_bet (0.01 SOL - simple fixed amount)
+            700_000,       // base_survival_ppm (70%)
+            8_000,         // decay_per_dive_ppm (0.8%)
+            50_000,        // min_survival_ppm (5%)
+            19,            // treasure_multiplier_num
+            10,            // treasure_multiplier_den (1.9x per dive)
+            100,           // max_payout_multiplier (100x max)
+            5,             // max_dives
+            10_000_000,    // min_bet (0.01 SOL)
+            1_000_000_000, // max_bet (1 SOL)
+            750,           // timeout_slots (~5 minutes at 400ms per slot)
         )
     }
 
@@ -101,20 +489,306 @@ impl GameConfig {
             crate::errors::GameError::InvalidConfig
         );
 
+        // Decay can't exceed the base probability, and must exist once there is a second dive
+        require!(
+            self.decay_per_dive_ppm <= self.base_survival_ppm,
+            crate::errors::GameError::InvalidConfig
+        );
+        require!(
+            self.max_dives <= 1 || self.decay_per_dive_ppm > 0,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // House edge is a fraction of 1_000_000
+        require!(
+            self.house_edge_bps <= 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Sessions must be cleanable eventually
+        require!(
+            self.timeout_slots > 0,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Exposure cap is a non-zero fraction of the vault balance
+        require!(
+            self.max_exposure_bps > 0 && self.max_exposure_bps <= 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Milestone bonus at most doubles the treasure
+        require!(
+            self.milestone_bonus_bps <= 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Vault-scaled bet cap is a fraction of 1_000_000, 0 disables it
+        require!(
+            self.max_bet_vault_bps <= 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Gamble odds are a fraction of 1_000_000
+        require!(
+            self.final_gamble_bps <= 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Rake and jackpot share must leave part of the bet in play
+        require!(
+            (self.rake_bps as u64 + self.jackpot_bps as u64) < 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Loss rebate is a fraction of 1_000_000 of the bet
+        require!(
+            self.loss_rebate_bps <= 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Insurance refunds a fraction of 1_000_000
+        require!(
+            self.insurance_payout_bps <= 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Boosts add to a survival probability out of 1_000_000
+        require!(
+            self.max_boost_bps <= 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Referral fee is a fraction of 1_000_000
+        require!(
+            self.referral_fee_bps <= 1_000_000,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // The treasure curve must actually grow; a flat or shrinking one never reaches the
+        // payout cap, so max_dives_for_bet would walk every dive up to max_dives
+        match self.curve_mode {
+            CurveMode::Geometric => require!(
+                self.treasure_multiplier_num > self.treasure_multiplier_den,
+                crate::errors::GameError::InvalidConfig
+            ),
+            CurveMode::Linear => require!(
+                self.linear_step_bps > 0,
+                crate::errors::GameError::InvalidConfig
+            ),
+        }
+
         // Dive limit validation
         require!(self.max_dives > 0, crate::errors::GameError::InvalidConfig);
+        require!(
+            self.min_dives_to_cash_out <= self.max_dives,
+            crate::errors::GameError::InvalidConfig
+        );
+        // The override ceiling is either off or at least the regular dive limit
+        require!(
+            self.absolute_max_dives == 0 || self.absolute_max_dives >= self.max_dives,
+            crate::errors::GameError::InvalidConfig
+        );
+        // A free first dive reaches dive 2 at no risk, so at least one real roll must
+        // follow before any treasure can leave the session
+        require!(
+            !self.first_dive_guaranteed || self.min_dives_to_cash_out >= 3,
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // Bet bounds must be positive and ordered
+        require!(self.min_bet > 0, crate::errors::GameError::InvalidConfig);
+        require!(
+            self.min_bet <= self.max_bet,
+            crate::errors::GameError::InvalidConfig
+        );
+        // A bet step must leave at least one multiple inside [min_bet, max_bet]
+        if self.bet_step > 0 {
+            let first_step = self
+                .min_bet
+                .div_ceil(self.bet_step)
+                .checked_mul(self.bet_step)
+                .ok_or(crate::errors::GameError::InvalidConfig)?;
+            require!(
+                first_step <= self.max_bet,
+                crate::errors::GameError::InvalidConfig
+            );
+        }
+
+        // USD bets need a feed to price them, and lamport grids can't apply to cents
+        require!(
+            !self.usd_denominated
+                || (self.price_feed != Pubkey::default()
+                    && self.max_price_age_secs > 0
+                    && !self.use_bet_whitelist
+                    && self.bet_step == 0),
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // A whitelist with only unused slots would reject every bet
+        require!(
+            !self.use_bet_whitelist || self.allowed_bets.iter().any(|&bet| bet > 0),
+            crate::errors::GameError::InvalidConfig
+        );
+
+        // An absolute payout cap must leave room to win something on the smallest bet
+        require!(
+            self.max_payout_lamports == 0 || self.max_payout_lamports > self.min_bet,
+            crate::errors::GameError::InvalidConfig
+        );
 
-        // Fixed bet must be positive
-        require!(self.fixed_bet > 0, crate::errors::GameError::InvalidConfig);
+        Ok(())
+    }
+
+    /// Free vault balance a new session needs before its max_payout may be reserved
+    /// Demo mode only asks for 20% of max_payout; full coverage asks for all of it
+    pub fn required_coverage(&self, max_payout: u64) -> u64 {
+        if self.require_full_coverage {
+            max_payout
+        } else {
+            max_payout / 5
+        }
+    }
+
+    /// First step of an admin transfer; replaces any earlier proposal
+    pub fn propose_admin(&mut self, new_admin: Pubkey) -> Result<()> {
+        require!(
+            new_admin != Pubkey::default(),
+            crate::errors::GameError::InvalidConfig
+        );
+        self.pending_admin = Some(new_admin);
+        Ok(())
+    }
+
+    /// Second step: only the pending key can take over as admin
+    pub fn accept_admin(&mut self, signer: Pubkey) -> Result<()> {
+        require!(
+            self.pending_admin == Some(signer),
+            crate::errors::GameError::NotPendingAdmin
+        );
+        self.admin = signer;
+        self.pending_admin = None;
+        Ok(())
+    }
+
+    /// Rejects new sessions and rounds while the admin has paused the game
+    pub fn ensure_not_paused(&self) -> Result<()> {
+        require!(!self.paused, crate::errors::GameError::GamePaused);
+        Ok(())
+    }
+
+    /// An override may only deepen the session, up to absolute_max_dives
+    pub fn validate_max_dives_override(&self, max_dives: u16) -> Result<()> {
+        require!(
+            max_dives >= self.max_dives && max_dives <= self.absolute_max_dives,
+            crate::errors::GameError::InvalidMaxDivesOverride
+        );
+        Ok(())
+    }
+
+    /// Blocks cash-outs before the session reaches min_dives_to_cash_out
+    pub fn ensure_min_dives(&self, dive_number: u16) -> Result<()> {
+        require!(
+            dive_number >= self.min_dives_to_cash_out,
+            crate::errors::GameError::MinDivesNotReached
+        );
+        Ok(())
+    }
+
+    /// Validates a player's bet against the configured bounds, or against allowed_bets
+    /// when the whitelist is enabled
+    pub fn validate_bet(&self, bet_amount: u64) -> Result<()> {
+        if self.use_bet_whitelist {
+            require!(
+                bet_amount > 0 && self.allowed_bets.contains(&bet_amount),
+                crate::errors::GameError::InvalidBetAmount
+            );
+            return Ok(());
+        }
+        require!(
+            bet_amount >= self.min_bet && bet_amount <= self.max_bet,
+            crate::errors::GameError::InvalidBetAmount
+        );
+        self.validate_bet_step(bet_amount)
+    }
+
+    /// Counts sessions opened under this config
+    pub fn open_sessions(&mut self, count: u64) -> Result<()> {
+        self.active_sessions = self
+            .active_sessions
+            .checked_add(count)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Counts sessions out as they end; ones opened before the count existed can't take it
+    /// below zero
+    pub fn close_sessions(&mut self, count: u64) {
+        self.active_sessions = self.active_sessions.saturating_sub(count);
+    }
+
+    /// close_sessions for `session` if the round just played ended it
+    pub fn release_ended(&mut self, session: &GameSession) {
+        if session.status != SessionStatus::Active {
+            self.close_sessions(1);
+        }
+    }
+
+    /// Whether surviving `dive_number` reaches the milestone bonus
+    pub fn milestone_reached(&self, dive_number: u16) -> bool {
+        self.milestone_dive > 0
+            && self.milestone_bonus_bps > 0
+            && dive_number >= self.milestone_dive
+    }
+
+    /// Largest bet the vault can back under max_bet_vault_bps, None when disabled
+    /// Scales with liquidity so a bet's max payout stays a bounded share of the balance
+    pub fn vault_max_bet(&self, vault_balance: u64) -> Option<u64> {
+        if self.max_bet_vault_bps == 0 {
+            return None;
+        }
+        let denominator = 1_000_000u128 * self.max_payout_multiplier.max(1) as u128;
+        let cap = vault_balance as u128 * self.max_bet_vault_bps as u128 / denominator;
+        Some(cap.min(u64::MAX as u128) as u64)
+    }
+
+    /// Rejects a bet above vault_max_bet for the vault's current balance
+    pub fn validate_bet_vault_cap(&self, bet_amount: u64, vault_balance: u64) -> Result<()> {
+        if let Some(cap) = self.vault_max_bet(vault_balance) {
+            require!(
+                bet_amount <= cap,
+                crate::errors::GameError::BetExceedsVaultCap
+            );
+        }
+        Ok(())
+    }
+
+    /// Withdraw delay in force at `slot`
+    /// A lowered delay only takes over once the one it replaced has run out
+    pub fn withdraw_delay_at(&self, slot: u64) -> u64 {
+        if slot < self.withdraw_delay_floor_until {
+            self.withdraw_delay_slots
+                .max(self.withdraw_delay_floor_slots)
+        } else {
+            self.withdraw_delay_slots
+        }
+    }
 
+    /// Rejects amounts off the bet_step grid
+    pub fn validate_bet_step(&self, amount: u64) -> Result<()> {
+        require!(
+            self.bet_step == 0 || amount.is_multiple_of(self.bet_step),
+            crate::errors::GameError::InvalidBetAmount
+        );
         Ok(())
     }
 }
 #[account]
-#[derive(InitSpace)]
+#[derive(InitSpace, Default)]
 #[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
 #[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
 pub struct GameSession {
+    pub version: u8, // Layout version, see SESSION_VERSION
     pub user: Pubkey,
     pub house_vault: Pubkey,
     pub status: SessionStatus,
@@ -129,9 +803,55 @@ pub struct GameSession {
     /// Slot number when session was last active
     /// Updated on: start_session, play_round (if survived), cash_out
     pub last_active_slot: u64,
+    /// Commit-reveal RNG: hash of the server seed for the next round (zeroed when unset)
+    pub pending_commitment: [u8; 32],
+    /// Slot at which pending_commitment was stored
+    pub commitment_slot: u64,
+    /// Lamports already paid out through partial_cash_out
+    /// Deducted from the treasure curve and from the vault reservation
+    pub total_withdrawn: u64,
+    /// Receives referral_fee_bps of the cash out payout when set
+    pub referrer: Option<Pubkey>,
+    pub difficulty: u8, // Difficulty tier of the GameConfig this session plays under
+    /// Gross curve value at dive_number, so the next dive is a single step; 0 when unknown
+    pub gross_treasure: u64,
+    /// Slot after which clean_expired_session may close the session
+    /// Fixed from the config timeout at the last activity, so later timeout changes don't move it
+    pub expires_at_slot: u64,
+    /// Latest server seed the keeper revealed for this session, published in RevealedSeedEvent
+    /// when the session closes
+    pub server_seed: Option<u64>,
+    /// Set by buy_insurance; the next roll consumes it and a loss refunds insurance_payout_bps
+    pub insured: bool,
+    /// Keeper allowed to commit and play this session's rounds, moved by rebind_session_keeper
+    pub keeper: Pubkey,
+    /// Dive limit set by the house at start_session for event sessions, replacing config.max_dives
+    pub max_dives_override: Option<u16>,
+    /// Set once the session survives config.milestone_dive; the bonus then applies to the curve
+    pub milestone_claimed: bool,
+    /// SOL/USD price in micro-USD locked at start_session for USD-denominated configs, else 0
+    pub locked_price_micro_usd: u64,
+    /// Set by apply_boost; added to the next roll's survival threshold, which consumes it
+    pub next_dive_boost_bps: u32,
+    /// Opaque label from start_session so indexers can tell apps or campaigns apart, zeros if unset
+    pub client_tag: [u8; 16],
+    /// Vault lamports set aside at start_session: max_payout, or less under ReservationMode::Expected
+    pub reserved_payout: u64,
+    /// VRF account recorded by request_vrf_round, which play_round_vrf must resolve next
+    pub pending_vrf: Option<Pubkey>,
+    /// Request slot of pending_vrf's round, later than any activity before the request
+    pub vrf_request_slot: u64,
 }
 
 impl GameSession {
+    /// Empty Active session at the current layout version, for callers to fill in
+    pub fn blank() -> Self {
+        Self {
+            version: SESSION_VERSION,
+            ..Default::default()
+        }
+    }
+
     /// Ensures the session is in Active status
     /// Should be called at the start of any instruction that requires active gameplay
     pub fn ensure_active(&self) -> Result<()> {
@@ -142,10 +862,150 @@ impl GameSession {
         Ok(())
     }
 
-    /// Marks the session as Lost and validates the state transition
-    pub fn mark_lost(&mut self) -> Result<()> {
-        self.ensure_active()?;
-        self.status = SessionStatus::Lost;
+    /// Dive limit for this session: its override when the house set one, else config.max_dives
+    pub fn max_dives(&self, config: &GameConfig) -> u16 {
+        self.max_dives_override.unwrap_or(config.max_dives)
+    }
+
+    /// Guards against a client acting on a stale view of the session
+    pub fn ensure_dive(&self, expected_dive: u16) -> Result<()> {
+        require!(
+            self.dive_number == expected_dive,
+            crate::errors::GameError::RoundMismatch
+        );
+        Ok(())
+    }
+
+    /// Anti-MEV delay: the first roll must land `min_slots` after start_session, so its slot
+    /// hash can't be known by whoever produced the start
+    pub fn ensure_play_delay(&self, current_slot: u64, min_slots: u64) -> Result<()> {
+        if self.dive_number == 1 {
+            require!(
+                current_slot.saturating_sub(self.last_active_slot) >= min_slots,
+                crate::errors::GameError::PlayedTooSoon
+            );
+        }
+        Ok(())
+    }
+
+    /// Slippage guard: the signed cash-out never pays less than the client saw
+    pub fn ensure_min_payout(&self, min_payout: u64) -> Result<()> {
+        require!(
+            self.current_treasure >= min_payout,
+            crate::errors::GameError::PayoutBelowMinimum
+        );
+        Ok(())
+    }
+
+    /// Records activity at `slot` and pushes the deadline out to `slot + timeout_slots`
+    pub fn record_activity(&mut self, slot: u64, timeout_slots: u64) {
+        self.last_active_slot = slot;
+        self.expires_at_slot = slot.saturating_add(timeout_slots);
+    }
+
+    /// Slots since last activity, or SessionNotExpired until expires_at_slot has passed
+    pub fn expired_slots(&self, current_slot: u64) -> Result<u64> {
+        require!(
+            current_slot > self.expires_at_slot,
+            crate::errors::GameError::SessionNotExpired
+        );
+        current_slot
+            .checked_sub(self.last_active_slot)
+            .ok_or(crate::errors::GameError::Overflow.into())
+    }
+
+    /// Stores the keeper's commitment for the next round
+    pub fn set_commitment(&mut self, commitment: [u8; 32], slot: u64) {
+        self.pending_commitment = commitment;
+        self.commitment_slot = slot;
+    }
+
+    /// Verifies a revealed server seed against the pending commitment and clears it
+    /// The commitment must have been stored in an earlier slot than the reveal
+    /// The latest seed revealed is kept in server_seed
+    pub fn consume_commitment(&mut self, server_seed: u64, current_slot: u64) -> Result<()> {
+        require!(
+            self.pending_commitment != [0u8; 32],
+            crate::errors::GameError::CommitmentMismatch
+        );
+        require!(
+            self.commitment_slot < current_slot,
+            crate::errors::GameError::CommitmentMismatch
+        );
+        require!(
+            crate::rng::commitment_for(server_seed) == self.pending_commitment,
+            crate::errors::GameError::CommitmentMismatch
+        );
+        self.pending_commitment = [0u8; 32];
+        self.server_seed = Some(server_seed);
+        Ok(())
+    }
+
+    /// Portion of reserved_payout still reserved in the house vault for this session
+    pub fn reserved_amount(&self) -> u64 {
+        self.reserved_payout.saturating_sub(self.total_withdrawn)
+    }
+
+    /// Withdraws part of the treasure while keeping the session active
+    /// A partial cash out must leave at least bet_amount in the treasure
+    pub fn apply_partial_cash_out(&mut self, amount: u64) -> Result<()> {
+        self.ensure_active()?;
+        require!(amount > 0, crate::errors::GameError::InsufficientTreasure);
+        require!(
+            amount < self.current_treasure,
+            crate::errors::GameError::InsufficientTreasure
+        );
+        let remaining = self.current_treasure - amount;
+        require!(
+            remaining >= self.bet_amount,
+            crate::errors::GameError::InsufficientTreasure
+        );
+        self.current_treasure = remaining;
+        self.total_withdrawn = self
+            .total_withdrawn
+            .checked_add(amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Records the VRF request the next play_round_vrf must resolve
+    /// The round has to be requested after the session's last activity, and only one can
+    /// be pending, so a result is never chosen after it is known
+    pub fn record_vrf_request(&mut self, vrf: Pubkey, request_slot: u64) -> Result<()> {
+        self.ensure_no_pending_vrf()?;
+        require!(
+            request_slot > self.last_active_slot,
+            crate::errors::GameError::StaleVrfRequest
+        );
+        self.pending_vrf = Some(vrf);
+        self.vrf_request_slot = request_slot;
+        Ok(())
+    }
+
+    /// Clears the pending request, which must be `vrf`'s round at `request_slot`
+    pub fn take_vrf_request(&mut self, vrf: &Pubkey, request_slot: u64) -> Result<()> {
+        require!(
+            self.pending_vrf == Some(*vrf) && self.vrf_request_slot == request_slot,
+            crate::errors::GameError::StaleVrfRequest
+        );
+        self.pending_vrf = None;
+        self.vrf_request_slot = 0;
+        Ok(())
+    }
+
+    /// Blocks exits and purchases while a VRF result the player may already see is unplayed
+    pub fn ensure_no_pending_vrf(&self) -> Result<()> {
+        require!(
+            self.pending_vrf.is_none(),
+            crate::errors::GameError::VrfRequestPending
+        );
+        Ok(())
+    }
+
+    /// Marks the session as Lost and validates the state transition
+    pub fn mark_lost(&mut self) -> Result<()> {
+        self.ensure_active()?;
+        self.status = SessionStatus::Lost;
         Ok(())
     }
 
@@ -156,38 +1016,269 @@ impl GameSession {
         Ok(())
     }
 }
+#[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct UserStats {
+    pub user: Pubkey,
+    pub total_bet: u64,
+    pub total_won: u64,  // Sum of cash out payouts, including partial withdrawals
+    pub total_lost: u64, // Sum of bets on lost sessions
+    pub sessions_played: u64,
+    pub best_dive: u16,
+    pub best_payout: u64,
+    pub session_counter: u64, // Next session_index start_session will accept
+    pub active_session_count: u16, // Sessions started and not yet closed
+    pub bonus_locked_lamports: u64, // Granted by grant_bonus, paid by claim_bonus once wagered
+    pub bonus_house_vault: Pubkey, // Vault holding the locked bonus
+    pub bump: u8,
+}
+impl UserStats {
+    /// Consumes the next session index; anything else is a stale or replayed index
+    pub fn claim_session_index(&mut self, session_index: u64) -> Result<()> {
+        require!(
+            session_index == self.session_counter,
+            crate::errors::GameError::StaleSessionIndex
+        );
+        self.session_counter = self
+            .session_counter
+            .checked_add(1)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Locks a bonus funded by `house_vault`; bonuses from several vaults can't be mixed
+    pub fn grant_bonus(&mut self, house_vault: Pubkey, amount: u64) -> Result<()> {
+        require!(amount > 0, crate::errors::GameError::InvalidDepositAmount);
+        require!(
+            self.bonus_locked_lamports == 0 || self.bonus_house_vault == house_vault,
+            crate::errors::GameError::BonusVaultMismatch
+        );
+        self.bonus_locked_lamports = self
+            .bonus_locked_lamports
+            .checked_add(amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        self.bonus_house_vault = house_vault;
+        Ok(())
+    }
+
+    /// Releases the whole locked bonus once total_bet reaches `wagering_multiple` times it
+    pub fn unlock_bonus(&mut self, wagering_multiple: u16) -> Result<u64> {
+        require!(
+            self.bonus_locked_lamports > 0,
+            crate::errors::GameError::NoBonusLocked
+        );
+        let required = self.bonus_locked_lamports as u128 * wagering_multiple as u128;
+        require!(
+            self.total_bet as u128 >= required,
+            crate::errors::GameError::BonusWageringNotMet
+        );
+        Ok(std::mem::take(&mut self.bonus_locked_lamports))
+    }
+
+    /// Counts a newly started session
+    pub fn record_bet(&mut self, bet_amount: u64) -> Result<()> {
+        self.total_bet = self
+            .total_bet
+            .checked_add(bet_amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        self.sessions_played = self
+            .sessions_played
+            .checked_add(1)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Counts a session the house paid the bet for; total_bet only tracks the user's own stake,
+    /// so bonus wagering can't be met with house money
+    pub fn record_house_funded_session(&mut self) -> Result<()> {
+        self.sessions_played = self
+            .sessions_played
+            .checked_add(1)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Counts a newly started session against `limit` open sessions (0 = unlimited)
+    pub fn open_session(&mut self, limit: u16) -> Result<()> {
+        require!(
+            limit == 0 || self.active_session_count < limit,
+            crate::errors::GameError::TooManyActiveSessions
+        );
+        self.active_session_count = self
+            .active_session_count
+            .checked_add(1)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Counts a session closing; sessions started before the counter existed saturate at 0
+    pub fn close_session(&mut self) {
+        self.active_session_count = self.active_session_count.saturating_sub(1);
+    }
+
+    /// Adds lamports staked on an already counted session
+    pub fn record_bet_increase(&mut self, amount: u64) -> Result<()> {
+        self.total_bet = self
+            .total_bet
+            .checked_add(amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Records a session's final payout; `withdrawn` is what its partial cash outs already
+    /// added to total_won, so best_payout covers the whole session
+    /// best_dive and best_payout only move when exceeded
+    pub fn record_win(&mut self, payout: u64, withdrawn: u64, dive_number: u16) -> Result<()> {
+        self.record_withdrawal(payout)?;
+        self.best_dive = self.best_dive.max(dive_number);
+        self.best_payout = self.best_payout.max(payout.saturating_add(withdrawn));
+        Ok(())
+    }
+
+    /// Records a partial cash out, which counts as won even if the session is lost later
+    pub fn record_withdrawal(&mut self, amount: u64) -> Result<()> {
+        self.total_won = self
+            .total_won
+            .checked_add(amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Records a lost session's bet
+    pub fn record_loss(&mut self, bet_amount: u64) -> Result<()> {
+        self.total_lost = self
+            .total_lost
+            .checked_add(bet_amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+}
+/// One cash out on a Leaderboard; unused slots are all zero
+#[derive(
+    AnchorDeserialize, AnchorSerialize, Clone, Copy, Default, PartialEq, Eq, InitSpace, Debug,
+)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+pub struct LeaderboardEntry {
+    pub user: Pubkey,
+    pub payout: u64, // Total paid for the session, including partial cash outs
+    pub dive_number: u16,
+}
+/// Biggest payouts of a house vault, highest first
+#[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct Leaderboard {
+    pub house_vault: Pubkey,
+    pub entries: [LeaderboardEntry; LEADERBOARD_SIZE],
+    pub bump: u8,
+}
+impl Leaderboard {
+    /// Inserts a payout if it beats the lowest entry, evicting that entry
+    /// Ties keep the earlier entry ahead; returns whether the payout was ranked
+    pub fn record(&mut self, user: Pubkey, payout: u64, dive_number: u16) -> bool {
+        let last = LEADERBOARD_SIZE - 1;
+        if payout <= self.entries[last].payout {
+            return false;
+        }
+        self.entries[last] = LeaderboardEntry {
+            user,
+            payout,
+            dive_number,
+        };
+        let mut i = last;
+        while i > 0 && self.entries[i - 1].payout < payout {
+            self.entries.swap(i - 1, i);
+            i -= 1;
+        }
+        true
+    }
+}
+/// Aggregate house performance, one per house vault
+#[account]
+#[derive(InitSpace)]
+#[cfg_attr(feature = "tsify", derive(Tsify, Serialize, Deserialize))]
+#[cfg_attr(feature = "tsify", tsify(into_wasm_abi, from_wasm_abi))]
+pub struct HouseStats {
+    pub house_vault: Pubkey,
+    pub total_volume: u64,   // Sum of all bets
+    pub total_paid_out: u64, // Sum of all payouts, including partial cash outs
+    pub total_sessions: u64,
+    pub total_losses: u64,         // Number of sessions the player lost
+    pub net_profit_lamports: i128, // total_volume - total_paid_out, negative when the house is down
+    pub bump: u8,
+}
+impl HouseStats {
+    /// Counts a newly started session and its bet
+    pub fn record_session_start(&mut self, bet_amount: u64) -> Result<()> {
+        self.total_volume = self
+            .total_volume
+            .checked_add(bet_amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        self.total_sessions = self
+            .total_sessions
+            .checked_add(1)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        self.net_profit_lamports = self
+            .net_profit_lamports
+            .checked_add(bet_amount as i128)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Books extra volume for an already counted session
+    pub fn record_bet_increase(&mut self, amount: u64) -> Result<()> {
+        self.total_volume = self
+            .total_volume
+            .checked_add(amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        self.net_profit_lamports = self
+            .net_profit_lamports
+            .checked_add(amount as i128)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Records lamports paid from the vault to a player
+    pub fn record_payout(&mut self, amount: u64) -> Result<()> {
+        self.total_paid_out = self
+            .total_paid_out
+            .checked_add(amount)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        self.net_profit_lamports = self
+            .net_profit_lamports
+            .checked_sub(amount as i128)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+
+    /// Counts a session the player lost; the bet was already booked at start
+    pub fn record_loss(&mut self) -> Result<()> {
+        self.total_losses = self
+            .total_losses
+            .checked_add(1)
+            .ok_or(crate::errors::GameError::Overflow)?;
+        Ok(())
+    }
+}
 #[cfg(test)]
 mod tests {
     use super::*;
 
     fn test_config() -> GameConfig {
-        let (base, decay, min, num, den, max_mult, max_dives, fixed_bet) =
-            GameConfig::default_config();
-        GameConfig {
-            admin: Pubkey::default(),
-            base_survival_ppm: base,
-            decay_per_dive_ppm: decay,
-            min_survival_ppm: min,
-            treasure_multiplier_num: num,
-            treasure_multiplier_den: den,
-            max_payout_multiplier: max_mult,
-            max_dives,
-            fixed_bet,
-            bump: 0,
-        }
+        crate::test_utils::test_config(5)
     }
 
     fn test_session() -> GameSession {
         GameSession {
-            user: Pubkey::default(),
-            house_vault: Pubkey::default(),
-            status: SessionStatus::Active,
             bet_amount: 1_000_000,
             current_treasure: 1_000_000,
             max_payout: 100_000_000,
+            reserved_payout: 100_000_000,
             dive_number: 1,
-            bump: 0,
-            last_active_slot: 0,
+            ..GameSession::blank()
         }
     }
 
@@ -197,74 +1288,510 @@ mod tests {
             game_keeper: Pubkey::default(),
             total_reserved: 0,
             locked: false,
+            pending_authority: None,
+            strict_accounting: false,
+            version: VAULT_VERSION,
+            jackpot_pool: 0,
+            sequence: 0,
+            pending_withdraw_amount: 0,
+            withdraw_unlock_slot: 0,
+            cosigner: None,
+            active_sessions: 0,
+            bonus_pool: 0,
+            authorized_crank: None,
+            consecutive_big_payouts: 0,
             bump: 0,
         }
     }
 
-    // GameConfig::validate() tests
+    // GameConfig::validate() tests
+    #[test]
+    fn test_validate_valid_config() {
+        let config = test_config();
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_zero_treasure_denominator() {
+        let mut config = test_config();
+        config.treasure_multiplier_den = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_zero_treasure_numerator() {
+        let mut config = test_config();
+        config.treasure_multiplier_num = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_non_growing_geometric_curve() {
+        let mut config = test_config();
+        // Deflationary: 0.9x per dive
+        config.treasure_multiplier_num = 9;
+        config.treasure_multiplier_den = 10;
+        assert!(config.validate().is_err());
+        // Flat
+        config.treasure_multiplier_num = 10;
+        assert!(config.validate().is_err());
+        config.treasure_multiplier_num = 11;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_linear_curve_ignores_multiplier() {
+        let mut config = test_config();
+        config.curve_mode = CurveMode::Linear;
+        config.linear_step_bps = 500_000;
+        config.treasure_multiplier_num = 9;
+        config.treasure_multiplier_den = 10;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_zero_max_payout_multiplier() {
+        let mut config = test_config();
+        config.max_payout_multiplier = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_base_survival_exceeds_100_percent() {
+        let mut config = test_config();
+        config.base_survival_ppm = 1_000_001;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_min_survival_exceeds_base() {
+        let mut config = test_config();
+        config.min_survival_ppm = config.base_survival_ppm + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_house_edge_bounds() {
+        let mut config = test_config();
+        config.house_edge_bps = 1_000_000;
+        assert!(config.validate().is_ok());
+        config.house_edge_bps = 1_000_001;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_decay_larger_than_base() {
+        let mut config = test_config();
+        config.decay_per_dive_ppm = config.base_survival_ppm;
+        assert!(config.validate().is_ok());
+        config.decay_per_dive_ppm = config.base_survival_ppm + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_zero_decay() {
+        let mut config = test_config();
+        config.decay_per_dive_ppm = 0;
+        assert!(config.validate().is_err());
+        // A single-dive game never decays
+        config.max_dives = 1;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_relaxed_coverage_needs_20_percent() {
+        let config = test_config();
+        assert!(!config.require_full_coverage);
+        assert_eq!(config.required_coverage(1_000_000_000), 200_000_000);
+        assert_eq!(config.required_coverage(4), 0);
+    }
+
+    #[test]
+    fn test_full_coverage_needs_entire_max_payout() {
+        let mut config = test_config();
+        config.require_full_coverage = true;
+        assert_eq!(config.required_coverage(1_000_000_000), 1_000_000_000);
+
+        // A vault one lamport short passes demo mode but not full coverage
+        let available = 999_999_999;
+        assert!(available >= test_config().required_coverage(1_000_000_000));
+        assert!(available < config.required_coverage(1_000_000_000));
+    }
+
+    #[test]
+    fn test_admin_handoff() {
+        let mut config = test_config();
+        let new_admin = Pubkey::new_unique();
+        config.propose_admin(new_admin).unwrap();
+        assert_eq!(config.admin, Pubkey::default());
+
+        config.accept_admin(new_admin).unwrap();
+        assert_eq!(config.admin, new_admin);
+        assert_eq!(config.pending_admin, None);
+    }
+
+    #[test]
+    fn test_stray_admin_accept_is_rejected() {
+        let mut config = test_config();
+        let err = config.accept_admin(Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::NotPendingAdmin.into());
+
+        config.propose_admin(Pubkey::new_unique()).unwrap();
+        let err = config.accept_admin(Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::NotPendingAdmin.into());
+        assert_eq!(config.admin, Pubkey::default());
+    }
+
+    #[test]
+    fn test_paused_config_blocks_play() {
+        let mut config = test_config();
+        assert!(config.ensure_not_paused().is_ok());
+        config.paused = true;
+        let err = config.ensure_not_paused().unwrap_err();
+        assert_eq!(err, crate::errors::GameError::GamePaused.into());
+        // Pausing is not a config error, so updates can still be validated
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_cash_out_min_dives_threshold() {
+        let mut config = test_config();
+        config.min_dives_to_cash_out = 3;
+        let err = config.ensure_min_dives(2).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::MinDivesNotReached.into());
+        assert!(config.ensure_min_dives(3).is_ok());
+        assert!(config.ensure_min_dives(4).is_ok());
+    }
+
+    #[test]
+    fn test_validate_min_dives_to_cash_out_within_max_dives() {
+        let mut config = test_config();
+        config.min_dives_to_cash_out = config.max_dives;
+        assert!(config.validate().is_ok());
+        config.min_dives_to_cash_out = config.max_dives + 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_first_dive_guaranteed_needs_a_real_roll_before_cash_out() {
+        let mut config = test_config();
+        config.first_dive_guaranteed = true;
+        config.min_dives_to_cash_out = 2;
+        assert!(config.validate().is_err());
+        config.min_dives_to_cash_out = 3;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_max_payout_lamports_above_min_bet() {
+        let mut config = test_config();
+        config.max_payout_lamports = config.min_bet;
+        assert!(config.validate().is_err());
+        config.max_payout_lamports = config.min_bet + 1;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_final_gamble_odds() {
+        let mut config = test_config();
+        config.final_gamble_bps = 1_000_000;
+        assert!(config.validate().is_ok());
+        config.final_gamble_bps = 1_000_001;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_rake_must_leave_a_bet() {
+        let mut config = test_config();
+        config.rake_bps = 999_999;
+        assert!(config.validate().is_ok());
+        config.rake_bps = 1_000_000;
+        assert!(config.validate().is_err());
+        config.rake_bps = 600_000;
+        config.jackpot_bps = 400_000;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_zero_timeout_slots() {
+        let mut config = test_config();
+        config.timeout_slots = 0;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_difficulty_zero_keeps_original_config_address() {
+        let pda = |difficulty: u8| {
+            Pubkey::find_program_address(
+                &[GAME_CONFIG_SEED.as_bytes(), difficulty_seed(&difficulty)],
+                &crate::ID,
+            )
+            .0
+        };
+        let (original, _) =
+            Pubkey::find_program_address(&[GAME_CONFIG_SEED.as_bytes()], &crate::ID);
+        assert_eq!(pda(0), original);
+        assert_ne!(pda(1), original);
+        assert_ne!(pda(1), pda(2));
+    }
+
+    #[test]
+    fn test_difficulties_have_independent_survival_curves() {
+        let easy = GameConfig {
+            difficulty: 1,
+            base_survival_ppm: 990_000,
+            ..test_config()
+        };
+        let hard = GameConfig {
+            difficulty: 2,
+            base_survival_ppm: 700_000,
+            ..test_config()
+        };
+        for dive in [1, 5, 20] {
+            assert!(
+                crate::game_math::survival_probability_bps(&easy, dive)
+                    > crate::game_math::survival_probability_bps(&hard, dive)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ensure_dive_matching() {
+        let mut session = test_session();
+        session.dive_number = 3;
+        assert!(session.ensure_dive(3).is_ok());
+    }
+
+    #[test]
+    fn test_first_dive_waits_min_slots_before_play() {
+        let mut session = test_session();
+        session.last_active_slot = 100;
+        let err = session.ensure_play_delay(101, 2).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::PlayedTooSoon.into());
+        assert!(session.ensure_play_delay(102, 2).is_ok());
+        // Off by default
+        assert!(session.ensure_play_delay(100, 0).is_ok());
+
+        // Later dives aren't delayed
+        session.dive_number = 2;
+        assert!(session.ensure_play_delay(100, 2).is_ok());
+    }
+
+    #[test]
+    fn test_ensure_dive_mismatching() {
+        let mut session = test_session();
+        session.dive_number = 3;
+        for stale in [2, 4] {
+            let err = session.ensure_dive(stale).unwrap_err();
+            assert_eq!(err, crate::errors::GameError::RoundMismatch.into());
+        }
+    }
+
+    #[test]
+    fn test_min_payout_boundaries() {
+        let mut session = test_session();
+        session.current_treasure = 50_000_000;
+        assert!(session.ensure_min_payout(0).is_ok());
+        assert!(session.ensure_min_payout(50_000_000).is_ok());
+        let err = session.ensure_min_payout(50_000_001).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::PayoutBelowMinimum.into());
+    }
+
+    #[test]
+    fn test_session_within_configured_timeout_is_not_expired() {
+        let mut session = test_session();
+        session.record_activity(1_000, 100);
+        let err = session.expired_slots(1_100).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::SessionNotExpired.into());
+        assert_eq!(session.expired_slots(1_101).unwrap(), 101);
+
+        // A longer window keeps the same session alive
+        session.record_activity(1_000, 9_000);
+        assert!(session.expired_slots(1_101).is_err());
+        assert!(session.expired_slots(999).is_err());
+    }
+
+    #[test]
+    fn test_config_timeout_change_does_not_move_deadline() {
+        let mut config = test_config();
+        config.timeout_slots = 100;
+        let mut session = test_session();
+        session.record_activity(1_000, config.timeout_slots);
+
+        // The deadline was fixed from the timeout in force at the last activity
+        config.timeout_slots = 9_000;
+        assert_eq!(session.expires_at_slot, 1_100);
+        assert_eq!(session.expired_slots(1_101).unwrap(), 101);
+
+        // Only the next activity picks up the new timeout
+        session.record_activity(1_200, config.timeout_slots);
+        assert_eq!(session.expires_at_slot, 10_200);
+    }
+
+    #[test]
+    fn test_validate_max_exposure_bounds() {
+        let mut config = test_config();
+        config.max_exposure_bps = 0;
+        assert!(config.validate().is_err());
+        config.max_exposure_bps = 1_000_001;
+        assert!(config.validate().is_err());
+        config.max_exposure_bps = 500_000;
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
-    fn test_validate_valid_config() {
-        let config = test_config();
+    fn test_vault_bet_cap_scales_with_balance() {
+        let mut config = test_config();
+        config.max_payout_multiplier = 100;
+        // 10% of the vault may back a max payout, so a 100 SOL vault takes 0.1 SOL bets
+        config.max_bet_vault_bps = 100_000;
+        let bet = 200_000_000;
+        assert_eq!(config.vault_max_bet(100_000_000_000), Some(100_000_000));
+        let err = config
+            .validate_bet_vault_cap(bet, 100_000_000_000)
+            .unwrap_err();
+        assert_eq!(err, crate::errors::GameError::BetExceedsVaultCap.into());
+        // The same bet fits once the vault doubles
+        assert!(config.validate_bet_vault_cap(bet, 200_000_000_000).is_ok());
+        // And stops fitting again as liquidity drains
+        assert!(config.validate_bet_vault_cap(bet, 150_000_000_000).is_err());
+    }
+
+    #[test]
+    fn test_validate_usd_denominated_needs_price_feed() {
+        let mut config = test_config();
+        config.usd_denominated = true;
+        assert!(config.validate().is_err());
+        config.price_feed = Pubkey::new_unique();
         assert!(config.validate().is_ok());
+        config.bet_step = 1_000;
+        assert!(config.validate().is_err());
     }
 
     #[test]
-    fn test_validate_zero_treasure_denominator() {
+    fn test_vault_bet_cap_disabled_by_default() {
         let mut config = test_config();
-        config.treasure_multiplier_den = 0;
+        assert_eq!(config.vault_max_bet(0), None);
+        assert!(config.validate_bet_vault_cap(u64::MAX, 0).is_ok());
+        config.max_bet_vault_bps = 1_000_001;
         assert!(config.validate().is_err());
     }
 
     #[test]
-    fn test_validate_zero_treasure_numerator() {
+    fn test_validate_referral_fee_bounds() {
         let mut config = test_config();
-        config.treasure_multiplier_num = 0;
+        config.referral_fee_bps = 1_000_000;
+        assert!(config.validate().is_ok());
+        config.referral_fee_bps = 1_000_001;
         assert!(config.validate().is_err());
     }
 
     #[test]
-    fn test_validate_zero_max_payout_multiplier() {
+    fn test_validate_linear_curve_requires_step() {
         let mut config = test_config();
-        config.max_payout_multiplier = 0;
+        config.curve_mode = CurveMode::Linear;
         assert!(config.validate().is_err());
+        config.linear_step_bps = 500_000;
+        assert!(config.validate().is_ok());
     }
 
     #[test]
-    fn test_validate_base_survival_exceeds_100_percent() {
+    fn test_validate_zero_max_dives() {
         let mut config = test_config();
-        config.base_survival_ppm = 1_000_001;
+        config.max_dives = 0;
         assert!(config.validate().is_err());
     }
 
     #[test]
-    fn test_validate_min_survival_exceeds_base() {
+    fn test_validate_min_bet_positive() {
         let mut config = test_config();
-        config.min_survival_ppm = config.base_survival_ppm + 1;
+        config.min_bet = 10_000_000; // 0.01 SOL
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_min_bet_zero_is_invalid() {
+        let mut config = test_config();
+        config.min_bet = 0; // Zero bet is invalid - must be positive
         assert!(config.validate().is_err());
     }
 
     #[test]
-    fn test_validate_zero_max_dives() {
+    fn test_validate_min_bet_above_max_bet_is_invalid() {
         let mut config = test_config();
-        config.max_dives = 0;
+        config.min_bet = config.max_bet + 1;
         assert!(config.validate().is_err());
     }
 
     #[test]
-    fn test_validate_fixed_bet_positive() {
+    fn test_validate_equal_bet_bounds() {
+        let mut config = test_config();
+        config.max_bet = config.min_bet;
+        assert!(config.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_bet_within_bounds() {
+        let config = test_config();
+        assert!(config.validate_bet(config.min_bet).is_ok());
+        assert!(config.validate_bet(config.max_bet).is_ok());
+    }
+
+    #[test]
+    fn test_validate_bet_outside_bounds() {
+        let config = test_config();
+        assert!(config.validate_bet(config.min_bet - 1).is_err());
+        assert!(config.validate_bet(config.max_bet + 1).is_err());
+        assert!(config.validate_bet(0).is_err());
+    }
+
+    #[test]
+    fn test_validate_bet_step() {
+        let mut config = test_config();
+        config.bet_step = 10_000_000;
+        assert!(config.validate().is_ok());
+        assert!(config.validate_bet(config.min_bet).is_ok());
+        assert!(config.validate_bet(50_000_000).is_ok());
+        let err = config.validate_bet(55_000_000).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::InvalidBetAmount.into());
+    }
+
+    #[test]
+    fn test_validate_bet_step_needs_a_multiple_in_range() {
         let mut config = test_config();
-        config.fixed_bet = 10_000_000; // 0.01 SOL
+        config.min_bet = 15_000_000;
+        config.max_bet = 18_000_000;
+        config.bet_step = 10_000_000;
+        assert!(config.validate().is_err());
+        config.max_bet = 20_000_000;
         assert!(config.validate().is_ok());
     }
 
     #[test]
-    fn test_validate_fixed_bet_zero_is_invalid() {
+    fn test_validate_bet_whitelist() {
         let mut config = test_config();
-        config.fixed_bet = 0; // Zero bet is invalid - must be positive
+        config.use_bet_whitelist = true;
+        config.allowed_bets = [50_000_000, 100_000_000, 200_000_000, 0, 0, 0, 0, 0];
+        assert!(config.validate().is_ok());
+        assert!(config.validate_bet(100_000_000).is_ok());
+        // In range but not listed
+        assert!(config.validate_bet(75_000_000).is_err());
+        // Unused slots don't admit zero
+        assert!(config.validate_bet(0).is_err());
+
+        config.allowed_bets = [0; 8];
         assert!(config.validate().is_err());
     }
 
     // GameSession tests
+    #[test]
+    fn test_blank_session_is_active_at_current_version() {
+        let session = GameSession::blank();
+        assert_eq!(session.version, SESSION_VERSION);
+        assert_eq!(session.status, SessionStatus::Active);
+        assert_eq!(session.dive_number, 0);
+    }
+
     #[test]
     fn test_ensure_active_when_active() {
         let session = test_session();
@@ -327,6 +1854,103 @@ mod tests {
         assert!(session.mark_cashed_out().is_err());
     }
 
+    // Commit-reveal tests
+    #[test]
+    fn test_commit_then_reveal_succeeds() {
+        let mut session = test_session();
+        session.set_commitment(crate::rng::commitment_for(42), 10);
+        assert!(session.consume_commitment(42, 11).is_ok());
+        assert_eq!(session.pending_commitment, [0u8; 32]);
+    }
+
+    #[test]
+    fn test_reveal_with_wrong_preimage_fails() {
+        let mut session = test_session();
+        session.set_commitment(crate::rng::commitment_for(42), 10);
+        assert!(session.consume_commitment(43, 11).is_err());
+    }
+
+    #[test]
+    fn test_reveal_without_commit_fails() {
+        let mut session = test_session();
+        assert!(session.consume_commitment(42, 11).is_err());
+    }
+
+    #[test]
+    fn test_reveal_in_commit_slot_fails() {
+        let mut session = test_session();
+        session.set_commitment(crate::rng::commitment_for(42), 10);
+        assert!(session.consume_commitment(42, 10).is_err());
+    }
+
+    #[test]
+    fn test_first_revealed_seed_is_kept() {
+        let mut session = test_session();
+        assert_eq!(session.server_seed, None);
+        session.set_commitment(crate::rng::commitment_for(42), 10);
+        session.consume_commitment(42, 11).unwrap();
+        session.set_commitment(crate::rng::commitment_for(7), 12);
+        session.consume_commitment(7, 13).unwrap();
+        assert_eq!(session.server_seed, Some(7));
+    }
+
+    #[test]
+    fn test_commitment_cannot_be_reused() {
+        let mut session = test_session();
+        session.set_commitment(crate::rng::commitment_for(42), 10);
+        assert!(session.consume_commitment(42, 11).is_ok());
+        assert!(session.consume_commitment(42, 12).is_err());
+    }
+
+    // Partial cash out tests
+    #[test]
+    fn test_partial_cash_out_keeps_session_active() {
+        let mut session = test_session();
+        session.current_treasure = 3_000_000;
+        assert!(session.apply_partial_cash_out(1_000_000).is_ok());
+        assert_eq!(session.status, SessionStatus::Active);
+        assert_eq!(session.current_treasure, 2_000_000);
+        assert_eq!(session.total_withdrawn, 1_000_000);
+    }
+
+    #[test]
+    fn test_partial_cash_out_rejects_full_amount() {
+        let mut session = test_session();
+        session.current_treasure = 3_000_000;
+        assert!(session.apply_partial_cash_out(3_000_000).is_err());
+        assert!(session.apply_partial_cash_out(0).is_err());
+    }
+
+    #[test]
+    fn test_partial_cash_out_rejects_dropping_below_bet() {
+        let mut session = test_session();
+        session.current_treasure = 1_500_000;
+        assert!(session.apply_partial_cash_out(500_001).is_err());
+        assert!(session.apply_partial_cash_out(500_000).is_ok());
+        assert_eq!(session.current_treasure, session.bet_amount);
+    }
+
+    #[test]
+    fn test_partial_cash_out_reservation_stays_consistent() {
+        let mut session = test_session();
+        let mut vault = test_vault();
+        vault.reserve(session.max_payout).unwrap();
+        session.current_treasure = 5_000_000;
+
+        session.apply_partial_cash_out(2_000_000).unwrap();
+        vault.release(2_000_000).unwrap();
+        assert_eq!(vault.total_reserved, session.reserved_amount());
+
+        session.current_treasure = 6_000_000;
+        session.apply_partial_cash_out(1_000_000).unwrap();
+        vault.release(1_000_000).unwrap();
+        assert_eq!(vault.total_reserved, session.reserved_amount());
+
+        // Closing the session releases exactly what is left
+        vault.release(session.reserved_amount()).unwrap();
+        assert_eq!(vault.total_reserved, 0);
+    }
+
     // HouseVault tests
     #[test]
     fn test_reserve_success() {
@@ -384,6 +2008,266 @@ mod tests {
         assert_eq!(vault.total_reserved, 0);
     }
 
+    #[test]
+    fn test_jackpot_pool_is_committed_until_taken() {
+        let mut vault = test_vault();
+        vault.total_reserved = 1_000;
+        vault.add_to_jackpot(400).unwrap();
+        vault.add_to_jackpot(100).unwrap();
+        assert_eq!(vault.committed(), 1_500);
+        assert_eq!(vault.withdrawable(10_000, 100), 8_400);
+        assert_eq!(vault.take_jackpot(), 500);
+        assert_eq!(vault.take_jackpot(), 0);
+        assert_eq!(vault.withdrawable(10_000, 100), 8_900);
+        vault.jackpot_pool = u64::MAX;
+        assert!(vault.add_to_jackpot(1).is_err());
+    }
+
+    #[test]
+    fn test_strict_release_within_reserved() {
+        let mut vault = test_vault();
+        vault.strict_accounting = true;
+        vault.total_reserved = 1000;
+        assert!(vault.release(1000).is_ok());
+        assert_eq!(vault.total_reserved, 0);
+    }
+
+    #[test]
+    fn test_strict_release_underflow_fails() {
+        let mut vault = test_vault();
+        vault.strict_accounting = true;
+        vault.total_reserved = 500;
+        let err = vault.release(1000).err().unwrap();
+        assert_eq!(err, crate::errors::GameError::Overflow.into());
+        assert_eq!(vault.total_reserved, 500);
+    }
+
+    #[test]
+    fn test_set_game_keeper_rotates_keeper() {
+        let mut vault = test_vault();
+        let keeper = Pubkey::new_unique();
+        assert!(vault.set_game_keeper(keeper).is_ok());
+        assert_eq!(vault.game_keeper, keeper);
+    }
+
+    #[test]
+    fn test_set_game_keeper_rejects_default_key() {
+        let mut vault = test_vault();
+        let keeper = Pubkey::new_unique();
+        vault.set_game_keeper(keeper).unwrap();
+        assert!(vault.set_game_keeper(Pubkey::default()).is_err());
+        assert_eq!(vault.game_keeper, keeper);
+    }
+
+    fn test_user_stats() -> UserStats {
+        UserStats {
+            user: Pubkey::default(),
+            total_bet: 0,
+            total_won: 0,
+            total_lost: 0,
+            sessions_played: 0,
+            best_dive: 0,
+            best_payout: 0,
+            session_counter: 0,
+            active_session_count: 0,
+            bonus_locked_lamports: 0,
+            bonus_house_vault: Pubkey::default(),
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_session_index_must_match_counter() {
+        let mut stats = test_user_stats();
+        stats.claim_session_index(0).unwrap();
+        stats.claim_session_index(1).unwrap();
+        assert_eq!(stats.session_counter, 2);
+
+        // Reusing a prior index is rejected once the counter has advanced
+        for stale in [0, 1, 3] {
+            let err = stats.claim_session_index(stale).unwrap_err();
+            assert_eq!(err, crate::errors::GameError::StaleSessionIndex.into());
+        }
+        assert_eq!(stats.session_counter, 2);
+    }
+
+    #[test]
+    fn test_active_session_limit_is_hit_and_cleared() {
+        let mut stats = test_user_stats();
+        stats.open_session(2).unwrap();
+        stats.open_session(2).unwrap();
+        let err = stats.open_session(2).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::TooManyActiveSessions.into());
+        assert_eq!(stats.active_session_count, 2);
+
+        // Closing a session frees a slot
+        stats.close_session();
+        assert!(stats.open_session(2).is_ok());
+
+        // 0 means no limit
+        assert!(stats.open_session(0).is_ok());
+        assert_eq!(stats.active_session_count, 3);
+    }
+
+    #[test]
+    fn test_close_session_saturates_for_uncounted_sessions() {
+        let mut stats = test_user_stats();
+        stats.close_session();
+        assert_eq!(stats.active_session_count, 0);
+    }
+
+    #[test]
+    fn test_user_stats_win_increments_total_won() {
+        let mut stats = test_user_stats();
+        stats.record_bet(1_000_000).unwrap();
+        stats.record_win(3_610_000, 0, 3).unwrap();
+        assert_eq!(stats.sessions_played, 1);
+        assert_eq!(stats.total_bet, 1_000_000);
+        assert_eq!(stats.total_won, 3_610_000);
+        assert_eq!(stats.best_dive, 3);
+        assert_eq!(stats.best_payout, 3_610_000);
+
+        stats.record_win(1_900_000, 0, 2).unwrap();
+        assert_eq!(stats.total_won, 5_510_000);
+    }
+
+    #[test]
+    fn test_partial_withdrawals_count_once() {
+        let mut stats = test_user_stats();
+        stats.record_withdrawal(1_000_000).unwrap();
+        assert_eq!(stats.total_won, 1_000_000);
+        assert_eq!(stats.best_payout, 0);
+
+        stats.record_win(2_610_000, 1_000_000, 3).unwrap();
+        assert_eq!(stats.total_won, 3_610_000);
+        assert_eq!(stats.best_payout, 3_610_000);
+    }
+
+    #[test]
+    fn test_house_funded_session_is_not_wagered() {
+        let mut stats = test_user_stats();
+        stats.record_house_funded_session().unwrap();
+        assert_eq!(stats.sessions_played, 1);
+        assert_eq!(stats.total_bet, 0);
+    }
+
+    #[test]
+    fn test_user_stats_best_dive_only_updates_when_exceeded() {
+        let mut stats = test_user_stats();
+        stats.record_win(6_859_000, 0, 4).unwrap();
+        stats.record_win(1_900_000, 0, 2).unwrap();
+        assert_eq!(stats.best_dive, 4);
+        assert_eq!(stats.best_payout, 6_859_000);
+
+        stats.record_win(13_032_100, 0, 5).unwrap();
+        assert_eq!(stats.best_dive, 5);
+        assert_eq!(stats.best_payout, 13_032_100);
+    }
+
+    #[test]
+    fn test_user_stats_loss_leaves_wins_untouched() {
+        let mut stats = test_user_stats();
+        stats.record_bet(1_000_000).unwrap();
+        stats.record_loss(1_000_000).unwrap();
+        assert_eq!(stats.total_lost, 1_000_000);
+        assert_eq!(stats.total_won, 0);
+        assert_eq!(stats.best_dive, 0);
+    }
+
+    fn test_house_stats() -> HouseStats {
+        HouseStats {
+            house_vault: Pubkey::default(),
+            total_volume: 0,
+            total_paid_out: 0,
+            total_sessions: 0,
+            total_losses: 0,
+            net_profit_lamports: 0,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn test_house_stats_losing_player_is_house_profit() {
+        let mut stats = test_house_stats();
+        stats.record_session_start(1_000_000).unwrap();
+        stats.record_loss().unwrap();
+        assert_eq!(stats.total_volume, 1_000_000);
+        assert_eq!(stats.total_sessions, 1);
+        assert_eq!(stats.total_losses, 1);
+        assert_eq!(stats.total_paid_out, 0);
+        assert_eq!(stats.net_profit_lamports, 1_000_000);
+    }
+
+    #[test]
+    fn test_house_stats_net_profit_goes_negative() {
+        let mut stats = test_house_stats();
+        stats.record_session_start(1_000_000).unwrap();
+        stats.record_loss().unwrap();
+        stats.record_session_start(1_000_000).unwrap();
+        stats.record_payout(3_610_000).unwrap();
+        assert_eq!(stats.total_volume, 2_000_000);
+        assert_eq!(stats.total_paid_out, 3_610_000);
+        assert_eq!(stats.total_losses, 1);
+        assert_eq!(stats.net_profit_lamports, -1_610_000);
+    }
+
+    #[test]
+    fn test_exposure_cap_allows_session_near_cap_then_rejects_next() {
+        let mut vault = test_vault();
+        // 50% exposure of a 1000-lamport vault allows 500 reserved
+        vault.check_exposure(1000, 450, 500_000).unwrap();
+        vault.reserve(450).unwrap();
+
+        let err = vault.check_exposure(1000, 100, 500_000).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::VaultCapacityExceeded.into());
+        vault.check_exposure(1000, 50, 500_000).unwrap();
+    }
+
+    #[test]
+    fn test_full_exposure_caps_at_vault_balance() {
+        let mut vault = test_vault();
+        vault.reserve(600).unwrap();
+        assert!(vault.check_exposure(1000, 400, 1_000_000).is_ok());
+        assert!(vault.check_exposure(1000, 401, 1_000_000).is_err());
+    }
+
+    #[test]
+    fn test_propose_then_accept_authority() {
+        let mut vault = test_vault();
+        let new_authority = Pubkey::new_unique();
+        vault.propose_authority(new_authority).unwrap();
+        assert_eq!(vault.pending_authority, Some(new_authority));
+        assert_eq!(vault.house_authority, Pubkey::default());
+
+        vault.accept_authority(new_authority).unwrap();
+        assert_eq!(vault.house_authority, new_authority);
+        assert_eq!(vault.pending_authority, None);
+    }
+
+    #[test]
+    fn test_unauthorized_accept_is_rejected() {
+        let mut vault = test_vault();
+        let err = vault.accept_authority(Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::NotPendingAuthority.into());
+
+        vault.propose_authority(Pubkey::new_unique()).unwrap();
+        let err = vault.accept_authority(Pubkey::new_unique()).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::NotPendingAuthority.into());
+        assert_eq!(vault.house_authority, Pubkey::default());
+        assert!(vault.propose_authority(Pubkey::default()).is_err());
+    }
+
+    #[test]
+    fn test_withdrawable_excludes_reserved_and_rent() {
+        let mut vault = test_vault();
+        vault.reserve(3_000).unwrap();
+        assert_eq!(vault.withdrawable(10_000, 1_000), 6_000);
+        // Everything above reserved + rent, nothing below
+        assert_eq!(vault.withdrawable(4_000, 1_000), 0);
+        assert_eq!(vault.withdrawable(3_500, 1_000), 0);
+        assert_eq!(vault.withdrawable(0, 1_000), 0);
+    }
+
     #[test]
     fn test_reserve_and_release_cycle() {
         let mut vault = test_vault();
@@ -404,4 +2288,189 @@ mod tests {
         assert!(vault.release(2000).is_ok());
         assert_eq!(vault.total_reserved, 0);
     }
+
+    #[test]
+    fn test_leaderboard_evicts_smallest_and_stays_ordered() {
+        let mut board = Leaderboard {
+            house_vault: Pubkey::default(),
+            entries: [LeaderboardEntry::default(); LEADERBOARD_SIZE],
+            bump: 0,
+        };
+        for payout in 1..=LEADERBOARD_SIZE as u64 {
+            assert!(board.record(Pubkey::new_unique(), payout * 100, 2));
+        }
+        // Not above the current minimum of 100
+        assert!(!board.record(Pubkey::new_unique(), 100, 2));
+
+        let winner = Pubkey::new_unique();
+        assert!(board.record(winner, 550, 7));
+        let payouts: Vec<u64> = board.entries.iter().map(|e| e.payout).collect();
+        assert_eq!(
+            payouts,
+            vec![1000, 900, 800, 700, 600, 550, 500, 400, 300, 200]
+        );
+        assert_eq!(board.entries[5].user, winner);
+        assert_eq!(board.entries[5].dive_number, 7);
+    }
+
+    #[test]
+    fn test_leaderboard_ties_keep_earlier_entry_first() {
+        let mut board = Leaderboard {
+            house_vault: Pubkey::default(),
+            entries: [LeaderboardEntry::default(); LEADERBOARD_SIZE],
+            bump: 0,
+        };
+        let first = Pubkey::new_unique();
+        let second = Pubkey::new_unique();
+        board.record(first, 500, 3);
+        board.record(second, 500, 4);
+        assert_eq!(board.entries[0].user, first);
+        assert_eq!(board.entries[1].user, second);
+        assert_eq!(board.entries[2].payout, 0);
+    }
+
+    #[test]
+    fn test_max_dives_override_stays_within_ceiling() {
+        let mut config = test_config();
+        // Disabled by default
+        assert!(config
+            .validate_max_dives_override(config.max_dives)
+            .is_err());
+
+        config.absolute_max_dives = config.max_dives + 10;
+        assert!(config.validate().is_ok());
+        assert!(config
+            .validate_max_dives_override(config.max_dives + 10)
+            .is_ok());
+        let err = config
+            .validate_max_dives_override(config.max_dives + 11)
+            .unwrap_err();
+        assert_eq!(
+            err,
+            crate::errors::GameError::InvalidMaxDivesOverride.into()
+        );
+        // Overrides only deepen a session
+        assert!(config
+            .validate_max_dives_override(config.max_dives - 1)
+            .is_err());
+
+        config.absolute_max_dives = config.max_dives - 1;
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_withdraw_unlocks_at_unlock_slot() {
+        let mut vault = test_vault();
+        vault.request_withdraw(500, 100).unwrap();
+        let err = vault.take_unlocked_withdraw(99).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::WithdrawalNotUnlocked.into());
+        assert_eq!(vault.take_unlocked_withdraw(100).unwrap(), 500);
+        // Paid requests can't be replayed
+        let err = vault.take_unlocked_withdraw(100).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::NoPendingWithdrawal.into());
+    }
+
+    #[test]
+    fn test_cancel_withdraw_clears_request() {
+        let mut vault = test_vault();
+        assert!(vault.cancel_withdraw().is_err());
+        vault.request_withdraw(500, 100).unwrap();
+        assert_eq!(vault.cancel_withdraw().unwrap(), 500);
+        assert_eq!(vault.pending_withdraw_amount, 0);
+        assert!(vault.take_unlocked_withdraw(200).is_err());
+    }
+
+    #[test]
+    fn test_bonus_unlocks_at_wagering_multiple() {
+        let vault = Pubkey::new_unique();
+        let mut stats = UserStats {
+            total_bet: 0,
+            ..test_user_stats()
+        };
+        assert!(stats.unlock_bonus(5).is_err());
+        stats.grant_bonus(vault, 100).unwrap();
+        stats.grant_bonus(vault, 100).unwrap();
+        assert!(stats.grant_bonus(Pubkey::new_unique(), 100).is_err());
+        stats.total_bet = 999;
+        let err = stats.unlock_bonus(5).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::BonusWageringNotMet.into());
+        assert_eq!(stats.bonus_locked_lamports, 200);
+        stats.total_bet = 1_000;
+        assert_eq!(stats.unlock_bonus(5).unwrap(), 200);
+        assert_eq!(stats.bonus_locked_lamports, 0);
+    }
+
+    #[test]
+    fn test_bonus_pool_is_committed() {
+        let vault = HouseVault {
+            bonus_pool: 300,
+            ..test_vault()
+        };
+        assert_eq!(vault.withdrawable(1_000, 100), 600);
+    }
+
+    #[test]
+    fn test_vault_session_cap_frees_on_close() {
+        let mut vault = test_vault();
+        vault.open_session(2).unwrap();
+        vault.open_session(2).unwrap();
+        let err = vault.open_session(2).unwrap_err();
+        assert_eq!(err, crate::errors::GameError::VaultCapacityExceeded.into());
+        vault.close_session();
+        assert!(vault.open_session(2).is_ok());
+        // 0 disables the cap
+        assert!(vault.open_session(0).is_ok());
+        assert_eq!(vault.active_sessions, 3);
+    }
+
+    #[test]
+    fn test_payout_streak_disabled_without_threshold_and_limit() {
+        let mut vault = test_vault();
+        for _ in 0..10 {
+            assert!(!vault.record_payout_streak(u64::MAX, 0, 1));
+            assert!(!vault.record_payout_streak(u64::MAX, 1, 0));
+        }
+        assert_eq!(vault.consecutive_big_payouts, 0);
+        assert!(!vault.locked);
+    }
+
+    #[test]
+    fn test_house_win_resets_payout_streak() {
+        let mut vault = test_vault();
+        assert!(!vault.record_payout_streak(10, 5, 1));
+        vault.reset_payout_streak();
+        assert!(!vault.record_payout_streak(10, 5, 1));
+        assert!(vault.record_payout_streak(10, 5, 1));
+        assert!(vault.locked);
+    }
+
+    #[test]
+    fn test_set_authorized_crank() {
+        let mut vault = test_vault();
+        let crank = Pubkey::new_unique();
+        assert!(vault.crank_allowed(&Pubkey::new_unique()));
+        assert!(vault.set_authorized_crank(Some(Pubkey::default())).is_err());
+
+        vault.set_authorized_crank(Some(crank)).unwrap();
+        assert!(vault.crank_allowed(&crank));
+        assert!(!vault.crank_allowed(&Pubkey::new_unique()));
+
+        vault.set_authorized_crank(None).unwrap();
+        assert!(vault.crank_allowed(&Pubkey::new_unique()));
+    }
+
+    #[test]
+    fn test_set_cosigner_rejects_authority() {
+        let authority = Pubkey::new_unique();
+        let mut vault = HouseVault {
+            house_authority: authority,
+            ..test_vault()
+        };
+        assert!(vault.set_cosigner(Some(authority)).is_err());
+        let cosigner = Pubkey::new_unique();
+        vault.set_cosigner(Some(cosigner)).unwrap();
+        assert_eq!(vault.cosigner, Some(cosigner));
+        vault.set_cosigner(None).unwrap();
+        assert_eq!(vault.cosigner, None);
+    }
 }
@@ -0,0 +1,254 @@
+//! In-memory accounts for exercising instruction helpers without a validator
+
+use crate::states::*;
+use anchor_lang::prelude::*;
+use anchor_lang::{AccountSerialize, Discriminator};
+use solana_program::program_stubs::{self, SyscallStubs};
+use std::sync::Once;
+
+/// Slot reported by the stubbed Clock sysvar
+pub const STUB_SLOT: u64 = 1_000;
+
+/// Sysvar syscalls for running instruction handlers natively
+/// CPIs (e.g. system_program::transfer) are not supported off-chain
+struct TestSyscalls;
+
+impl SyscallStubs for TestSyscalls {
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Clock) = test_clock(STUB_SLOT) };
+        0
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe { *(var_addr as *mut Rent) = Rent::default() };
+        0
+    }
+}
+
+/// Installs the test syscalls once per test binary
+pub fn install_syscall_stubs() {
+    static INSTALL: Once = Once::new();
+    INSTALL.call_once(|| {
+        program_stubs::set_syscall_stubs(Box::new(TestSyscalls));
+    });
+}
+
+/// Owned backing storage for an `AccountInfo`
+pub struct TestAccount {
+    pub key: Pubkey,
+    pub owner: Pubkey,
+    pub lamports: u64,
+    pub data: Vec<u8>,
+    pub executable: bool,
+}
+
+impl TestAccount {
+    /// A program-owned account holding a serialized Anchor account
+    pub fn program<T: AccountSerialize + Discriminator>(value: &T, lamports: u64) -> Self {
+        let mut data = Vec::new();
+        value.try_serialize(&mut data).unwrap();
+        // Leave headroom so InitSpace-sized accounts can be re-serialized
+        data.resize(data.len() + 256, 0);
+        Self {
+            key: Pubkey::new_unique(),
+            owner: crate::ID,
+            lamports,
+            data,
+            executable: false,
+        }
+    }
+
+    /// A system-owned wallet without data
+    pub fn wallet(lamports: u64) -> Self {
+        Self {
+            key: Pubkey::new_unique(),
+            owner: Pubkey::default(),
+            lamports,
+            data: Vec::new(),
+            executable: false,
+        }
+    }
+
+    /// The System Program, for `Program<'info, System>` accounts
+    pub fn system_program() -> Self {
+        Self {
+            key: anchor_lang::system_program::ID,
+            owner: solana_program::bpf_loader::ID,
+            lamports: 1,
+            data: Vec::new(),
+            executable: true,
+        }
+    }
+
+    pub fn info(&mut self) -> AccountInfo<'_> {
+        AccountInfo::new(
+            &self.key,
+            true,
+            true,
+            &mut self.lamports,
+            &mut self.data,
+            &self.owner,
+            self.executable,
+            0,
+        )
+    }
+}
+
+pub fn test_clock(slot: u64) -> Clock {
+    Clock {
+        slot,
+        unix_timestamp: 1_700_000_000,
+        ..Clock::default()
+    }
+}
+
+/// Default economics with a configurable max_dives
+pub fn test_config(max_dives: u16) -> GameConfig {
+    let (base, decay, min, num, den, max_mult, _max_dives, min_bet, max_bet, timeout_slots) =
+        GameConfig::default_config();
+    GameConfig {
+        admin: Pubkey::default(),
+        base_survival_ppm: base,
+        decay_per_dive_ppm: decay,
+        min_survival_ppm: min,
+        treasure_multiplier_num: num,
+        treasure_multiplier_den: den,
+        max_payout_multiplier: max_mult,
+        max_dives,
+        min_bet,
+        max_bet,
+        vrf_enabled: false,
+        house_edge_bps: 0,
+        curve_mode: CurveMode::Geometric,
+        linear_step_bps: 0,
+        referral_fee_bps: 0,
+        max_exposure_bps: 1_000_000,
+        timeout_slots,
+        paused: false,
+        require_full_coverage: false,
+        pending_admin: None,
+        min_dives_to_cash_out: 0,
+        version: CONFIG_VERSION,
+        rake_bps: 0,
+        final_gamble_bps: 500_000,
+        jackpot_bps: 0,
+        difficulty: 0,
+        max_payout_lamports: 0,
+        allowed_bets: [0; 8],
+        use_bet_whitelist: false,
+        crank_reward_lamports: 0,
+        max_active_sessions_per_user: 0,
+        decay_mode: DecayMode::Linear,
+        insurance_payout_bps: 0,
+        verbose_logging: false,
+        first_dive_guaranteed: false,
+        allow_breakeven_exit: false,
+        absolute_max_dives: 0,
+        min_slots_before_play: 0,
+        loss_rebate_bps: 0,
+        bet_step: 0,
+        withdraw_delay_slots: 0,
+        max_bet_vault_bps: 0,
+        milestone_dive: 0,
+        milestone_bonus_bps: 0,
+        usd_denominated: false,
+        price_feed: Pubkey::default(),
+        max_price_age_secs: 60,
+        max_total_sessions: 0,
+        rounding_mode: RoundingMode::Floor,
+        bonus_wagering_multiple: 0,
+        max_boost_bps: 0,
+        expiry_mode: ExpiryMode::ReleaseOnly,
+        big_payout_lamports: 0,
+        max_consecutive_big_payouts: 0,
+        reservation_mode: ReservationMode::Full,
+        active_sessions: 0,
+        withdraw_delay_floor_slots: 0,
+        withdraw_delay_floor_until: 0,
+        bump: 0,
+    }
+}
+
+/// Config where every roll has the same fixed survival probability
+pub fn fixed_odds_config(survival_ppm: u32, max_dives: u16) -> GameConfig {
+    GameConfig {
+        base_survival_ppm: survival_ppm,
+        decay_per_dive_ppm: 0,
+        min_survival_ppm: survival_ppm,
+        ..test_config(max_dives)
+    }
+}
+
+/// Active session at dive 1 with a 0.01 SOL bet and 100x max payout
+pub fn test_session() -> GameSession {
+    GameSession {
+        bet_amount: 10_000_000,
+        current_treasure: 10_000_000,
+        max_payout: 1_000_000_000,
+        reserved_payout: 1_000_000_000,
+        dive_number: 1,
+        ..GameSession::blank()
+    }
+}
+
+/// Fresh stats account as created by init_if_needed
+pub fn test_user_stats() -> UserStats {
+    UserStats {
+        user: Pubkey::default(),
+        total_bet: 0,
+        total_won: 0,
+        total_lost: 0,
+        sessions_played: 0,
+        best_dive: 0,
+        best_payout: 0,
+        session_counter: 0,
+        active_session_count: 0,
+        bonus_locked_lamports: 0,
+        bonus_house_vault: Pubkey::default(),
+        bump: 0,
+    }
+}
+
+/// Fresh house stats account as created by init_if_needed
+pub fn test_house_stats() -> HouseStats {
+    HouseStats {
+        house_vault: Pubkey::default(),
+        total_volume: 0,
+        total_paid_out: 0,
+        total_sessions: 0,
+        total_losses: 0,
+        net_profit_lamports: 0,
+        bump: 0,
+    }
+}
+
+pub fn test_leaderboard() -> Leaderboard {
+    Leaderboard {
+        house_vault: Pubkey::default(),
+        entries: [LeaderboardEntry::default(); LEADERBOARD_SIZE],
+        bump: 0,
+    }
+}
+
+/// Unlocked vault holding the reservation of one test_session
+pub fn test_vault() -> HouseVault {
+    HouseVault {
+        house_authority: Pubkey::default(),
+        game_keeper: Pubkey::default(),
+        locked: false,
+        total_reserved: 1_000_000_000,
+        pending_authority: None,
+        strict_accounting: false,
+        version: VAULT_VERSION,
+        jackpot_pool: 0,
+        sequence: 0,
+        pending_withdraw_amount: 0,
+        withdraw_unlock_slot: 0,
+        cosigner: None,
+        active_sessions: 0,
+        bonus_pool: 0,
+        authorized_crank: None,
+        consecutive_big_payouts: 0,
+        bump: 0,
+    }
+}
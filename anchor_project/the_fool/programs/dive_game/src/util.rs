@@ -0,0 +1,233 @@
+use crate::errors::GameError;
+use crate::events::{FlowDirection, FlowReason, VaultFlowEvent};
+use anchor_lang::prelude::*;
+use anchor_lang::system_program;
+
+/// Moves `amount` lamports between two accounts the program may debit, e.g. out of the vault,
+/// which carries data and so can't go through system_program::transfer
+/// Both balances are computed before either is written, so a failed move changes nothing and
+/// a successful one neither creates nor destroys lamports
+pub fn transfer_lamports(from: &AccountInfo, to: &AccountInfo, amount: u64) -> Result<()> {
+    if amount == 0 {
+        return Ok(());
+    }
+    let from_balance = from
+        .lamports()
+        .checked_sub(amount)
+        .ok_or(GameError::InsufficientVaultBalance)?;
+    let to_balance = to
+        .lamports()
+        .checked_add(amount)
+        .ok_or(GameError::Overflow)?;
+    **from.try_borrow_mut_lamports()? = from_balance;
+    **to.try_borrow_mut_lamports()? = to_balance;
+    Ok(())
+}
+
+/// Ledger entry for `amount` that has just moved into or out of `vault`
+pub fn vault_flow(
+    vault: &AccountInfo,
+    direction: FlowDirection,
+    amount: u64,
+    reason: FlowReason,
+) -> VaultFlowEvent {
+    VaultFlowEvent {
+        vault: vault.key(),
+        direction,
+        amount,
+        reason,
+        balance_after: vault.lamports(),
+    }
+}
+
+/// transfer_lamports out of `vault`, returning the VaultFlowEvent for the caller to emit
+pub fn pay_from_vault(
+    vault: &AccountInfo,
+    to: &AccountInfo,
+    amount: u64,
+    reason: FlowReason,
+) -> Result<VaultFlowEvent> {
+    transfer_lamports(vault, to, amount)?;
+    Ok(vault_flow(vault, FlowDirection::Out, amount, reason))
+}
+
+/// Closes a session by moving all of its lamports to `destination`
+/// Shared by the paths that can't use Anchor's close constraint (cash_out, the loss branch
+/// of settle_round, batch cleanup); the runtime garbage collects the zero-lamport account
+pub fn close_session_to(destination: &AccountInfo, session: &AccountInfo) -> Result<()> {
+    transfer_lamports(session, destination, session.lamports())?;
+    debug_assert_eq!(session.lamports(), 0);
+    Ok(())
+}
+
+/// Creates a program-owned PDA of `space` bytes the way Anchor's `init` does
+/// An address that already holds lamports (e.g. dust sent to block it) can't go through
+/// create_account, so it is topped up to rent-exempt, allocated and assigned instead
+pub fn create_pda<'info>(
+    payer: &AccountInfo<'info>,
+    target: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    space: usize,
+    signer_seeds: &[&[u8]],
+) -> Result<()> {
+    let rent = Rent::get()?.minimum_balance(space);
+    let balance = target.lamports();
+    if balance == 0 {
+        return system_program::create_account(
+            CpiContext::new_with_signer(
+                system_program.clone(),
+                system_program::CreateAccount {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+                &[signer_seeds],
+            ),
+            rent,
+            space as u64,
+            &crate::ID,
+        );
+    }
+    let shortfall = rent.saturating_sub(balance);
+    if shortfall > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                system_program.clone(),
+                system_program::Transfer {
+                    from: payer.clone(),
+                    to: target.clone(),
+                },
+            ),
+            shortfall,
+        )?;
+    }
+    system_program::allocate(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Allocate {
+                account_to_allocate: target.clone(),
+            },
+            &[signer_seeds],
+        ),
+        space as u64,
+    )?;
+    system_program::assign(
+        CpiContext::new_with_signer(
+            system_program.clone(),
+            system_program::Assign {
+                account_to_assign: target.clone(),
+            },
+            &[signer_seeds],
+        ),
+        &crate::ID,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::TestAccount;
+
+    #[test]
+    fn test_close_moves_all_lamports() {
+        let mut user = TestAccount::wallet(5);
+        let mut session = TestAccount::wallet(2_000_000);
+        let user_info = user.info();
+        let session_info = session.info();
+
+        close_session_to(&user_info, &session_info).unwrap();
+
+        assert_eq!(user_info.lamports(), 2_000_005);
+        assert_eq!(session_info.lamports(), 0);
+    }
+
+    #[test]
+    fn test_transfer_conserves_lamports() {
+        let mut vault = TestAccount::wallet(1_000);
+        let mut user = TestAccount::wallet(50);
+        let vault_info = vault.info();
+        let user_info = user.info();
+
+        transfer_lamports(&vault_info, &user_info, 300).unwrap();
+
+        assert_eq!(vault_info.lamports(), 700);
+        assert_eq!(user_info.lamports(), 350);
+        assert_eq!(vault_info.lamports() + user_info.lamports(), 1_050);
+    }
+
+    #[test]
+    fn test_transfer_short_source_leaves_balances_untouched() {
+        let mut vault = TestAccount::wallet(100);
+        let mut user = TestAccount::wallet(50);
+        let vault_info = vault.info();
+        let user_info = user.info();
+
+        let err = transfer_lamports(&vault_info, &user_info, 101).unwrap_err();
+
+        assert_eq!(err, GameError::InsufficientVaultBalance.into());
+        assert_eq!(vault_info.lamports(), 100);
+        assert_eq!(user_info.lamports(), 50);
+    }
+
+    #[test]
+    fn test_flows_rebuild_vault_balance() {
+        let mut vault = TestAccount::wallet(10_000);
+        let mut user = TestAccount::wallet(0);
+        let vault_info = vault.info();
+        let user_info = user.info();
+        let credit = |amount: u64, reason: FlowReason| {
+            // Stands in for the system transfer CPI into the vault
+            **vault_info.try_borrow_mut_lamports().unwrap() += amount;
+            vault_flow(&vault_info, FlowDirection::In, amount, reason)
+        };
+
+        let flows = vec![
+            credit(5_000, FlowReason::Deposit),
+            credit(1_000, FlowReason::Bet),
+            pay_from_vault(&vault_info, &user_info, 2_500, FlowReason::CashOut).unwrap(),
+            credit(700, FlowReason::Insurance),
+            pay_from_vault(&vault_info, &user_info, 350, FlowReason::LossRefund).unwrap(),
+            pay_from_vault(&vault_info, &user_info, 4_000, FlowReason::Withdrawal).unwrap(),
+        ];
+
+        let net = flows.iter().fold(0i128, |net, flow| match flow.direction {
+            FlowDirection::In => net + flow.amount as i128,
+            FlowDirection::Out => net - flow.amount as i128,
+        });
+        assert_eq!(net, vault_info.lamports() as i128 - 10_000);
+        assert_eq!(flows.last().unwrap().balance_after, vault_info.lamports());
+        // Each entry's balance_after follows from the one before it
+        let mut balance = 10_000i128;
+        for flow in &flows {
+            balance += match flow.direction {
+                FlowDirection::In => flow.amount as i128,
+                FlowDirection::Out => -(flow.amount as i128),
+            };
+            assert_eq!(flow.balance_after as i128, balance);
+        }
+    }
+
+    #[test]
+    fn test_failed_payout_records_no_flow() {
+        let mut vault = TestAccount::wallet(100);
+        let mut user = TestAccount::wallet(0);
+        let vault_info = vault.info();
+        let user_info = user.info();
+
+        assert!(pay_from_vault(&vault_info, &user_info, 101, FlowReason::CashOut).is_err());
+        assert_eq!(vault_info.lamports(), 100);
+    }
+
+    #[test]
+    fn test_close_overflow_leaves_balances_untouched() {
+        let mut user = TestAccount::wallet(u64::MAX);
+        let mut session = TestAccount::wallet(1);
+        let user_info = user.info();
+        let session_info = session.info();
+
+        let err = close_session_to(&user_info, &session_info).unwrap_err();
+
+        assert_eq!(err, GameError::Overflow.into());
+        assert_eq!(user_info.lamports(), u64::MAX);
+        assert_eq!(session_info.lamports(), 1);
+    }
+}
@@ -0,0 +1,163 @@
+use crate::errors::GameError;
+use crate::rng;
+use anchor_lang::prelude::*;
+
+/// Switchboard V2 program that owns VRF accounts
+pub const SWITCHBOARD_PROGRAM_ID: Pubkey = pubkey!("SW1TCH7qEPTdLsDHRgPuMQjbQxKdH2aBStViMFnt64f");
+
+/// Byte offsets into a Switchboard V2 `VrfAccountData` (8-byte discriminator included)
+/// status: u8 | counter: u128 | authority: Pubkey | ... | current_round: VrfRound
+pub const VRF_STATUS_OFFSET: usize = 8;
+pub const VRF_AUTHORITY_OFFSET: usize = 25;
+pub const VRF_REQUEST_SLOT_OFFSET: usize = 27_726;
+pub const VRF_RESULT_OFFSET: usize = 27_742;
+
+/// VrfStatus::StatusVerified and VrfStatus::StatusCallbackSuccess
+const STATUS_VERIFIED: u8 = 3;
+const STATUS_CALLBACK_SUCCESS: u8 = 4;
+
+/// The fields of a VRF account needed to derive a round roll
+pub struct VrfRoundResult {
+    pub authority: Pubkey,
+    pub request_slot: u64,
+    pub result: [u8; 32],
+}
+
+/// The current round of a VRF account that the oracle hasn't resolved yet
+pub struct VrfRoundRequest {
+    pub authority: Pubkey,
+    pub request_slot: u64,
+}
+
+/// Reads the current VRF round while it still awaits the oracle
+/// Fails with VrfAlreadyResolved once a result exists, so a request is only recorded before
+/// anyone can see its roll
+pub fn read_vrf_request(data: &[u8]) -> Result<VrfRoundRequest> {
+    require!(data.len() >= VRF_RESULT_OFFSET + 32, GameError::VrfNotReady);
+    let status = data[VRF_STATUS_OFFSET];
+    require!(
+        status != STATUS_VERIFIED && status != STATUS_CALLBACK_SUCCESS,
+        GameError::VrfAlreadyResolved
+    );
+    Ok(VrfRoundRequest {
+        authority: read_authority(data),
+        request_slot: read_request_slot(data),
+    })
+}
+
+fn read_authority(data: &[u8]) -> Pubkey {
+    let mut authority = [0u8; 32];
+    authority.copy_from_slice(&data[VRF_AUTHORITY_OFFSET..VRF_AUTHORITY_OFFSET + 32]);
+    Pubkey::new_from_array(authority)
+}
+
+fn read_request_slot(data: &[u8]) -> u64 {
+    let mut request_slot = [0u8; 8];
+    request_slot.copy_from_slice(&data[VRF_REQUEST_SLOT_OFFSET..VRF_REQUEST_SLOT_OFFSET + 8]);
+    u64::from_le_bytes(request_slot)
+}
+
+/// Reads the resolved result of the current VRF round
+/// Fails with VrfNotReady until the oracle has verified the proof
+pub fn read_vrf_result(data: &[u8]) -> Result<VrfRoundResult> {
+    require!(data.len() >= VRF_RESULT_OFFSET + 32, GameError::VrfNotReady);
+    let status = data[VRF_STATUS_OFFSET];
+    require!(
+        status == STATUS_VERIFIED || status == STATUS_CALLBACK_SUCCESS,
+        GameError::VrfNotReady
+    );
+
+    let mut result = [0u8; 32];
+    result.copy_from_slice(&data[VRF_RESULT_OFFSET..VRF_RESULT_OFFSET + 32]);
+    require!(result != [0u8; 32], GameError::VrfNotReady);
+
+    Ok(VrfRoundResult {
+        authority: read_authority(data),
+        request_slot: read_request_slot(data),
+        result,
+    })
+}
+
+/// Maps a VRF result buffer to a roll in [0, 1_000_000)
+pub fn roll_from_vrf_result(result: &[u8; 32], dive_number: u16) -> u32 {
+    rng::random_roll_bps(result, dive_number)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vrf_data(status: u8, authority: Pubkey, request_slot: u64, result: [u8; 32]) -> Vec<u8> {
+        let mut data = vec![0u8; VRF_RESULT_OFFSET + 32 + 256];
+        data[VRF_STATUS_OFFSET] = status;
+        data[VRF_AUTHORITY_OFFSET..VRF_AUTHORITY_OFFSET + 32].copy_from_slice(authority.as_ref());
+        data[VRF_REQUEST_SLOT_OFFSET..VRF_REQUEST_SLOT_OFFSET + 8]
+            .copy_from_slice(&request_slot.to_le_bytes());
+        data[VRF_RESULT_OFFSET..VRF_RESULT_OFFSET + 32].copy_from_slice(&result);
+        data
+    }
+
+    #[test]
+    fn test_read_verified_result() {
+        let authority = Pubkey::new_unique();
+        let data = vrf_data(STATUS_VERIFIED, authority, 55, [9u8; 32]);
+        let round = read_vrf_result(&data).unwrap();
+        assert_eq!(round.authority, authority);
+        assert_eq!(round.request_slot, 55);
+        assert_eq!(round.result, [9u8; 32]);
+    }
+
+    #[test]
+    fn test_callback_success_is_ready() {
+        let data = vrf_data(STATUS_CALLBACK_SUCCESS, Pubkey::new_unique(), 1, [1u8; 32]);
+        assert!(read_vrf_result(&data).is_ok());
+    }
+
+    #[test]
+    fn test_requesting_status_not_ready() {
+        let data = vrf_data(1, Pubkey::new_unique(), 1, [1u8; 32]);
+        assert!(read_vrf_result(&data).is_err());
+    }
+
+    #[test]
+    fn test_empty_result_not_ready() {
+        let data = vrf_data(STATUS_VERIFIED, Pubkey::new_unique(), 1, [0u8; 32]);
+        assert!(read_vrf_result(&data).is_err());
+    }
+
+    #[test]
+    fn test_request_reads_only_before_result() {
+        let authority = Pubkey::new_unique();
+        let request = read_vrf_request(&vrf_data(1, authority, 55, [0u8; 32])).unwrap();
+        assert_eq!(request.authority, authority);
+        assert_eq!(request.request_slot, 55);
+
+        let err = read_vrf_request(&vrf_data(STATUS_VERIFIED, authority, 55, [9u8; 32]))
+            .err()
+            .unwrap();
+        assert_eq!(err, GameError::VrfAlreadyResolved.into());
+    }
+
+    #[test]
+    fn test_truncated_account_not_ready() {
+        assert!(read_vrf_result(&[0u8; 64]).is_err());
+    }
+
+    #[test]
+    fn test_result_to_bps_matches_rng() {
+        let result = [77u8; 32];
+        for dive in 1..=20 {
+            let roll = roll_from_vrf_result(&result, dive);
+            assert_eq!(roll, rng::random_roll_bps(&result, dive));
+            assert!(roll < 1_000_000);
+        }
+    }
+
+    #[test]
+    fn test_result_to_bps_depends_on_result() {
+        assert_ne!(
+            roll_from_vrf_result(&[1u8; 32], 1),
+            roll_from_vrf_result(&[2u8; 32], 1)
+        );
+    }
+}
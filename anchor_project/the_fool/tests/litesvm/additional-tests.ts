@@ -1821,7 +1821,7 @@ describe("LiteSVM Additional Tests - Comprehensive Coverage", () => {
       const configData = buildInitConfigData({
         fixedBet: new BN(10_000_000), // 0.01 SOL
         baseSurvivalPpm: 1, // 0.0001% survival - almost guaranteed death
-        decayPerDivePpm: 0, // No decay needed
+        decayPerDivePpm: 1, // Minimal decay (validate requires > 0)
         minSurvivalPpm: 1,
       });
       const configIx = new TransactionInstruction({